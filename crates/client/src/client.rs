@@ -1,132 +1,1408 @@
-use crate::{MojaveClientError, types::SignedBlock};
-use ethrex_common::types::Block;
+use crate::{ForwardTransactionError, MojaveClientError, types::SignedBlock};
+use ethrex_common::{H256, types::Block};
 use ethrex_rpc::{
     clients::eth::RpcResponse,
     utils::{RpcRequest, RpcRequestId},
 };
-use futures::{
-    FutureExt,
-    future::{Fuse, select_ok},
+use futures::{StreamExt, future::join_all, stream::FuturesUnordered};
+use mojave_signature::AnySigningKey;
+use rand::Rng;
+use reqwest::{
+    Url,
+    header::{AUTHORIZATION, HeaderMap, HeaderValue},
 };
-use mojave_signature::{Signature, Signer, SigningKey};
-use reqwest::Url;
+use serde::Serialize;
 use serde_json::json;
-use std::{pin::Pin, str::FromStr, sync::Arc};
+use std::{
+    collections::HashMap,
+    fmt,
+    str::FromStr,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tracing::Instrument;
+
+/// Timeout of the requests to the urls without an override.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Timeout of the connection to a url, part of the request timeout.
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How a transaction is forwarded when several sequencers are configured.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ForwardStrategy {
+    /// Tries the sequencers one after the other until one accepts the transaction.
+    #[default]
+    FirstSuccess,
+    /// Sends the transaction to every sequencer, succeeding if at least one accepts it.
+    All,
+}
+
+impl FromStr for ForwardStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "first-success" => Ok(Self::FirstSuccess),
+            "all" => Ok(Self::All),
+            other => Err(format!(
+                "unknown forward strategy {other}, expected first-success or all"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ForwardStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FirstSuccess => write!(f, "first-success"),
+            Self::All => write!(f, "all"),
+        }
+    }
+}
+
+/// Order in which the urls are tried by the requests sent to one url at a time, e.g. the
+/// transactions forwarded with [`ForwardStrategy::FirstSuccess`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UrlSelection {
+    /// Always starts from the first address, the others being fallbacks.
+    #[default]
+    Ordered,
+    /// Starts each request from the address following the one the previous request started
+    /// from, spreading the load over all of them.
+    RoundRobin,
+    /// Starts each request from a random address.
+    Random,
+}
+
+impl FromStr for UrlSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ordered" => Ok(Self::Ordered),
+            "round-robin" => Ok(Self::RoundRobin),
+            "random" => Ok(Self::Random),
+            other => Err(format!(
+                "unknown url selection {other}, expected ordered, round-robin or random"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for UrlSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ordered => write!(f, "ordered"),
+            Self::RoundRobin => write!(f, "round-robin"),
+            Self::Random => write!(f, "random"),
+        }
+    }
+}
+
+/// Timeouts and headers of the requests sent by a [`MojaveClient`], e.g. a shorter timeout for
+/// the peers on the same LAN than for the ones across a WAN.
+#[derive(Clone, Debug)]
+pub struct ClientConfig {
+    /// Timeout of the requests to the urls without an override, from the connection to the end
+    /// of the response.
+    pub request_timeout: Duration,
+    /// Timeout of the connection to any url, so that an unreachable peer fails fast.
+    pub connect_timeout: Duration,
+    /// Timeouts overriding `request_timeout` for the requests to the given urls, which must be among
+    /// the addresses of the client.
+    pub url_timeouts: HashMap<String, Duration>,
+    /// Overall deadline of a request sent to several urls, after which the remaining attempts
+    /// are aborted. Unbounded by default, each attempt still being bounded by its timeout.
+    pub retry_budget: Option<Duration>,
+    /// Headers sent along with every request, e.g. to authenticate to a gateway in front of the
+    /// full nodes. The values marked sensitive are redacted from the `Debug` output.
+    pub headers: HeaderMap,
+    pub url_selection: UrlSelection,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            url_timeouts: HashMap::new(),
+            retry_budget: None,
+            headers: HeaderMap::new(),
+            url_selection: UrlSelection::default(),
+        }
+    }
+}
+
+impl ClientConfig {
+    /// Authenticates every request with the given bearer token, which is never logged.
+    pub fn with_bearer_token(mut self, token: &str) -> Result<Self, MojaveClientError> {
+        let mut value = HeaderValue::from_str(&format!("Bearer {token}"))
+            .map_err(|_| MojaveClientError::InvalidHeaderValue(AUTHORIZATION.to_string()))?;
+        value.set_sensitive(true);
+        self.headers.insert(AUTHORIZATION, value);
+        Ok(self)
+    }
+}
+
+/// How many full nodes must accept a broadcast block for the broadcast to succeed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BroadcastMode {
+    /// Succeeds as soon as one full node accepts the block, without waiting for the others.
+    #[default]
+    FirstOk,
+    /// Succeeds once the given number of full nodes accepted the block, e.g. a majority so that
+    /// a partitioned subset of them does not go unnoticed.
+    Quorum(usize),
+    /// Succeeds once every full node accepted the block.
+    All,
+}
+
+impl FromStr for BroadcastMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "first-ok" => Ok(Self::FirstOk),
+            "all" => Ok(Self::All),
+            other => match other.strip_prefix("quorum:").map(str::parse::<usize>) {
+                Some(Ok(quorum)) if quorum > 0 => Ok(Self::Quorum(quorum)),
+                _ => Err(format!(
+                    "unknown broadcast mode {other}, expected first-ok, all or quorum:<number of full nodes>"
+                )),
+            },
+        }
+    }
+}
+
+impl fmt::Display for BroadcastMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FirstOk => write!(f, "first-ok"),
+            Self::Quorum(quorum) => write!(f, "quorum:{quorum}"),
+            Self::All => write!(f, "all"),
+        }
+    }
+}
+
+/// Outcome of a broadcast for each full node, in the order of the addresses of the client.
+#[derive(Debug)]
+pub struct BroadcastReport {
+    pub outcomes: Vec<BroadcastOutcome>,
+}
+
+#[derive(Debug)]
+pub struct BroadcastOutcome {
+    pub url: String,
+    /// `None` if the broadcast succeeded or ran out of budget before this full node answered.
+    pub result: Option<Result<(), MojaveClientError>>,
+}
+
+impl BroadcastReport {
+    /// Number of full nodes that accepted the block.
+    pub fn accepted(&self) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|outcome| matches!(outcome.result, Some(Ok(()))))
+            .count()
+    }
+
+    /// Errors of the full nodes that refused the block or could not be reached.
+    pub fn errors(&self) -> Vec<String> {
+        self.outcomes
+            .iter()
+            .filter_map(|outcome| match &outcome.result {
+                Some(Err(error)) => Some(format!("{}: {error}", outcome.url)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Broadcast figures of a full node since the client was created.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BroadcastStats {
+    pub url: String,
+    /// Number of broadcast blocks the full node accepted.
+    pub acks: u64,
+    /// Number of broadcast blocks the full node refused or could not be sent.
+    pub failures: u64,
+    /// UNIX time in milliseconds of the last block the full node accepted.
+    pub last_success: Option<u64>,
+}
+
+/// How a request failing on a connection error or a 5xx response is retried on the same url,
+/// waiting `base_delay * 2^retry` capped at `max_delay` between two attempts.
+///
+/// A retried request may be processed twice if its first attempt reached the peer before
+/// failing. Broadcasting a block again is harmless, the full node rejecting the replayed epoch,
+/// so broadcasts are always retried. Forwarding a transaction again could submit it twice, so
+/// forwarded transactions are only retried with `retry_transactions`.
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Waits a random delay between half and all of the computed one, so that the clients
+    /// retrying at once spread their retries.
+    pub jitter: bool,
+    pub retry_transactions: bool,
+}
+
+impl RetryPolicy {
+    /// Never retries, the policy of a client unless set with [`MojaveClient::with_retry`].
+    pub const NONE: Self = Self {
+        max_retries: 0,
+        base_delay: Duration::ZERO,
+        max_delay: Duration::ZERO,
+        jitter: false,
+        retry_transactions: false,
+    };
+
+    /// Delay before the retry number `retry`, counting from 0.
+    fn delay(&self, retry: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        if self.jitter && !delay.is_zero() {
+            rand::thread_rng().gen_range(delay / 2..=delay)
+        } else {
+            delay
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(2),
+            jitter: true,
+            retry_transactions: false,
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct MojaveClient {
     inner: Arc<MojaveClientInner>,
+    retry_policy: RetryPolicy,
 }
 
 #[derive(Debug)]
 struct MojaveClientInner {
     client: reqwest::Client,
     urls: Vec<Url>,
-    signing_key: SigningKey,
+    request_timeout: Duration,
+    url_timeouts: HashMap<Url, Duration>,
+    retry_budget: Option<Duration>,
+    signing_key: Option<AnySigningKey>,
+    last_epoch: AtomicU64,
+    next_request_id: AtomicU64,
+    url_selection: UrlSelection,
+    /// Index of the url the next round-robin request starts from.
+    next_url: AtomicUsize,
+    /// Broadcast figures of each url, in the order of `urls`.
+    broadcast_stats: Mutex<Vec<BroadcastStats>>,
 }
 
 impl MojaveClient {
     pub fn new(
         full_node_addresses: &[String],
-        private_key: &str,
+        signing_key: AnySigningKey,
     ) -> Result<Self, MojaveClientError> {
-        let urls = full_node_addresses
+        Self::with_config(
+            full_node_addresses,
+            Some(signing_key),
+            ClientConfig::default(),
+        )
+    }
+
+    /// Returns a client that cannot sign, e.g. for a full node forwarding transactions to the
+    /// sequencers.
+    pub fn without_signing_key(addresses: &[String]) -> Result<Self, MojaveClientError> {
+        Self::with_config(addresses, None, ClientConfig::default())
+    }
+
+    /// Returns a client sending its requests with the timeouts of `config`, signing blocks only
+    /// if given a signing key.
+    pub fn with_config(
+        addresses: &[String],
+        signing_key: Option<AnySigningKey>,
+        config: ClientConfig,
+    ) -> Result<Self, MojaveClientError> {
+        let parse_url = |url: &String| {
+            let url = Url::parse(url)
+                .map_err(|error| MojaveClientError::ParseUrlError(error.to_string()))?;
+            match url.scheme() {
+                "http" | "https" => Ok(url),
+                _ => Err(MojaveClientError::UnsupportedScheme(url.to_string())),
+            }
+        };
+        // An address listed twice would be sent every request twice.
+        let mut urls: Vec<Url> = Vec::with_capacity(addresses.len());
+        for url in addresses.iter().map(parse_url) {
+            let url = url?;
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+        let url_timeouts = config
+            .url_timeouts
+            .iter()
+            .map(|(url, timeout)| {
+                let url = parse_url(url)?;
+                if !urls.contains(&url) {
+                    return Err(MojaveClientError::ParseUrlError(format!(
+                        "{url} has a timeout override but is not among the addresses"
+                    )));
+                }
+                Ok((url, *timeout))
+            })
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        let broadcast_stats = urls
             .iter()
-            .map(|url| {
-                Url::parse(url).map_err(|error| MojaveClientError::ParseUrlError(error.to_string()))
+            .map(|url| BroadcastStats {
+                url: url.to_string(),
+                ..BroadcastStats::default()
             })
-            .collect::<Result<Vec<_>, _>>()?;
-        let signing_key = SigningKey::from_str(private_key)?;
+            .collect();
         Ok(Self {
             inner: Arc::new(MojaveClientInner {
-                client: reqwest::Client::new(),
+                client: reqwest::Client::builder()
+                    .connect_timeout(config.connect_timeout)
+                    .default_headers(config.headers)
+                    .build()?,
                 urls,
+                request_timeout: config.request_timeout,
+                url_timeouts,
+                retry_budget: config.retry_budget,
                 signing_key,
+                last_epoch: AtomicU64::new(0),
+                next_request_id: AtomicU64::new(1),
+                url_selection: config.url_selection,
+                next_url: AtomicUsize::new(0),
+                broadcast_stats: Mutex::new(broadcast_stats),
             }),
+            retry_policy: RetryPolicy::NONE,
         })
     }
 
-    /// Sends multiple RPC requests to a list of urls and returns
-    /// the first response without waiting for others to finish.
-    async fn send_request_race(
+    /// Retries the failed requests following `policy`, see [`RetryPolicy`].
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Sends the broadcast request to every url at once and returns as soon as `required` of
+    /// them accepted it, without waiting for the others to answer.
+    ///
+    /// Once the retry budget is exceeded, the pending requests are aborted and the errors of
+    /// the urls that already failed are returned.
+    async fn send_broadcast_request(
         &self,
         request: RpcRequest,
+        required: usize,
+    ) -> Result<BroadcastReport, MojaveClientError> {
+        let urls = &self.inner.urls;
+        if required > urls.len() {
+            return Err(MojaveClientError::Custom(format!(
+                "A quorum of {required} full nodes exceeds the {} configured ones",
+                urls.len()
+            )));
+        }
+
+        let request = &request;
+        let mut results: Vec<_> = urls.iter().map(|_| None).collect();
+        let mut accepted = 0;
+        let broadcast = async {
+            let mut requests: FuturesUnordered<_> = urls
+                .iter()
+                .enumerate()
+                .map(|(index, url)| async move {
+                    let response = self.send_request_to_url(url, request, true).await;
+                    (index, broadcast_result(response))
+                })
+                .collect();
+            while accepted < required {
+                let Some((index, result)) = requests.next().await else {
+                    break;
+                };
+                accepted += usize::from(result.is_ok());
+                results[index] = Some(result);
+            }
+        };
+
+        let exceeded_budget = match self.inner.retry_budget {
+            Some(budget) => tokio::time::timeout(budget, broadcast)
+                .await
+                .err()
+                .map(|_| budget),
+            None => {
+                broadcast.await;
+                None
+            }
+        };
+        self.record_broadcast(results.iter().map(Option::as_ref));
+        let report = BroadcastReport {
+            outcomes: urls
+                .iter()
+                .zip(results)
+                .map(|(url, result)| BroadcastOutcome {
+                    url: url.to_string(),
+                    result,
+                })
+                .collect(),
+        };
+        match exceeded_budget {
+            _ if accepted >= required => Ok(report),
+            Some(budget) => Err(MojaveClientError::RetryBudgetExceeded {
+                budget,
+                errors: report.errors(),
+            }),
+            None => Err(MojaveClientError::QuorumNotReached { required, report }),
+        }
+    }
+
+    /// Sends the given RPC request to all configured URLs at once and returns the outcome for
+    /// each of them, in the order of the addresses of the client.
+    pub async fn send_request_all(
+        &self,
+        request: RpcRequest,
+    ) -> Vec<(Url, Result<RpcResponse, MojaveClientError>)> {
+        let request = &request;
+        join_all(self.inner.urls.iter().map(|url| async move {
+            (
+                url.clone(),
+                self.send_request_to_url(url, request, true).await,
+            )
+        }))
+        .await
+    }
+
+    /// Sends the request, retrying it following the retry policy if `retriable`.
+    async fn send_request_to_url(
+        &self,
+        url: &Url,
+        request: &RpcRequest,
+        retriable: bool,
     ) -> Result<RpcResponse, MojaveClientError> {
-        let requests: Vec<Pin<Box<Fuse<_>>>> = self
-            .inner
-            .urls
-            .iter()
-            .map(|url| Box::pin(self.send_request_to_url(url, &request).fuse()))
-            .collect();
+        let body = serde_json::ser::to_string(&request).map_err(|error| {
+            MojaveClientError::FailedToSerializeRequestBody(format!("{error}: {request:?}"))
+        })?;
+        let max_retries = if retriable {
+            self.retry_policy.max_retries
+        } else {
+            0
+        };
+        let mut retry = 0;
+        loop {
+            match self.send_body_to_url(url, body.clone()).await {
+                Err(error) if retry < max_retries && is_retriable(&error) => {
+                    let delay = self.retry_policy.delay(retry);
+                    tracing::debug!(%url, %error, retry, ?delay, "Retrying the request");
+                    tokio::time::sleep(delay).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn send_body_to_url(
+        &self,
+        url: &Url,
+        body: String,
+    ) -> Result<RpcResponse, MojaveClientError> {
+        let timeout = self.timeout_for(url);
+        let response = async {
+            let response = self
+                .inner
+                .client
+                .post(url.as_ref())
+                .timeout(timeout)
+                .header("content-type", "application/json")
+                .body(body)
+                .send()
+                .await?;
+            if response.status().is_server_error() {
+                return Ok(Err(MojaveClientError::ServerError {
+                    url: url.to_string(),
+                    status: response.status().as_u16(),
+                }));
+            }
+            response.json::<RpcResponse>().await.map(Ok)
+        };
+        response.await.map_err(|error| {
+            if error.is_timeout() {
+                MojaveClientError::Timeout {
+                    url: url.to_string(),
+                    timeout,
+                }
+            } else {
+                MojaveClientError::from(error)
+            }
+        })?
+    }
 
-        let (response, _) = select_ok(requests)
+    /// Returns the urls in the order the next request sent to one url at a time tries them,
+    /// following the url selection of the client.
+    fn select_urls(&self) -> impl Iterator<Item = &Url> {
+        let urls = &self.inner.urls;
+        let start = match self.inner.url_selection {
+            UrlSelection::Ordered => 0,
+            UrlSelection::RoundRobin => self.inner.next_url.fetch_add(1, Ordering::Relaxed),
+            UrlSelection::Random => rand::thread_rng().gen_range(0..urls.len().max(1)),
+        };
+        urls.iter()
+            .cycle()
+            .skip(start % urls.len().max(1))
+            .take(urls.len())
+    }
+
+    fn timeout_for(&self, url: &Url) -> Duration {
+        self.inner
+            .url_timeouts
+            .get(url)
+            .copied()
+            .unwrap_or(self.inner.request_timeout)
+    }
+
+    /// Broadcasts the block to the full nodes, succeeding once as many of them as `mode`
+    /// requires accepted it.
+    pub async fn send_broadcast_block(
+        &self,
+        block: &Block,
+        mode: BroadcastMode,
+    ) -> Result<BroadcastReport, MojaveClientError> {
+        let request = self.broadcast_block_request(block)?;
+        let required = match mode {
+            BroadcastMode::FirstOk => 1,
+            BroadcastMode::Quorum(quorum) => quorum,
+            BroadcastMode::All => self.inner.urls.len(),
+        };
+        let span = tracing::info_span!("broadcast_block", request_id = ?request.id);
+        self.send_broadcast_request(request, required)
+            .instrument(span)
             .await
-            .map_err(|error| MojaveClientError::Custom(format!("All RPC calls failed: {error}")))?;
-        Ok(response)
     }
 
-    /// Sends the given RPC request to all configured URLs sequentially.
-    /// Returns the response from the first successful request, or the last error if all requests fail.
-    #[allow(unused)]
-    async fn send_request(&self, request: RpcRequest) -> Result<RpcResponse, MojaveClientError> {
-        let mut response = Err(MojaveClientError::Custom(
-            "All rpc calls failed".to_string(),
-        ));
+    /// Broadcasts the block to every full node and waits for all of them to answer, returning
+    /// whether each of them accepted it. Unlike [`MojaveClient::send_broadcast_block`], this
+    /// ignores the retry budget.
+    pub async fn send_broadcast_block_detailed(
+        &self,
+        block: &Block,
+    ) -> Result<Vec<(Url, Result<(), MojaveClientError>)>, MojaveClientError> {
+        let request = self.broadcast_block_request(block)?;
+        let span = tracing::info_span!("broadcast_block", request_id = ?request.id);
+        let results: Vec<_> = self
+            .send_request_all(request)
+            .instrument(span)
+            .await
+            .into_iter()
+            .map(|(url, response)| (url, broadcast_result(response)))
+            .collect();
+        self.record_broadcast(results.iter().map(|(_, result)| Some(result)));
+        Ok(results)
+    }
 
-        for url in self.inner.urls.iter() {
-            let maybe_response = self.send_request_to_url(url, &request).await;
-            if maybe_response.is_ok() {
-                response = maybe_response;
+    /// Returns the broadcast figures of every full node, in the order of the addresses of the
+    /// client.
+    pub fn broadcast_stats(&self) -> Vec<BroadcastStats> {
+        self.inner
+            .broadcast_stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Adds the results of a broadcast, one per url in the order of the urls, to the figures of
+    /// the full nodes. A full node that did not answer, `None`, is left as it is.
+    fn record_broadcast<'a>(
+        &self,
+        results: impl Iterator<Item = Option<&'a Result<(), MojaveClientError>>>,
+    ) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or_default();
+        let mut stats = self
+            .inner
+            .broadcast_stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (stats, result) in stats.iter_mut().zip(results) {
+            match result {
+                Some(Ok(())) => {
+                    stats.acks += 1;
+                    stats.last_success = Some(now);
+                }
+                Some(Err(_)) => stats.failures += 1,
+                None => {}
             }
         }
-        response
     }
 
-    async fn send_request_to_url(
+    /// Builds the `mojave_sendBroadcastBlock` request carrying the block signed with the
+    /// sequencer's key.
+    fn broadcast_block_request(&self, block: &Block) -> Result<RpcRequest, MojaveClientError> {
+        let signing_key = self
+            .inner
+            .signing_key
+            .as_ref()
+            .ok_or(MojaveClientError::MissingSigningKey)?;
+        let params = SignedBlock::new(block.clone(), self.next_epoch(), signing_key)?;
+
+        Ok(RpcRequest {
+            id: RpcRequestId::Number(self.next_id()),
+            jsonrpc: "2.0".to_string(),
+            method: "mojave_sendBroadcastBlock".to_string(),
+            params: Some(vec![json!(params)]),
+        })
+    }
+
+    /// Forwards a raw transaction to the sequencers following the given strategy and returns
+    /// its hash.
+    pub async fn send_forward_transaction(
+        &self,
+        raw_transaction: &[u8],
+        strategy: ForwardStrategy,
+    ) -> Result<H256, ForwardTransactionError> {
+        let request_id = self.next_id();
+        let request = RpcRequest {
+            id: RpcRequestId::Number(request_id),
+            jsonrpc: "2.0".to_string(),
+            method: "eth_sendRawTransaction".to_string(),
+            params: Some(vec![json!(format!("0x{}", hex::encode(raw_transaction)))]),
+        };
+        self.forward_transaction(&request, strategy)
+            .instrument(tracing::info_span!("forward_transaction", request_id))
+            .await
+    }
+
+    async fn forward_transaction(
+        &self,
+        request: &RpcRequest,
+        strategy: ForwardStrategy,
+    ) -> Result<H256, ForwardTransactionError> {
+        let mut last_error =
+            ForwardTransactionError::RPCError("No sequencer to forward to".to_owned());
+        match strategy {
+            ForwardStrategy::FirstSuccess => {
+                for url in self.select_urls() {
+                    match self.forward_to_url(url, request).await {
+                        Ok(tx_hash) => return Ok(tx_hash),
+                        Err(error) => {
+                            tracing::warn!(%url, %error, "Failed to forward the transaction");
+                            last_error = error;
+                        }
+                    }
+                }
+                Err(last_error)
+            }
+            ForwardStrategy::All => {
+                let results = join_all(
+                    self.inner
+                        .urls
+                        .iter()
+                        .map(|url| self.forward_to_url(url, request)),
+                )
+                .await;
+                let mut tx_hash = None;
+                for (url, result) in self.inner.urls.iter().zip(results) {
+                    match result {
+                        Ok(hash) => tx_hash = Some(hash),
+                        Err(error) => {
+                            tracing::warn!(%url, %error, "Failed to forward the transaction");
+                            last_error = error;
+                        }
+                    }
+                }
+                tx_hash.ok_or(last_error)
+            }
+        }
+    }
+
+    async fn forward_to_url(
         &self,
         url: &Url,
         request: &RpcRequest,
-    ) -> Result<RpcResponse, MojaveClientError> {
-        self.inner
-            .client
-            .post(url.as_ref())
-            .header("content-type", "application/json")
-            .body(serde_json::ser::to_string(&request).map_err(|error| {
-                MojaveClientError::FailedToSerializeRequestBody(format!("{error}: {request:?}"))
-            })?)
-            .send()
-            .await?
-            .json::<RpcResponse>()
+    ) -> Result<H256, ForwardTransactionError> {
+        let retriable = self.retry_policy.retry_transactions;
+        match self.send_request_to_url(url, request, retriable).await? {
+            RpcResponse::Success(result) => Ok(serde_json::from_value(result.result)?),
+            RpcResponse::Error(error_response) => Err(ForwardTransactionError::RPCError(
+                error_response.error.message,
+            )),
+        }
+    }
+
+    /// Returns the id of the next request, unique among the requests of the client and its
+    /// clones so that the responses can be told apart in the logs.
+    pub fn next_id(&self) -> u64 {
+        self.inner.next_request_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Returns the epoch of the next broadcast: the current UNIX time in milliseconds, bumped
+    /// if needed so that it is strictly greater than the previous one. Being time based, it
+    /// keeps increasing across restarts of the sequencer.
+    fn next_epoch(&self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or_default();
+        let previous = self
+            .inner
+            .last_epoch
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+                Some(now.max(last + 1))
+            })
+            .unwrap_or_default();
+        now.max(previous + 1)
+    }
+}
+
+/// Connection errors and 5xx responses are likely transient, unlike a timeout whose request may
+/// still be processed by the peer.
+fn is_retriable(error: &MojaveClientError) -> bool {
+    match error {
+        MojaveClientError::ReqwestError(error) => error.is_connect(),
+        MojaveClientError::ServerError { .. } => true,
+        _ => false,
+    }
+}
+
+/// Whether a full node accepted a broadcast block, given its answer.
+fn broadcast_result(
+    response: Result<RpcResponse, MojaveClientError>,
+) -> Result<(), MojaveClientError> {
+    match response? {
+        RpcResponse::Success(result) => {
+            serde_json::from_value(result.result).map_err(MojaveClientError::from)
+        }
+        RpcResponse::Error(error_response) => {
+            Err(MojaveClientError::RpcError(error_response.error.message))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethrex_common::types::{BlockBody, BlockHeader};
+    use mojave_signature::SignatureScheme;
+
+    const PRIVATE_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    fn client() -> MojaveClient {
+        let signing_key =
+            AnySigningKey::from_str_with_scheme(SignatureScheme::Secp256k1, PRIVATE_KEY).unwrap();
+        MojaveClient::new(&["http://127.0.0.1:8545".to_owned()], signing_key).unwrap()
+    }
+
+    #[test]
+    fn broadcast_block_request_carries_a_valid_signed_block() {
+        let client = client();
+        let block = Block::new(
+            BlockHeader {
+                number: 1,
+                ..Default::default()
+            },
+            BlockBody {
+                transactions: vec![],
+                ommers: vec![],
+                withdrawals: None,
+            },
+        );
+
+        let request = client.broadcast_block_request(&block).unwrap();
+        assert_eq!(request.method, "mojave_sendBroadcastBlock");
+
+        let params = request.params.unwrap();
+        assert_eq!(params.len(), 1);
+        let signed_block: SignedBlock = serde_json::from_value(params[0].clone()).unwrap();
+        assert_eq!(signed_block.block.hash(), block.hash());
+        signed_block.verify().unwrap();
+    }
+
+    #[tokio::test]
+    async fn bearer_token_is_sent_but_not_logged() {
+        let accepting = mock_sequencer("/authenticated", true)
+            .match_header("authorization", "Bearer s3cr3t")
+            .expect(1)
+            .create();
+        let config = ClientConfig::default().with_bearer_token("s3cr3t").unwrap();
+        assert!(!format!("{config:?}").contains("s3cr3t"));
+
+        let url = format!("{}/authenticated", mockito::server_url());
+        let client = MojaveClient::with_config(&[url], None, config).unwrap();
+        assert!(!format!("{client:?}").contains("s3cr3t"));
+        client
+            .send_forward_transaction(RAW_TRANSACTION, ForwardStrategy::FirstSuccess)
             .await
-            .map_err(MojaveClientError::from)
+            .unwrap();
+        accepting.assert();
+
+        assert!(matches!(
+            ClientConfig::default().with_bearer_token("line\nbreak"),
+            Err(MojaveClientError::InvalidHeaderValue(_))
+        ));
     }
 
-    pub async fn send_broadcast_block(&self, block: &Block) -> Result<(), MojaveClientError> {
-        let hash = block.hash();
-        let signature: Signature = self.inner.signing_key.sign(&hash)?;
-        let verifying_key = self.inner.signing_key.verifying_key();
+    #[test]
+    fn duplicate_addresses_are_only_kept_once() {
+        let addresses = [
+            "http://127.0.0.1:8545".to_owned(),
+            "http://127.0.0.2:8545".to_owned(),
+            // Parsed to the same url as the first address.
+            "http://127.0.0.1:8545/".to_owned(),
+        ];
+        let client = MojaveClient::without_signing_key(&addresses).unwrap();
+        let urls: Vec<_> = client.inner.urls.iter().map(Url::as_str).collect();
+        assert_eq!(urls, ["http://127.0.0.1:8545/", "http://127.0.0.2:8545/"]);
+
+        let client = MojaveClient::without_signing_key(&[]).unwrap();
+        assert!(client.inner.urls.is_empty());
+    }
 
-        let params = SignedBlock {
-            block: block.clone(),
-            signature,
-            verifying_key,
+    #[test]
+    fn addresses_must_be_http_urls() {
+        for address in ["ws://127.0.0.1:8546", "file:///tmp/node.ipc"] {
+            assert!(matches!(
+                MojaveClient::without_signing_key(&[address.to_owned()]),
+                Err(MojaveClientError::UnsupportedScheme(_))
+            ));
+        }
+        assert!(MojaveClient::without_signing_key(&["https://node.example".to_owned()]).is_ok());
+    }
+
+    #[test]
+    fn round_robin_rotates_the_first_url() {
+        let addresses = ["a", "b", "c"].map(|host| format!("http://{host}:8545"));
+        let config = ClientConfig {
+            url_selection: UrlSelection::RoundRobin,
+            ..ClientConfig::default()
         };
+        let client = MojaveClient::with_config(&addresses, None, config).unwrap();
+
+        let selections: Vec<Vec<_>> = (0..4)
+            .map(|_| {
+                client
+                    .select_urls()
+                    .map(|url| url.host_str().unwrap().to_owned())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            selections,
+            [
+                ["a", "b", "c"],
+                ["b", "c", "a"],
+                ["c", "a", "b"],
+                ["a", "b", "c"]
+            ]
+        );
+
+        let ordered = MojaveClient::without_signing_key(&addresses).unwrap();
+        for _ in 0..2 {
+            let first = ordered.select_urls().next().unwrap();
+            assert_eq!(first.host_str(), Some("a"));
+        }
+        let random = MojaveClient::with_config(
+            &addresses,
+            None,
+            ClientConfig {
+                url_selection: UrlSelection::Random,
+                ..ClientConfig::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(random.select_urls().count(), 3);
+    }
+
+    #[test]
+    fn request_ids_increase_across_clones() {
+        let client = client();
+        let clone = client.clone();
+        let block = Block::new(BlockHeader::default(), BlockBody::default());
+
+        let first = client.broadcast_block_request(&block).unwrap();
+        let second = clone.broadcast_block_request(&block).unwrap();
+        assert!(matches!(first.id, RpcRequestId::Number(1)));
+        assert!(matches!(second.id, RpcRequestId::Number(2)));
+        assert_eq!(client.next_id(), 3);
+    }
+
+    const RAW_TRANSACTION: &[u8] = &[0x02, 0xf8, 0x01];
+
+    fn mock_sequencer(path: &str, accepts: bool) -> mockito::Mock {
+        let body = if accepts {
+            format!(
+                r#"{{"jsonrpc":"2.0","id":1,"result":"{:#x}"}}"#,
+                H256::repeat_byte(1)
+            )
+        } else {
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"nonce too low"}}"#
+                .to_owned()
+        };
+        mockito::mock("POST", path)
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "method": "eth_sendRawTransaction",
+                "params": [format!("0x{}", hex::encode(RAW_TRANSACTION))],
+            })))
+            .with_header("content-type", "application/json")
+            .with_body(body)
+    }
+
+    fn forwarder() -> MojaveClient {
+        let url = mockito::server_url();
+        MojaveClient::without_signing_key(&[format!("{url}/first"), format!("{url}/second")])
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn first_success_stops_at_the_first_accepting_sequencer() {
+        let first = mock_sequencer("/first", false).expect(1).create();
+        let second = mock_sequencer("/second", true).expect(1).create();
+
+        let tx_hash = forwarder()
+            .send_forward_transaction(RAW_TRANSACTION, ForwardStrategy::FirstSuccess)
+            .await
+            .unwrap();
+        assert_eq!(tx_hash, H256::repeat_byte(1));
+        first.assert();
+        second.assert();
+        drop((first, second));
+
+        // Once the first sequencer accepts the transaction, it is not sent to the second one.
+        let first = mock_sequencer("/first", true).expect(1).create();
+        let second = mock_sequencer("/second", true).expect(0).create();
+        forwarder()
+            .send_forward_transaction(RAW_TRANSACTION, ForwardStrategy::FirstSuccess)
+            .await
+            .unwrap();
+        first.assert();
+        second.assert();
+    }
+
+    #[tokio::test]
+    async fn all_sends_to_every_sequencer() {
+        let first = mock_sequencer("/first", false).expect(1).create();
+        let second = mock_sequencer("/second", true).expect(1).create();
+
+        let tx_hash = forwarder()
+            .send_forward_transaction(RAW_TRANSACTION, ForwardStrategy::All)
+            .await
+            .unwrap();
+        assert_eq!(tx_hash, H256::repeat_byte(1));
+        first.assert();
+        second.assert();
+        drop((first, second));
+
+        let _first = mock_sequencer("/first", false).create();
+        let _second = mock_sequencer("/second", false).create();
+        let result = forwarder()
+            .send_forward_transaction(RAW_TRANSACTION, ForwardStrategy::All)
+            .await;
+        assert!(matches!(result, Err(ForwardTransactionError::RPCError(_))));
+    }
+
+    #[tokio::test]
+    async fn url_timeout_override_only_applies_to_its_url() {
+        // Accepts connections but never answers them.
+        let silent = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let silent_url = format!("http://{}/", silent.local_addr().unwrap());
+        let answering_url = format!("{}/answering", mockito::server_url());
+        let config = ClientConfig {
+            url_timeouts: HashMap::from([(silent_url.clone(), Duration::from_millis(100))]),
+            ..Default::default()
+        };
+        let client =
+            MojaveClient::with_config(&[silent_url.clone(), answering_url.clone()], None, config)
+                .unwrap();
+        assert_eq!(
+            client.timeout_for(&Url::parse(&silent_url).unwrap()),
+            Duration::from_millis(100)
+        );
+        assert_eq!(
+            client.timeout_for(&Url::parse(&answering_url).unwrap()),
+            DEFAULT_REQUEST_TIMEOUT
+        );
+
+        // The silent sequencer times out well before the default timeout.
+        let answering = mock_sequencer("/answering", true).expect(1).create();
+        let tx_hash = tokio::time::timeout(
+            Duration::from_secs(5),
+            client.send_forward_transaction(RAW_TRANSACTION, ForwardStrategy::FirstSuccess),
+        )
+        .await
+        .expect("the override should have timed out the silent sequencer")
+        .unwrap();
+        assert_eq!(tx_hash, H256::repeat_byte(1));
+        answering.assert();
+    }
+
+    #[tokio::test]
+    async fn retry_budget_bounds_the_broadcast() {
+        let silent = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let _failing = mockito::mock("POST", "/failing")
+            .with_status(500)
+            .with_body("internal error")
+            .create();
+        let signing_key =
+            AnySigningKey::from_str_with_scheme(SignatureScheme::Secp256k1, PRIVATE_KEY).unwrap();
+        let config = ClientConfig {
+            retry_budget: Some(Duration::from_millis(200)),
+            ..Default::default()
+        };
+        let client = MojaveClient::with_config(
+            &[
+                format!("http://{}/", silent.local_addr().unwrap()),
+                format!("{}/failing", mockito::server_url()),
+            ],
+            Some(signing_key),
+            config,
+        )
+        .unwrap();
+        let block = Block::new(BlockHeader::default(), BlockBody::default());
+
+        let started = std::time::Instant::now();
+        let result = client
+            .send_broadcast_block(&block, BroadcastMode::FirstOk)
+            .await;
+        assert!(started.elapsed() < Duration::from_secs(5));
+        let Err(MojaveClientError::RetryBudgetExceeded { budget, errors }) = result else {
+            panic!("the broadcast should exceed its retry budget: {result:?}");
+        };
+        assert_eq!(budget, Duration::from_millis(200));
+        // Only the failing url answered before the deadline.
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("/failing"), "{errors:?}");
+    }
+
+    #[tokio::test]
+    async fn stalled_url_times_out() {
+        let _stalled = mockito::mock("POST", "/stalled")
+            .with_header("content-type", "application/json")
+            .with_body_from_fn(|_| {
+                std::thread::sleep(Duration::from_millis(500));
+                Ok(())
+            })
+            .create();
+        let url = format!("{}/stalled", mockito::server_url());
+        let config = ClientConfig {
+            request_timeout: Duration::from_millis(100),
+            ..Default::default()
+        };
+        let client = MojaveClient::with_config(&[url.clone()], None, config).unwrap();
 
         let request = RpcRequest {
             id: RpcRequestId::Number(1),
             jsonrpc: "2.0".to_string(),
-            method: "mojave_sendBroadcastBlock".to_string(),
-            params: Some(vec![json!(params)]),
+            method: "eth_chainId".to_string(),
+            params: None,
         };
+        let result = client
+            .send_request_to_url(&Url::parse(&url).unwrap(), &request, true)
+            .await;
+        assert!(matches!(
+            result,
+            Err(MojaveClientError::Timeout { timeout, .. }) if timeout == Duration::from_millis(100)
+        ));
+    }
 
-        match self.send_request_race(request).await {
-            Ok(RpcResponse::Success(result)) => {
-                serde_json::from_value(result.result).map_err(MojaveClientError::from)
-            }
-            Ok(RpcResponse::Error(error_response)) => {
-                Err(MojaveClientError::RpcError(error_response.error.message))
+    /// Serves `failures` 503 responses, then `body` to every request, and counts the requests.
+    fn flaky_server(failures: usize, body: &'static str) -> (String, Arc<AtomicU64>) {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicU64::new(0));
+        let served = requests.clone();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut buffer = [0u8; 8192];
+                let _ = stream.read(&mut buffer);
+                let count = served.fetch_add(1, Ordering::SeqCst) as usize;
+                let response = if count < failures {
+                    "HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                        .to_owned()
+                } else {
+                    format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                        body.len()
+                    )
+                };
+                let _ = stream.write_all(response.as_bytes());
             }
-            Err(error) => Err(error),
+        });
+        (url, requests)
+    }
+
+    fn fast_retries() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 2,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(50),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn broadcast_is_retried_on_server_errors() {
+        let (url, requests) = flaky_server(2, r#"{"jsonrpc":"2.0","id":1,"result":null}"#);
+        let signing_key =
+            AnySigningKey::from_str_with_scheme(SignatureScheme::Secp256k1, PRIVATE_KEY).unwrap();
+        let block = Block::new(BlockHeader::default(), BlockBody::default());
+
+        let client = MojaveClient::new(&[url.clone()], signing_key.clone()).unwrap();
+        let result = client
+            .send_broadcast_block(&block, BroadcastMode::FirstOk)
+            .await;
+        assert!(result.is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        let client = MojaveClient::new(&[url], signing_key)
+            .unwrap()
+            .with_retry(fast_retries());
+        client
+            .send_broadcast_block(&block, BroadcastMode::FirstOk)
+            .await
+            .unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn forwarded_transactions_are_only_retried_when_enabled() {
+        let body = r#"{"jsonrpc":"2.0","id":1,"result":"0x0101010101010101010101010101010101010101010101010101010101010101"}"#;
+        let (url, requests) = flaky_server(1, body);
+
+        let client = MojaveClient::without_signing_key(&[url.clone()])
+            .unwrap()
+            .with_retry(fast_retries());
+        let result = client
+            .send_forward_transaction(RAW_TRANSACTION, ForwardStrategy::FirstSuccess)
+            .await;
+        assert!(matches!(
+            result,
+            Err(ForwardTransactionError::Client(
+                MojaveClientError::ServerError { status: 503, .. }
+            ))
+        ));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        let client = client.with_retry(RetryPolicy {
+            retry_transactions: true,
+            ..fast_retries()
+        });
+        let tx_hash = client
+            .send_forward_transaction(RAW_TRANSACTION, ForwardStrategy::FirstSuccess)
+            .await
+            .unwrap();
+        assert_eq!(tx_hash, H256::repeat_byte(1));
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn retry_delays_grow_exponentially_up_to_the_maximum() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+            jitter: false,
+            ..Default::default()
+        };
+        let delays: Vec<_> = (0..5)
+            .map(|retry| policy.delay(retry).as_millis())
+            .collect();
+        assert_eq!(delays, [100, 200, 400, 500, 500]);
+        assert_eq!(policy.delay(u32::MAX), Duration::from_millis(500));
+
+        let policy = RetryPolicy {
+            jitter: true,
+            ..policy
+        };
+        for retry in 0..5 {
+            let delay = policy.delay(retry).as_millis();
+            assert!((delays[retry as usize] / 2..=delays[retry as usize]).contains(&delay));
+        }
+    }
+
+    fn mock_full_node(path: &str, accepts: bool) -> mockito::Mock {
+        let body = if accepts {
+            r#"{"jsonrpc":"2.0","id":1,"result":null}"#
+        } else {
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"stale epoch"}}"#
+        };
+        mockito::mock("POST", path)
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "method": "mojave_sendBroadcastBlock",
+            })))
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create()
+    }
+
+    #[tokio::test]
+    async fn broadcast_succeeds_once_the_quorum_accepted_the_block() {
+        let _first = mock_full_node("/quorum/first", true);
+        let _second = mock_full_node("/quorum/second", true);
+        let _third = mock_full_node("/quorum/third", false);
+        let url = mockito::server_url();
+        let signing_key =
+            AnySigningKey::from_str_with_scheme(SignatureScheme::Secp256k1, PRIVATE_KEY).unwrap();
+        let client = MojaveClient::new(
+            &["first", "second", "third"].map(|name| format!("{url}/quorum/{name}")),
+            signing_key,
+        )
+        .unwrap();
+        let block = Block::new(BlockHeader::default(), BlockBody::default());
+
+        let report = client
+            .send_broadcast_block(&block, BroadcastMode::Quorum(2))
+            .await
+            .unwrap();
+        assert!(report.accepted() >= 2);
+        assert_eq!(report.outcomes.len(), 3);
+
+        let Err(MojaveClientError::QuorumNotReached { required, report }) = client
+            .send_broadcast_block(&block, BroadcastMode::All)
+            .await
+        else {
+            panic!("the third full node refuses the block");
+        };
+        assert_eq!(required, 3);
+        assert_eq!(report.accepted(), 2);
+        let errors = report.errors();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("/quorum/third") && errors[0].contains("stale epoch"));
+
+        let result = client
+            .send_broadcast_block(&block, BroadcastMode::Quorum(4))
+            .await;
+        assert!(matches!(result, Err(MojaveClientError::Custom(_))));
+    }
+
+    #[tokio::test]
+    async fn detailed_broadcast_reports_every_full_node() {
+        let _accepting = mock_full_node("/detailed/accepting", true);
+        let _refusing = mock_full_node("/detailed/refusing", false);
+        let url = mockito::server_url();
+        let signing_key =
+            AnySigningKey::from_str_with_scheme(SignatureScheme::Secp256k1, PRIVATE_KEY).unwrap();
+        let addresses = [
+            format!("{url}/detailed/accepting"),
+            format!("{url}/detailed/refusing"),
+            "http://127.0.0.1:1".to_string(),
+        ];
+        let client = MojaveClient::new(&addresses, signing_key).unwrap();
+        let block = Block::new(BlockHeader::default(), BlockBody::default());
+
+        let results = client.send_broadcast_block_detailed(&block).await.unwrap();
+        let urls: Vec<_> = results.iter().map(|(url, _)| url.to_string()).collect();
+        let addresses: Vec<_> = addresses
+            .iter()
+            .map(|address| Url::parse(address).unwrap().to_string())
+            .collect();
+        assert_eq!(urls, addresses);
+        assert!(results[0].1.is_ok());
+        assert!(
+            matches!(&results[1].1, Err(MojaveClientError::RpcError(message)) if message == "stale epoch")
+        );
+        assert!(results[2].1.is_err());
+    }
+
+    #[tokio::test]
+    async fn broadcast_stats_count_the_acks_and_failures_of_each_full_node() {
+        let _accepting = mock_full_node("/stats/accepting", true);
+        let _refusing = mock_full_node("/stats/refusing", false);
+        let url = mockito::server_url();
+        let signing_key =
+            AnySigningKey::from_str_with_scheme(SignatureScheme::Secp256k1, PRIVATE_KEY).unwrap();
+        let addresses = [
+            format!("{url}/stats/accepting"),
+            format!("{url}/stats/refusing"),
+            "http://127.0.0.1:1".to_string(),
+        ];
+        let client = MojaveClient::new(&addresses, signing_key).unwrap();
+        let block = Block::new(BlockHeader::default(), BlockBody::default());
+        assert!(
+            client.broadcast_stats().iter().all(|stats| stats.acks == 0
+                && stats.failures == 0
+                && stats.last_success.is_none())
+        );
+
+        client.send_broadcast_block_detailed(&block).await.unwrap();
+        let _ = client
+            .send_broadcast_block(&block, BroadcastMode::All)
+            .await;
+
+        let stats = client.broadcast_stats();
+        let urls: Vec<_> = stats.iter().map(|stats| stats.url.clone()).collect();
+        let addresses: Vec<_> = addresses
+            .iter()
+            .map(|address| Url::parse(address).unwrap().to_string())
+            .collect();
+        assert_eq!(urls, addresses);
+        assert_eq!((stats[0].acks, stats[0].failures), (2, 0));
+        assert!(stats[0].last_success.is_some());
+        for stats in &stats[1..] {
+            assert_eq!((stats.acks, stats.failures), (0, 2));
+            assert_eq!(stats.last_success, None);
+        }
+    }
+
+    #[test]
+    fn broadcast_mode_is_parsed() {
+        for mode in [
+            BroadcastMode::FirstOk,
+            BroadcastMode::Quorum(2),
+            BroadcastMode::All,
+        ] {
+            assert_eq!(BroadcastMode::from_str(&mode.to_string()), Ok(mode));
+        }
+        for invalid in ["first", "quorum", "quorum:0", "quorum:two"] {
+            assert!(BroadcastMode::from_str(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn url_timeout_override_must_be_an_address() {
+        let config = ClientConfig {
+            url_timeouts: HashMap::from([(
+                "http://127.0.0.1:1234".to_owned(),
+                Duration::from_secs(1),
+            )]),
+            ..Default::default()
+        };
+        let result = MojaveClient::with_config(&["http://127.0.0.1:8545".to_owned()], None, config);
+        assert!(matches!(result, Err(MojaveClientError::ParseUrlError(_))));
+    }
+
+    #[test]
+    fn signing_requires_a_signing_key() {
+        let client =
+            MojaveClient::without_signing_key(&["http://127.0.0.1:8545".to_owned()]).unwrap();
+        let block = Block::new(BlockHeader::default(), BlockBody::default());
+        assert!(matches!(
+            client.broadcast_block_request(&block),
+            Err(MojaveClientError::MissingSigningKey)
+        ));
+    }
+
+    #[test]
+    fn epochs_are_strictly_increasing() {
+        let client = client();
+        let mut previous = client.next_epoch();
+        for _ in 0..1_000 {
+            let epoch = client.next_epoch();
+            assert!(epoch > previous);
+            previous = epoch;
         }
     }
 }
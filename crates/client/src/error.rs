@@ -12,7 +12,28 @@ pub enum MojaveClientError {
     RpcError(String),
     #[error("Failed to parse URL: {0}")]
     ParseUrlError(String),
-    #[error("Missing signing key in environment variable PRIVATE_KEY")]
+    #[error("Unsupported scheme of {0}, expected http or https")]
+    UnsupportedScheme(String),
+    #[error("{url} answered with the server error {status}")]
+    ServerError { url: String, status: u16 },
+    #[error("Request to {url} timed out after {timeout:?}")]
+    Timeout {
+        url: String,
+        timeout: std::time::Duration,
+    },
+    #[error("Retry budget of {budget:?} exceeded, errors so far: [{}]", errors.join(", "))]
+    RetryBudgetExceeded {
+        budget: std::time::Duration,
+        errors: Vec<String>,
+    },
+    #[error("Only {} of the {required} required full nodes accepted the block, errors: [{}]", report.accepted(), report.errors().join(", "))]
+    QuorumNotReached {
+        required: usize,
+        report: crate::BroadcastReport,
+    },
+    #[error("Invalid value of the {0} header")]
+    InvalidHeaderValue(String),
+    #[error("Missing signing key, this client cannot sign blocks")]
     MissingSigningKey,
     #[error("Signature error: {0}")]
     SignatureError(#[from] mojave_signature::SignatureError),
@@ -24,4 +45,6 @@ pub enum ForwardTransactionError {
     SerdeJSONError(#[from] serde_json::Error),
     #[error("{0}")]
     RPCError(String),
+    #[error(transparent)]
+    Client(#[from] MojaveClientError),
 }
@@ -2,5 +2,9 @@ mod client;
 mod error;
 pub mod types;
 
-pub use client::MojaveClient;
+pub use client::{
+    BroadcastMode, BroadcastOutcome, BroadcastReport, BroadcastStats, ClientConfig,
+    DEFAULT_CONNECT_TIMEOUT, DEFAULT_REQUEST_TIMEOUT, ForwardStrategy, MojaveClient, RetryPolicy,
+    UrlSelection,
+};
 pub use error::{ForwardTransactionError, MojaveClientError};
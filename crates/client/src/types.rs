@@ -1,11 +1,268 @@
-use ethrex_common::types::Block;
-use mojave_signature::{Signature, VerifyingKey};
+use ethrex_common::{H256, types::Block};
+use mojave_signature::{
+    AnySigningKey, AnyVerifyingKey, Signature, SignatureError, ThresholdVerifier,
+};
 use serde::{Deserialize, Serialize};
 
-// need to check whether we will use Message and contain other data or not
-#[derive(Serialize, Deserialize)]
+/// A block broadcast by the sequencer to the full nodes, along with the sequencer's signature
+/// over the block hash. The signature scheme is the one the sequencer was configured with. The
+/// key to verify the signature with is recovered from it when the scheme allows it, and carried
+/// along otherwise.
+///
+/// The signature also covers `epoch`, a number strictly increasing with every broadcast of a
+/// given sequencer, so that full nodes can reject a captured block replayed later on.
+///
+/// This is the wire format of `mojave_sendBroadcastBlock`, shared by the sequencer producing
+/// it and the full node consuming it. Renaming or removing a field is a breaking change for
+/// the network.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SignedBlock {
     pub block: Block,
+    pub epoch: u64,
     pub signature: Signature,
-    pub verifying_key: VerifyingKey,
+    /// Only set for the schemes whose verifying key cannot be recovered from the signature.
+    /// Payloads signed before the keys were recovered carry it for every scheme.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verifying_key: Option<AnyVerifyingKey>,
+    /// Signatures of other sequencers over the same block hash and epoch, for the full nodes
+    /// requiring a block to be signed by several of them, see [`SignedBlock::verify_threshold`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub co_signatures: Vec<Signature>,
+}
+
+impl SignedBlock {
+    /// Signs the block header hash, along with the broadcast epoch, with the sequencer's key.
+    pub fn new(
+        block: Block,
+        epoch: u64,
+        signing_key: &AnySigningKey,
+    ) -> Result<Self, SignatureError> {
+        let signature = signing_key.sign(&(block.header.hash(), epoch))?;
+        let verifying_key =
+            (!signing_key.scheme().is_recoverable()).then(|| signing_key.verifying_key());
+        Ok(Self {
+            block,
+            epoch,
+            signature,
+            verifying_key,
+            co_signatures: vec![],
+        })
+    }
+
+    /// Adds the signature of another sequencer over the same block hash and epoch.
+    pub fn co_sign(&mut self, signing_key: &AnySigningKey) -> Result<(), SignatureError> {
+        let signature = signing_key.sign(&(self.block.header.hash(), self.epoch))?;
+        self.co_signatures.push(signature);
+        Ok(())
+    }
+
+    /// Returns whether the signature and co-signatures are produced by enough of the keys of
+    /// `verifier` over this block's header hash and epoch.
+    pub fn verify_threshold(&self, verifier: &ThresholdVerifier) -> Result<bool, SignatureError> {
+        let message = (self.block.header.hash(), self.epoch);
+        let signatures: Vec<Signature> = std::iter::once(&self.signature)
+            .chain(&self.co_signatures)
+            .cloned()
+            .collect();
+        verifier.verify_threshold(&message, &signatures)
+    }
+
+    /// Checks that the signature covers this block's header hash and epoch and returns the key
+    /// that produced it. A recovered key always matches its signature, so tampering with the
+    /// block shows as another signer and callers must check the signer they expect.
+    pub fn verify(&self) -> Result<AnyVerifyingKey, SignatureError> {
+        let message = (self.block.header.hash(), self.epoch);
+        let verifying_key = self.signer(&message)?;
+        verifying_key.verify(&message, &self.signature)?;
+        Ok(verifying_key)
+    }
+
+    fn signer(&self, message: &(H256, u64)) -> Result<AnyVerifyingKey, SignatureError> {
+        match &self.verifying_key {
+            Some(verifying_key) => Ok(verifying_key.clone()),
+            None => AnyVerifyingKey::recover(message, &self.signature),
+        }
+    }
+
+    /// Verifies the signatures of a run of blocks at once and returns, in the order of the
+    /// blocks, the key that produced each valid signature, see [`SignedBlock::verify`].
+    pub fn verify_batch(
+        blocks: &[SignedBlock],
+    ) -> Result<Vec<Option<AnyVerifyingKey>>, SignatureError> {
+        let messages: Vec<_> = blocks
+            .iter()
+            .map(|block| (block.block.header.hash(), block.epoch))
+            .collect();
+        let verifying_keys: Vec<_> = blocks
+            .iter()
+            .zip(&messages)
+            .map(|(block, message)| block.signer(message).ok())
+            .collect();
+        let items: Vec<_> = blocks
+            .iter()
+            .zip(&messages)
+            .zip(&verifying_keys)
+            .filter_map(|((block, message), verifying_key)| {
+                Some((verifying_key.as_ref()?, message, &block.signature))
+            })
+            .collect();
+        let mut results = AnyVerifyingKey::verify_batch(&items)?.into_iter();
+        Ok(verifying_keys
+            .into_iter()
+            .map(|verifying_key| verifying_key.filter(|_| results.next().unwrap_or(false)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethrex_common::types::{BlockBody, BlockHeader};
+    use mojave_signature::SignatureScheme;
+
+    const SECP256K1_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const ED25519_KEY: &str = "902ddc4259c907ef56ad9be31f6640978eb8d392e18ffde0a569ded804df23e1";
+
+    fn secp256k1_key() -> AnySigningKey {
+        AnySigningKey::from_str_with_scheme(SignatureScheme::Secp256k1, SECP256K1_KEY).unwrap()
+    }
+
+    fn ed25519_key() -> AnySigningKey {
+        AnySigningKey::from_str_with_scheme(SignatureScheme::Ed25519, ED25519_KEY).unwrap()
+    }
+
+    fn block() -> Block {
+        Block::new(
+            BlockHeader {
+                number: 7,
+                gas_limit: 30_000_000,
+                timestamp: 1_700_000_000,
+                ..Default::default()
+            },
+            BlockBody {
+                transactions: vec![],
+                ommers: vec![],
+                withdrawals: None,
+            },
+        )
+    }
+
+    #[test]
+    fn signed_block_serde_round_trip() {
+        let signing_key = secp256k1_key();
+        let signed_block = SignedBlock::new(block(), 1, &signing_key).unwrap();
+
+        let json = serde_json::to_value(&signed_block).unwrap();
+        let decoded: SignedBlock = serde_json::from_value(json.clone()).unwrap();
+
+        assert_eq!(decoded.block.hash(), signed_block.block.hash());
+        assert_eq!(decoded.epoch, signed_block.epoch);
+        assert_eq!(decoded.signature.bytes, signed_block.signature.bytes);
+        assert_eq!(decoded.signature.scheme, signed_block.signature.scheme);
+        assert!(json.get("verifying_key").is_none());
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), json);
+        assert_eq!(
+            decoded.verify().unwrap().to_address(),
+            signing_key.verifying_key().to_address()
+        );
+    }
+
+    /// Whether the block fails verification or shows another signer than `signing_key`.
+    fn is_rejected(signed_block: &SignedBlock, signing_key: &AnySigningKey) -> bool {
+        signed_block.verify().map_or(true, |verifying_key| {
+            verifying_key.to_address() != signing_key.verifying_key().to_address()
+        })
+    }
+
+    #[test]
+    fn tampered_signed_block_fails_verification() {
+        for signing_key in [secp256k1_key(), ed25519_key()] {
+            let mut signed_block = SignedBlock::new(block(), 1, &signing_key).unwrap();
+            signed_block.block.header.number += 1;
+
+            assert!(is_rejected(&signed_block, &signing_key));
+        }
+    }
+
+    #[test]
+    fn tampered_epoch_fails_verification() {
+        for signing_key in [secp256k1_key(), ed25519_key()] {
+            let mut signed_block = SignedBlock::new(block(), 1, &signing_key).unwrap();
+            signed_block.epoch += 1;
+
+            assert!(is_rejected(&signed_block, &signing_key));
+        }
+    }
+
+    #[test]
+    fn signed_blocks_are_verified_in_batch() {
+        let ed25519_key = ed25519_key();
+        let valid = SignedBlock::new(block(), 1, &ed25519_key).unwrap();
+        let mut tampered = SignedBlock::new(block(), 2, &ed25519_key).unwrap();
+        tampered.epoch += 1;
+        let secp256k1 = SignedBlock::new(block(), 3, &secp256k1_key()).unwrap();
+        let mut unrecoverable = SignedBlock::new(block(), 4, &secp256k1_key()).unwrap();
+        unrecoverable.signature.bytes.truncate(64);
+
+        let results =
+            SignedBlock::verify_batch(&[valid, tampered, secp256k1, unrecoverable]).unwrap();
+        let valid: Vec<_> = results.iter().map(Option::is_some).collect();
+        assert_eq!(valid, vec![true, false, true, false]);
+        assert_eq!(
+            results[0].as_ref().unwrap().to_address(),
+            ed25519_key.verifying_key().to_address()
+        );
+    }
+
+    #[test]
+    fn compact_signed_block_with_verifying_key_is_verified() {
+        let signing_key = secp256k1_key();
+        let mut signed_block = SignedBlock::new(block(), 1, &signing_key).unwrap();
+        signed_block.signature.bytes.truncate(64);
+        assert!(signed_block.verify().is_err());
+
+        signed_block.verifying_key = Some(signing_key.verifying_key());
+        let json = serde_json::to_value(&signed_block).unwrap();
+        let decoded: SignedBlock = serde_json::from_value(json).unwrap();
+        decoded.verify().unwrap();
+        assert!(SignedBlock::verify_batch(&[decoded]).unwrap()[0].is_some());
+    }
+
+    #[test]
+    fn signed_block_with_each_scheme() {
+        for signing_key in [secp256k1_key(), ed25519_key()] {
+            let scheme = signing_key.scheme();
+            let signed_block = SignedBlock::new(block(), 1, &signing_key).unwrap();
+            assert_eq!(signed_block.signature.scheme, scheme);
+            assert_eq!(signed_block.verify().unwrap().scheme(), scheme);
+
+            let json = serde_json::to_value(&signed_block).unwrap();
+            let decoded: SignedBlock = serde_json::from_value(json).unwrap();
+            decoded.verify().unwrap();
+        }
+    }
+
+    #[test]
+    fn co_signed_block_reaches_the_threshold() {
+        let secp256k1 = secp256k1_key();
+        let ed25519 = ed25519_key();
+        let verifier =
+            ThresholdVerifier::new(vec![secp256k1.verifying_key(), ed25519.verifying_key()], 2)
+                .unwrap();
+
+        let mut signed_block = SignedBlock::new(block(), 1, &secp256k1).unwrap();
+        assert!(!signed_block.verify_threshold(&verifier).unwrap());
+        let json = serde_json::to_value(&signed_block).unwrap();
+        assert!(json.get("co_signatures").is_none());
+
+        // Co-signing with the same key again does not make it a second signer.
+        signed_block.co_sign(&secp256k1).unwrap();
+        assert!(!signed_block.verify_threshold(&verifier).unwrap());
+
+        signed_block.co_sign(&ed25519).unwrap();
+        let json = serde_json::to_value(&signed_block).unwrap();
+        let decoded: SignedBlock = serde_json::from_value(json).unwrap();
+        assert!(decoded.verify_threshold(&verifier).unwrap());
+        decoded.verify().unwrap();
+    }
 }
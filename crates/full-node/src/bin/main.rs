@@ -4,21 +4,27 @@ use ethrex::{
 };
 use ethrex_blockchain::BlockchainType;
 use ethrex_p2p::{network::peer_table, peer_handler::PeerHandler, sync_manager::SyncManager};
-use ethrex_rpc::EthClient;
+use ethrex_rpc::{EthClient, NodeData};
 use ethrex_storage_rollup::{EngineTypeRollup, StoreRollup};
 use ethrex_vm::EvmEngine;
 use mojave_chain_utils::{
     initializer::{
-        get_authrpc_socket_addr, get_http_socket_addr, get_local_p2p_node, resolve_data_dir,
+        get_authrpc_socket_addr, get_http_socket_addr, get_local_p2p_node, get_metrics_socket_addr,
+        get_rpc_config, resolve_data_dir,
     },
     logging::init_logging,
-    unique_heap::AsyncUniqueHeap,
 };
+use mojave_client::{ClientConfig, MojaveClient};
 use mojave_full_node::{
     cli::{Cli, Command},
     error::Error,
-    rpc::start_api,
+    monitor::{
+        SequencerHealth, check_sequencer_at_startup, spawn_sequencer_health_probe,
+        spawn_state_root_check,
+    },
+    rpc::{ApiConfig, ApiServices, start_api},
 };
+use mojave_signature::ThresholdVerifier;
 use std::{path::PathBuf, sync::Arc, time::Duration};
 use tokio::sync::Mutex;
 use tokio_util::sync::CancellationToken;
@@ -76,38 +82,100 @@ async fn main() -> Result<(), Error> {
             .await;
 
             let rpc_shutdown = CancellationToken::new();
-            let eth_client = EthClient::new(&full_node_options.sequencer_address)?;
-            start_api(
-                get_http_socket_addr(&options),
-                get_authrpc_socket_addr(&options),
-                store,
+            // At least one address is required by the cli.
+            let eth_client = EthClient::new(&full_node_options.sequencer_addresses[0])?;
+            let mojave_client = MojaveClient::with_config(
+                &full_node_options.sequencer_addresses,
+                None,
+                ClientConfig {
+                    url_selection: full_node_options.url_selection,
+                    ..ClientConfig::default()
+                },
+            )?;
+            let chain_id = genesis.config.chain_id;
+            let sequencer_health = SequencerHealth::default();
+            // A failure is already logged and the full node keeps on starting.
+            let _ = check_sequencer_at_startup(&eth_client, chain_id, &sequencer_health).await;
+            if full_node_options.sequencer_health_check_interval > 0 {
+                spawn_sequencer_health_probe(
+                    eth_client.clone(),
+                    chain_id,
+                    sequencer_health.clone(),
+                    Duration::from_secs(full_node_options.sequencer_health_check_interval),
+                    rpc_shutdown.clone(),
+                );
+            }
+            if full_node_options.state_root_check_interval > 0 {
+                spawn_state_root_check(
+                    store.clone(),
+                    eth_client.clone(),
+                    Duration::from_secs(full_node_options.state_root_check_interval),
+                    rpc_shutdown.clone(),
+                );
+            }
+            let sequencer_threshold = if full_node_options.sequencer_public_keys.is_empty() {
+                None
+            } else {
+                Some(ThresholdVerifier::new(
+                    full_node_options.sequencer_public_keys.clone(),
+                    full_node_options.sequencer_signature_threshold as usize,
+                )?)
+            };
+            // The RPC server runs until Ctrl-C, which then shuts the rest of the full node down.
+            tokio::spawn({
+                let rpc_shutdown = rpc_shutdown.clone();
+                async move {
+                    let _ = tokio::signal::ctrl_c().await;
+                    rpc_shutdown.cancel();
+                }
+            });
+            let api_config = ApiConfig {
+                http_addr: get_http_socket_addr(&options),
+                authrpc_addr: get_authrpc_socket_addr(&options),
+                metrics_addr: get_metrics_socket_addr(&options),
+                forward_strategy: full_node_options.forward_strategy,
+                block_queue_max_size: full_node_options.block_queue_max_size as usize,
+                idle_timeout: (full_node_options.sequencer_idle_timeout > 0)
+                    .then(|| Duration::from_secs(full_node_options.sequencer_idle_timeout)),
+                verification_workers: full_node_options.block_verification_workers as usize,
+                verification_cache_size: full_node_options.block_verification_cache_size as usize,
+                sequencer_threshold,
+                max_clock_skew: Duration::from_secs(
+                    full_node_options.block_validation_max_clock_skew,
+                ),
+                max_broadcast_batch: full_node_options.block_broadcast_max_batch as usize,
+                backfill_policy: full_node_options.block_broadcast_backfill_policy,
+                max_reorg_depth: full_node_options.block_max_reorg_depth,
+                rpc_config: get_rpc_config(&options),
+            };
+            let api_services = ApiServices {
+                storage: store,
                 blockchain,
-                read_jwtsecret_file(&options.authrpc_jwtsecret),
-                local_p2p_node,
-                local_node_record.lock().await.clone(),
+                node_data: NodeData {
+                    jwt_secret: read_jwtsecret_file(&options.authrpc_jwtsecret),
+                    local_p2p_node,
+                    local_node_record: local_node_record.lock().await.clone(),
+                    client_version: get_client_version(),
+                },
                 syncer,
                 peer_handler,
-                get_client_version(),
-                rollup_store.clone(),
+                peer_table: peer_table.clone(),
+                rollup_store: rollup_store.clone(),
                 eth_client,
-                AsyncUniqueHeap::new(),
-                rpc_shutdown.clone(),
-            )
-            .await?;
+                mojave_client,
+                sequencer_health,
+            };
+            start_api(api_config, api_services, rpc_shutdown.clone()).await?;
 
-            tokio::select! {
-                _ = tokio::signal::ctrl_c() => {
-                    tracing::info!("Shutting down the full node..");
-                    rpc_shutdown.cancel();
-                    let node_config_path = PathBuf::from(data_dir).join("node_config.json");
-                    tracing::info!("Storing config at {:?}...", node_config_path);
-                    cancel_token.cancel();
-                    let node_config = NodeConfigFile::new(peer_table, local_node_record.lock().await.clone()).await;
-                    store_node_config_file(node_config, node_config_path).await;
-                    tokio::time::sleep(Duration::from_secs(1)).await;
-                    tracing::info!("Successfully shut down the full node.");
-                }
-            }
+            tracing::info!("Shutting down the full node..");
+            let node_config_path = PathBuf::from(data_dir).join("node_config.json");
+            tracing::info!("Storing config at {:?}...", node_config_path);
+            cancel_token.cancel();
+            let node_config =
+                NodeConfigFile::new(peer_table, local_node_record.lock().await.clone()).await;
+            store_node_config_file(node_config, node_config_path).await;
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            tracing::info!("Successfully shut down the full node.");
         }
     }
     Ok(())
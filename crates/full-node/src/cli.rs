@@ -1,5 +1,9 @@
+use crate::rpc::types::BackfillPolicy;
 use clap::{Parser, Subcommand};
 use mojave_chain_utils::options::Options;
+use mojave_client::{ForwardStrategy, UrlSelection};
+use mojave_signature::AnyVerifyingKey;
+use std::str::FromStr;
 use tracing::Level;
 
 #[allow(clippy::upper_case_acronyms)]
@@ -46,17 +50,134 @@ pub struct FullNodeOptions {
     #[arg(
         long = "sequencer.address",
         default_value = "0.0.0.0:1739",
-        help = "Allowed domain and port for the sequencer in the form 'domain:port'",
+        help = "Allowed domain(s) and port(s) for the sequencer in the form 'domain:port', can be specified multiple times. Blocks are back-filled from the first one",
         help_heading = "Full Node Options",
-        required = true
+        required = true,
+        value_delimiter = ','
     )]
-    pub sequencer_address: String,
+    pub sequencer_addresses: Vec<String>,
+    #[arg(
+        long = "sequencer.forward_strategy",
+        default_value_t = ForwardStrategy::FirstSuccess,
+        help = "How transactions are forwarded to the sequencers, either first-success or all",
+        help_heading = "Full Node Options"
+    )]
+    pub forward_strategy: ForwardStrategy,
+    #[arg(
+        long = "sequencer.url_selection",
+        default_value_t = UrlSelection::Ordered,
+        help = "Sequencer a transaction forwarded with the first-success strategy is sent to first, either ordered for the first address, round-robin or random",
+        help_heading = "Full Node Options"
+    )]
+    pub url_selection: UrlSelection,
+    #[arg(
+        long = "sequencer.health_check_interval",
+        default_value = "30",
+        help = "Interval in seconds between two health checks of the sequencer endpoint, 0 disables them",
+        help_heading = "Full Node Options"
+    )]
+    pub sequencer_health_check_interval: u64,
+    #[arg(
+        long = "state_root_check.interval",
+        default_value = "0",
+        help = "Interval in seconds between two comparisons of the latest state root with the sequencer's, 0 disables them",
+        help_heading = "Full Node Options"
+    )]
+    pub state_root_check_interval: u64,
+    #[arg(
+        long = "sequencer.idle_timeout",
+        default_value = "60",
+        help = "Time in seconds without any block from the sequencer after which its blocks are polled, with an exponential backoff while it is down, 0 disables polling",
+        help_heading = "Full Node Options"
+    )]
+    pub sequencer_idle_timeout: u64,
+    #[arg(
+        long = "block_verification.workers",
+        default_value = "4",
+        help = "Maximum number of broadcast block signatures verified at once, off the RPC workers",
+        help_heading = "Full Node Options",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub block_verification_workers: u64,
+    #[arg(
+        long = "block.verification_cache_size",
+        default_value = "1024",
+        help = "Number of verified broadcast block signatures remembered, so that a block broadcast again is not verified twice, 0 disables the cache",
+        help_heading = "Full Node Options"
+    )]
+    pub block_verification_cache_size: u64,
+    #[arg(
+        long = "block_validation.max_clock_skew",
+        default_value = "15",
+        help = "Time in seconds a broadcast block may be timestamped in the future, to tolerate the clock skew between the sequencer and the full node",
+        help_heading = "Full Node Options"
+    )]
+    pub block_validation_max_clock_skew: u64,
+    #[arg(
+        long = "block_broadcast.max_batch",
+        default_value = "64",
+        help = "Maximum number of blocks accepted in a single mojave_sendBroadcastBlockBatch, larger batches are rejected before their blocks are parsed",
+        help_heading = "Full Node Options",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub block_broadcast_max_batch: u64,
+    #[arg(
+        long = "block_queue.max_size",
+        default_value = "0",
+        help = "Maximum number of blocks waiting to be processed, the furthest ones being evicted for closer ones once reached, 0 leaves the queue unbounded",
+        help_heading = "Full Node Options"
+    )]
+    pub block_queue_max_size: u64,
+    #[arg(
+        long = "block.max_reorg_depth",
+        default_value = "0",
+        help = "Maximum number of blocks the head may be rewound by a block below it, older blocks being ignored as stale or replayed, 0 never rewinds the head",
+        help_heading = "Full Node Options"
+    )]
+    pub block_max_reorg_depth: u64,
+    #[arg(
+        long = "block_broadcast.backfill_policy",
+        default_value_t = BackfillPolicy::StrictAscending,
+        help = "How a broadcast block ahead of the local chain waits for the missing blocks, either strict-ascending to back-fill all of them first, or prioritize-newest:<n> to back-fill at most n of them per broadcast",
+        help_heading = "Full Node Options"
+    )]
+    pub block_broadcast_backfill_policy: BackfillPolicy,
+    #[arg(
+        long = "sequencer.public_keys",
+        help = "Public keys of the sequencers in the form 'scheme:hex', e.g. 'secp256k1:02ab..', can be specified multiple times. When set, a broadcast block must be signed by --sequencer.signature_threshold of them",
+        help_heading = "Full Node Options",
+        value_delimiter = ',',
+        value_parser = AnyVerifyingKey::from_str
+    )]
+    pub sequencer_public_keys: Vec<AnyVerifyingKey>,
+    #[arg(
+        long = "sequencer.signature_threshold",
+        default_value = "1",
+        help = "Number of distinct --sequencer.public_keys that must sign a broadcast block",
+        help_heading = "Full Node Options",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub sequencer_signature_threshold: u64,
 }
 
 impl Default for FullNodeOptions {
     fn default() -> Self {
         Self {
-            sequencer_address: "0.0.0.0:1739".to_string(),
+            sequencer_addresses: vec!["0.0.0.0:1739".to_string()],
+            forward_strategy: ForwardStrategy::default(),
+            url_selection: UrlSelection::default(),
+            sequencer_health_check_interval: 30,
+            state_root_check_interval: 0,
+            sequencer_idle_timeout: 60,
+            block_verification_workers: 4,
+            block_verification_cache_size: 1024,
+            block_validation_max_clock_skew: 15,
+            block_broadcast_max_batch: 64,
+            block_queue_max_size: 0,
+            block_max_reorg_depth: 0,
+            block_broadcast_backfill_policy: BackfillPolicy::default(),
+            sequencer_public_keys: vec![],
+            sequencer_signature_threshold: 1,
         }
     }
 }
@@ -64,7 +185,39 @@ impl Default for FullNodeOptions {
 impl std::fmt::Debug for FullNodeOptions {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("FullNodeOptions")
-            .field("sequencer_address", &self.sequencer_address)
+            .field("sequencer_addresses", &self.sequencer_addresses)
+            .field("forward_strategy", &self.forward_strategy)
+            .field("url_selection", &self.url_selection)
+            .field(
+                "sequencer_health_check_interval",
+                &self.sequencer_health_check_interval,
+            )
+            .field("state_root_check_interval", &self.state_root_check_interval)
+            .field("sequencer_idle_timeout", &self.sequencer_idle_timeout)
+            .field(
+                "block_verification_workers",
+                &self.block_verification_workers,
+            )
+            .field(
+                "block_verification_cache_size",
+                &self.block_verification_cache_size,
+            )
+            .field(
+                "block_validation_max_clock_skew",
+                &self.block_validation_max_clock_skew,
+            )
+            .field("block_broadcast_max_batch", &self.block_broadcast_max_batch)
+            .field("block_queue_max_size", &self.block_queue_max_size)
+            .field("block_max_reorg_depth", &self.block_max_reorg_depth)
+            .field(
+                "block_broadcast_backfill_policy",
+                &self.block_broadcast_backfill_policy,
+            )
+            .field("sequencer_public_keys", &self.sequencer_public_keys)
+            .field(
+                "sequencer_signature_threshold",
+                &self.sequencer_signature_threshold,
+            )
             .finish()
     }
 }
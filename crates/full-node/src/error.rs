@@ -14,4 +14,8 @@ pub enum Error {
     Rpc(#[from] RpcErr),
     #[error(transparent)]
     EthClient(#[from] EthClientError),
+    #[error(transparent)]
+    MojaveClient(#[from] mojave_client::MojaveClientError),
+    #[error(transparent)]
+    Signature(#[from] mojave_signature::SignatureError),
 }
@@ -1,3 +1,5 @@
 pub mod cli;
 pub mod error;
+pub mod metrics;
+pub mod monitor;
 pub mod rpc;
@@ -0,0 +1,106 @@
+use crate::monitor::SequencerHealth;
+use axum::{Router, extract::State, routing::get};
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{Arc, Mutex},
+};
+
+/// Why a broadcast block was rejected, the `reason` label of the rejected verifications.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RejectReason {
+    InvalidSignature,
+    ReplayedEpoch,
+}
+
+impl RejectReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InvalidSignature => "invalid_signature",
+            Self::ReplayedEpoch => "replayed_epoch",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    verified: u64,
+    rejected: BTreeMap<RejectReason, u64>,
+}
+
+/// Counts the broadcast blocks whose signature was verified or rejected, so that operators can
+/// alert on rejections, a sign of an attack or of a misconfigured sequencer key.
+#[derive(Clone, Debug, Default)]
+pub struct BroadcastMetrics(Arc<Mutex<Counters>>);
+
+impl BroadcastMetrics {
+    pub fn record_verified(&self) {
+        if let Ok(mut counters) = self.0.lock() {
+            counters.verified += 1;
+        }
+    }
+
+    pub fn record_rejected(&self, reason: RejectReason) {
+        if let Ok(mut counters) = self.0.lock() {
+            *counters.rejected.entry(reason).or_default() += 1;
+        }
+    }
+
+    pub fn verified(&self) -> u64 {
+        self.0.lock().map_or(0, |counters| counters.verified)
+    }
+
+    pub fn rejected(&self, reason: RejectReason) -> u64 {
+        self.0.lock().map_or(0, |counters| {
+            counters.rejected.get(&reason).copied().unwrap_or_default()
+        })
+    }
+
+    /// Renders the counters in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let Ok(counters) = self.0.lock() else {
+            return String::new();
+        };
+        let mut output = String::from(
+            "# HELP mojave_broadcast_block_verifications_total Broadcast blocks verified or rejected by the full node.\n\
+             # TYPE mojave_broadcast_block_verifications_total counter\n",
+        );
+        let _ = writeln!(
+            output,
+            "mojave_broadcast_block_verifications_total{{result=\"verified\"}} {}",
+            counters.verified
+        );
+        for (reason, count) in &counters.rejected {
+            let _ = writeln!(
+                output,
+                "mojave_broadcast_block_verifications_total{{result=\"rejected\",reason=\"{}\"}} {count}",
+                reason.as_str()
+            );
+        }
+        output
+    }
+}
+
+/// Renders the readiness of the sequencer endpoint as a gauge, 1 when its last health check
+/// succeeded.
+pub fn render_sequencer_health(health: &SequencerHealth) -> String {
+    format!(
+        "# HELP mojave_sequencer_ready Whether the last health check of the sequencer endpoint succeeded.\n\
+         # TYPE mojave_sequencer_ready gauge\n\
+         mojave_sequencer_ready {}\n",
+        u8::from(health.is_ready())
+    )
+}
+
+/// Serves the metrics on `GET /metrics`.
+pub fn metrics_router(metrics: BroadcastMetrics, sequencer_health: SequencerHealth) -> Router {
+    Router::new()
+        .route("/metrics", get(handle_metrics))
+        .with_state((metrics, sequencer_health))
+}
+
+async fn handle_metrics(
+    State((metrics, sequencer_health)): State<(BroadcastMetrics, SequencerHealth)>,
+) -> String {
+    metrics.render() + &render_sequencer_health(&sequencer_health)
+}
@@ -0,0 +1,311 @@
+use ethrex_common::H256;
+use ethrex_rpc::{EthClient, clients::EthClientError, types::block_identifier::BlockIdentifier};
+use ethrex_storage::{Store, error::StoreError};
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SequencerHealthError {
+    #[error("Sequencer endpoint is unreachable: {0}")]
+    Unreachable(#[from] EthClientError),
+    #[error("Sequencer chain id {sequencer} does not match the configured chain id {expected}")]
+    ChainIdMismatch { expected: u64, sequencer: u64 },
+}
+
+/// Readiness of the sequencer endpoint as of its last health check.
+#[derive(Clone, Debug, Default)]
+pub struct SequencerHealth(Arc<AtomicBool>);
+
+impl SequencerHealth {
+    pub fn is_ready(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Records the outcome of a health check and returns the previous readiness.
+    fn set_ready(&self, ready: bool) -> bool {
+        self.0.swap(ready, Ordering::Relaxed)
+    }
+}
+
+/// Checks that the sequencer endpoint answers `eth_chainId` with the configured chain id.
+pub async fn check_sequencer(
+    eth_client: &EthClient,
+    expected_chain_id: u64,
+) -> Result<(), SequencerHealthError> {
+    let sequencer = eth_client.get_chain_id().await?.as_u64();
+    if sequencer != expected_chain_id {
+        return Err(SequencerHealthError::ChainIdMismatch {
+            expected: expected_chain_id,
+            sequencer,
+        });
+    }
+    Ok(())
+}
+
+/// Checks the sequencer endpoint once at startup so that a misconfigured address is reported
+/// right away rather than on the first back-fill. A failure is logged but does not prevent the
+/// full node from starting, in case the sequencer is only temporarily down.
+pub async fn check_sequencer_at_startup(
+    eth_client: &EthClient,
+    expected_chain_id: u64,
+    health: &SequencerHealth,
+) -> Result<(), SequencerHealthError> {
+    let result = check_sequencer(eth_client, expected_chain_id).await;
+    match &result {
+        Ok(()) => info!("Sequencer endpoint is reachable"),
+        Err(error) => error!(
+            %error,
+            "Sequencer health check failed at startup, check the --sequencer.address option"
+        ),
+    }
+    health.set_ready(result.is_ok());
+    result
+}
+
+/// Periodically checks the sequencer endpoint and updates its readiness, logging whenever it
+/// changes.
+pub fn spawn_sequencer_health_probe(
+    eth_client: EthClient,
+    expected_chain_id: u64,
+    health: SequencerHealth,
+    interval: Duration,
+    shutdown_token: CancellationToken,
+) -> JoinHandle<()> {
+    tokio::task::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    let result = check_sequencer(&eth_client, expected_chain_id).await;
+                    let was_ready = health.set_ready(result.is_ok());
+                    match result {
+                        Ok(()) if !was_ready => info!("Sequencer endpoint is reachable again"),
+                        Err(error) if was_ready => warn!(%error, "Sequencer endpoint became unhealthy"),
+                        _ => {}
+                    }
+                }
+                _ = shutdown_token.cancelled() => {
+                    info!("Shutting down sequencer health probe");
+                    break;
+                }
+            }
+        }
+    })
+}
+
+/// Upper bound of the delay between two back-fill polls of an idle sequencer.
+pub const MAX_IDLE_BACKOFF: Duration = Duration::from_secs(10 * 60);
+
+/// Delay before the next back-fill poll once no block was received from the sequencer for the
+/// idle timeout. The delay doubles every time the sequencer cannot be reached, so that a dead
+/// endpoint is not hammered, and goes back to the idle timeout once it returns.
+#[derive(Clone, Debug)]
+pub struct IdleBackoff {
+    idle_timeout: Duration,
+    max: Duration,
+    delay: Duration,
+}
+
+impl IdleBackoff {
+    pub fn new(idle_timeout: Duration, max: Duration) -> Self {
+        Self {
+            idle_timeout,
+            max: max.max(idle_timeout),
+            delay: idle_timeout,
+        }
+    }
+
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    pub fn increase(&mut self) {
+        self.delay = self.delay.saturating_mul(2).min(self.max);
+    }
+
+    pub fn reset(&mut self) {
+        self.delay = self.idle_timeout;
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StateRootCheckError {
+    #[error(transparent)]
+    Store(#[from] StoreError),
+    #[error("Failed to fetch the sequencer block: {0}")]
+    EthClient(#[from] EthClientError),
+    #[error("Block header {0} is missing from the store")]
+    MissingHeader(u64),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum StateRootCheck {
+    Consistent {
+        block_number: u64,
+    },
+    Diverged {
+        block_number: u64,
+        local: H256,
+        sequencer: H256,
+    },
+}
+
+/// Compares the state root of the latest block of the full node with the one of the sequencer
+/// block of the same number.
+pub async fn check_state_root(
+    store: &Store,
+    eth_client: &EthClient,
+) -> Result<StateRootCheck, StateRootCheckError> {
+    let block_number = store.get_latest_block_number().await?;
+    let local = store
+        .get_block_header(block_number)?
+        .ok_or(StateRootCheckError::MissingHeader(block_number))?
+        .state_root;
+    let sequencer = eth_client
+        .get_block_by_number(BlockIdentifier::Number(block_number))
+        .await?
+        .header
+        .state_root;
+
+    if local == sequencer {
+        Ok(StateRootCheck::Consistent { block_number })
+    } else {
+        Ok(StateRootCheck::Diverged {
+            block_number,
+            local,
+            sequencer,
+        })
+    }
+}
+
+/// Periodically checks that the state of the full node did not diverge from the sequencer's.
+pub fn spawn_state_root_check(
+    store: Store,
+    eth_client: EthClient,
+    interval: Duration,
+    shutdown_token: CancellationToken,
+) -> JoinHandle<()> {
+    tokio::task::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => match check_state_root(&store, &eth_client).await {
+                    Ok(StateRootCheck::Consistent { block_number }) => {
+                        debug!(block_number, "State root matches the sequencer");
+                    }
+                    Ok(StateRootCheck::Diverged { block_number, local, sequencer }) => error!(
+                        block_number,
+                        local = %format!("{local:#x}"),
+                        sequencer = %format!("{sequencer:#x}"),
+                        "CRITICAL: the state of the full node diverged from the sequencer"
+                    ),
+                    Err(error) => warn!(%error, "Failed to check the state root against the sequencer"),
+                },
+                _ = shutdown_token.cancelled() => {
+                    info!("Shutting down state root check");
+                    break;
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{metrics::render_sequencer_health, rpc::tests::build_genesis};
+    use ethrex_common::types::{BlockBody, BlockHeader};
+    use ethrex_rpc::types::block::RpcBlock;
+    use ethrex_storage::EngineType;
+    use serde_json::json;
+
+    fn mock_chain_id(chain_id: u64) -> mockito::Mock {
+        mockito::mock("POST", "/")
+            .with_header("content-type", "application/json")
+            .with_body(format!(
+                r#"{{"jsonrpc":"2.0","id":1,"result":"{chain_id:#x}"}}"#
+            ))
+            .create()
+    }
+
+    fn mock_block(header: &BlockHeader) -> mockito::Mock {
+        let block =
+            RpcBlock::build(header.clone(), BlockBody::default(), header.hash(), true).unwrap();
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": block });
+        mockito::mock("POST", "/")
+            .with_header("content-type", "application/json")
+            .with_body(response.to_string())
+            .create()
+    }
+
+    #[tokio::test]
+    async fn state_root_divergence_is_detected() {
+        let store = Store::new("", EngineType::InMemory).unwrap();
+        store.add_initial_state(build_genesis()).await.unwrap();
+        let header = store.get_block_header(0).unwrap().unwrap();
+        let eth_client = EthClient::new(&mockito::server_url()).unwrap();
+
+        let mock = mock_block(&header);
+        assert_eq!(
+            check_state_root(&store, &eth_client).await.unwrap(),
+            StateRootCheck::Consistent { block_number: 0 }
+        );
+        drop(mock);
+
+        let diverged = BlockHeader {
+            state_root: H256::repeat_byte(0xab),
+            ..header.clone()
+        };
+        let _mock = mock_block(&diverged);
+        assert_eq!(
+            check_state_root(&store, &eth_client).await.unwrap(),
+            StateRootCheck::Diverged {
+                block_number: 0,
+                local: header.state_root,
+                sequencer: diverged.state_root,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn unreachable_sequencer_fails_startup_check() {
+        let eth_client = EthClient::new("http://127.0.0.1:1").unwrap();
+        let health = SequencerHealth::default();
+
+        let result = check_sequencer_at_startup(&eth_client, 1, &health).await;
+
+        assert!(matches!(result, Err(SequencerHealthError::Unreachable(_))));
+        assert!(!health.is_ready());
+        assert!(render_sequencer_health(&health).contains("mojave_sequencer_ready 0\n"));
+    }
+
+    #[tokio::test]
+    async fn sequencer_on_another_chain_fails_startup_check() {
+        let _mock = mock_chain_id(2);
+        let eth_client = EthClient::new(&mockito::server_url()).unwrap();
+        let health = SequencerHealth::default();
+
+        let result = check_sequencer_at_startup(&eth_client, 1, &health).await;
+        assert!(matches!(
+            result,
+            Err(SequencerHealthError::ChainIdMismatch {
+                expected: 1,
+                sequencer: 2
+            })
+        ));
+
+        check_sequencer_at_startup(&eth_client, 2, &health)
+            .await
+            .unwrap();
+        assert!(health.is_ready());
+        assert!(render_sequencer_health(&health).contains("mojave_sequencer_ready 1\n"));
+    }
+}
@@ -1,13 +1,21 @@
-use crate::rpc::{RpcApiContext, types::OrderedBlock};
+use crate::{
+    metrics::RejectReason,
+    rpc::{RpcApiContext, types::OrderedBlock},
+};
 use ethrex_common::types::{Block, BlockBody, Transaction};
 use ethrex_rpc::{
     RpcErr,
     types::{block::RpcBlock, block_identifier::BlockIdentifier},
     utils::RpcRequest,
 };
+use mojave_chain_utils::{rpc::RpcError, unique_heap::PushResult};
 use mojave_client::types::SignedBlock;
-use mojave_signature::Verifier;
+use mojave_signature::AnyVerifyingKey;
 use serde_json::Value;
+use std::{
+    ops::Range,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 pub struct SendBroadcastBlockRequest {
     signed_block: SignedBlock,
@@ -34,40 +42,191 @@ impl SendBroadcastBlockRequest {
         Ok(Self { signed_block })
     }
 
-    pub async fn call(request: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
+    pub async fn call(request: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcError> {
         let data = Self::get_block_data(&request.params)?;
+        handle_signed_block(data.signed_block, &context).await?;
+        Ok(Value::Null)
+    }
+}
 
-        // Check if the signature and sender are valid. If verification fails, return an error
-        // immediately without processing the block.
-        data.signed_block
-            .verifying_key
-            .verify(
-                &data.signed_block.block.header.hash(),
-                &data.signed_block.signature,
-            )
-            .map_err(|error| RpcErr::Internal(error.to_string()))?;
+pub struct SendBroadcastBlockBatchRequest {
+    signed_blocks: Vec<SignedBlock>,
+}
 
-        let latest_block_number = context.l1_context.storage.get_latest_block_number().await? + 1;
-        let signed_block_number = data.signed_block.block.header.number;
-        for block_number in latest_block_number..signed_block_number {
-            let block = context
-                .eth_client
-                .get_block_by_number(BlockIdentifier::Number(block_number))
-                .await
-                .map_err(|error| RpcErr::Internal(error.to_string()))?;
-            let block = rpc_block_to_block(block);
-            context.block_queue.push(OrderedBlock(block)).await;
+impl SendBroadcastBlockBatchRequest {
+    /// Checks the number of blocks against `max_blocks` before deserializing any of them.
+    fn get_block_data(req: &Option<Vec<Value>>, max_blocks: usize) -> Result<Self, RpcError> {
+        let params = req
+            .as_ref()
+            .ok_or(RpcErr::BadParams("No params provided".to_owned()))?;
+
+        if params.len() != 1 {
+            return Err(RpcErr::BadParams(format!(
+                "Expected exactly 1 parameter (an array of SignedBlock), but {} were provided",
+                params.len()
+            ))
+            .into());
         }
 
-        context
-            .block_queue
-            .push(OrderedBlock(data.signed_block.block))
-            .await;
-        tracing::info!("Received the block number: {}", signed_block_number);
+        let signed_blocks = params[0].as_array().ok_or(RpcErr::BadParams(
+            "Expected an array of SignedBlock".to_owned(),
+        ))?;
+        if signed_blocks.len() > max_blocks {
+            return Err(RpcError::LimitExceeded(format!(
+                "Batch of {} blocks exceeds the maximum of {max_blocks} blocks",
+                signed_blocks.len()
+            )));
+        }
+
+        let signed_blocks = signed_blocks
+            .iter()
+            .map(|signed_block| serde_json::from_value::<SignedBlock>(signed_block.clone()))
+            .collect::<Result<_, _>>()
+            .map_err(RpcErr::from)?;
+        Ok(Self { signed_blocks })
+    }
+
+    /// Verifies the signatures of the blocks at once, then handles the blocks in order, as if
+    /// each one was broadcast on its own, and stops at the first one that is rejected.
+    pub async fn call(request: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcError> {
+        let data = Self::get_block_data(&request.params, context.max_broadcast_batch)?;
+        let verified = context
+            .verification_pool
+            .verify_batch(data.signed_blocks)
+            .await?;
+        for verified in verified {
+            if let Ok((signed_block, _)) = &verified {
+                validate_timestamp(&signed_block.block, context.max_clock_skew)?;
+            }
+            accept_verified_block(verified, &context).await?;
+        }
         Ok(Value::Null)
     }
 }
 
+async fn handle_signed_block(
+    signed_block: SignedBlock,
+    context: &RpcApiContext,
+) -> Result<(), RpcError> {
+    validate_timestamp(&signed_block.block, context.max_clock_skew)?;
+    let verified = context.verification_pool.verify(signed_block).await;
+    accept_verified_block(verified, context).await
+}
+
+/// Back-fills the blocks missing before a verified broadcast block and queues it.
+async fn accept_verified_block(
+    verified: Result<(SignedBlock, AnyVerifyingKey), RpcErr>,
+    context: &RpcApiContext,
+) -> Result<(), RpcError> {
+    // Check if the signature and sender are valid. If verification fails, return an error
+    // immediately without processing the block.
+    let (signed_block, sequencer) = verified.inspect_err(|_| {
+        context
+            .broadcast_metrics
+            .record_rejected(RejectReason::InvalidSignature)
+    })?;
+    // Reject replayed broadcasts: the epoch is covered by the signature, so a sequencer's
+    // epochs can only move forward. The epoch is only kept once the block is queued.
+    let epoch = context
+        .broadcast_epochs
+        .advance(&sequencer.to_address(), signed_block.epoch)
+        .inspect_err(|_| {
+            context
+                .broadcast_metrics
+                .record_rejected(RejectReason::ReplayedEpoch)
+        })?;
+    context.broadcast_metrics.record_verified();
+
+    let latest_block_number = context.l1_context.storage.get_latest_block_number().await?;
+    let signed_block_number = signed_block.block.header.number;
+    let backfill_range = context
+        .backfill_policy
+        .backfill_range(latest_block_number, signed_block_number);
+    backfill_blocks(context, backfill_range).await?;
+
+    queue_block(context, signed_block.block).await?;
+    epoch.commit();
+    tracing::info!("Received the block number: {}", signed_block_number);
+    Ok(())
+}
+
+/// Rejects a block timestamped further than `max_clock_skew` in the future, which still accepts
+/// the blocks of a sequencer whose clock is slightly ahead of the local one.
+fn validate_timestamp(block: &Block, max_clock_skew: Duration) -> Result<(), RpcErr> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|error| RpcErr::Internal(error.to_string()))?;
+    let max_timestamp = (now + max_clock_skew).as_secs();
+    if block.header.timestamp > max_timestamp {
+        return Err(RpcErr::BadParams(format!(
+            "Block {} is timestamped {} seconds in the future, more than the tolerated clock skew of {} seconds",
+            block.header.number,
+            block.header.timestamp - now.as_secs(),
+            max_clock_skew.as_secs()
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the number and hash of the blocks waiting to be processed, in processing order, to
+/// debug a stalled queue. As an admin method, it is only served when enabled in the RPC config.
+pub(crate) async fn pending_blocks(
+    request: &RpcRequest,
+    context: &RpcApiContext,
+) -> Result<Value, RpcErr> {
+    if !context.rpc_config.admin_enabled {
+        return Err(RpcErr::MethodNotFound(request.method.clone()));
+    }
+    let blocks: Vec<Value> = context
+        .block_queue
+        .snapshot_sorted()
+        .await
+        .iter()
+        .map(|OrderedBlock(block)| {
+            serde_json::json!({
+                "number": block.header.number,
+                "hash": block.hash(),
+            })
+        })
+        .collect();
+    Ok(Value::Array(blocks))
+}
+
+/// Fetches the blocks of the range from the sequencer and queues them for processing.
+pub(crate) async fn backfill_blocks(
+    context: &RpcApiContext,
+    block_numbers: Range<u64>,
+) -> Result<(), RpcError> {
+    for block_number in block_numbers {
+        let block = context
+            .eth_client
+            .get_block_by_number(BlockIdentifier::Number(block_number))
+            .await
+            .map_err(|error| RpcErr::Internal(error.to_string()))?;
+        queue_block(context, rpc_block_to_block(block)).await?;
+    }
+    Ok(())
+}
+
+/// Queues the block for processing, failing with a limit error when the queue is full of blocks
+/// to process before it. A block already queued is left as is.
+async fn queue_block(context: &RpcApiContext, block: Block) -> Result<(), RpcError> {
+    let number = block.header.number;
+    match context.block_queue.try_push(OrderedBlock(block)).await {
+        PushResult::Inserted | PushResult::Duplicate => Ok(()),
+        PushResult::Replaced => {
+            tracing::warn!(
+                number,
+                "The block queue is full, evicted its highest block to queue this one"
+            );
+            Ok(())
+        }
+        PushResult::Full => Err(RpcError::LimitExceeded(format!(
+            "The block queue is full, block {number} was not queued"
+        ))),
+    }
+}
+
 fn rpc_block_to_block(rpc_block: RpcBlock) -> Block {
     match rpc_block.body {
         ethrex_rpc::types::block::BlockBodyWrapper::Full(full_block_body) => {
@@ -92,3 +251,351 @@ fn rpc_block_to_block(rpc_block: RpcBlock) -> Block {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        metrics::RejectReason,
+        rpc::{
+            tests::{build_genesis, next_block, test_context},
+            types::{BackfillPolicy, VerificationPool},
+        },
+    };
+    use ethrex_rpc::EthClient;
+    use mojave_chain_utils::unique_heap::{AsyncUniqueHeap, OverflowPolicy};
+    use mojave_signature::{AnySigningKey, SignatureScheme, ThresholdVerifier};
+    use serde_json::json;
+
+    fn broadcast_request(signed_block: &SignedBlock) -> RpcRequest {
+        RpcRequest {
+            id: ethrex_rpc::utils::RpcRequestId::Number(1),
+            jsonrpc: "2.0".to_string(),
+            method: "mojave_sendBroadcastBlock".to_string(),
+            params: Some(vec![json!(signed_block)]),
+        }
+    }
+
+    #[tokio::test]
+    async fn replayed_broadcast_is_rejected() {
+        let context = test_context().await;
+        let signing_key = AnySigningKey::from_str_with_scheme(
+            SignatureScheme::Secp256k1,
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .unwrap();
+        let block = next_block(&build_genesis().get_block());
+
+        let signed_block = SignedBlock::new(block.clone(), 10, &signing_key).unwrap();
+        let request = broadcast_request(&signed_block);
+        SendBroadcastBlockRequest::call(&request, context.clone())
+            .await
+            .unwrap();
+
+        // Replaying the same message, or one with an older epoch, is refused.
+        let result = SendBroadcastBlockRequest::call(&request, context.clone()).await;
+        assert!(matches!(result, Err(RpcError::Eth(RpcErr::BadParams(_)))));
+        let stale = SignedBlock::new(block, 5, &signing_key).unwrap();
+        let result = SendBroadcastBlockRequest::call(&broadcast_request(&stale), context).await;
+        assert!(matches!(result, Err(RpcError::Eth(RpcErr::BadParams(_)))));
+    }
+
+    #[tokio::test]
+    async fn broadcast_failing_after_the_epoch_check_can_be_retried() {
+        let mut context = test_context().await;
+        context.eth_client = EthClient::new("http://127.0.0.1:1").unwrap();
+        let signing_key = AnySigningKey::from_str_with_scheme(
+            SignatureScheme::Secp256k1,
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .unwrap();
+        // A block two blocks ahead, whose parent cannot be back-filled.
+        let block = next_block(&next_block(&build_genesis().get_block()));
+        let request = broadcast_request(&SignedBlock::new(block, 10, &signing_key).unwrap());
+
+        let result = SendBroadcastBlockRequest::call(&request, context.clone()).await;
+        assert!(matches!(result, Err(RpcError::Eth(RpcErr::Internal(_)))));
+        assert!(context.block_queue.is_empty().await);
+
+        context.backfill_policy = BackfillPolicy::PrioritizeNewest(0);
+        SendBroadcastBlockRequest::call(&request, context.clone())
+            .await
+            .unwrap();
+        let result = SendBroadcastBlockRequest::call(&request, context).await;
+        assert!(matches!(result, Err(RpcError::Eth(RpcErr::BadParams(_)))));
+    }
+
+    #[tokio::test]
+    async fn broadcast_to_a_full_queue_is_rejected_with_a_limit_error() {
+        let mut context = test_context().await;
+        context.block_queue = AsyncUniqueHeap::with_max_size(1, OverflowPolicy::Reject);
+        let signing_key = AnySigningKey::from_str_with_scheme(
+            SignatureScheme::Secp256k1,
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .unwrap();
+        let first = next_block(&build_genesis().get_block());
+        let second = next_block(&first);
+        context.block_queue.push(OrderedBlock(second)).await;
+
+        let request = broadcast_request(&SignedBlock::new(first, 1, &signing_key).unwrap());
+        let result = SendBroadcastBlockRequest::call(&request, context.clone()).await;
+        assert!(matches!(result, Err(RpcError::LimitExceeded(_))));
+
+        // The block was not queued, so its epoch is accepted again once there is room.
+        context.block_queue.pop().await.unwrap();
+        SendBroadcastBlockRequest::call(&request, context.clone())
+            .await
+            .unwrap();
+        assert_eq!(context.block_queue.len().await, 1);
+    }
+
+    #[tokio::test]
+    async fn retried_broadcast_is_accepted_from_the_verification_cache() {
+        let mut context = test_context().await;
+        context.eth_client = EthClient::new("http://127.0.0.1:1").unwrap();
+        context.verification_pool = VerificationPool::default().with_cache(16);
+        let signing_key = AnySigningKey::from_str_with_scheme(
+            SignatureScheme::Ed25519,
+            "902ddc4259c907ef56ad9be31f6640978eb8d392e18ffde0a569ded804df23e1",
+        )
+        .unwrap();
+        let block = next_block(&next_block(&build_genesis().get_block()));
+        let request = broadcast_request(&SignedBlock::new(block, 1, &signing_key).unwrap());
+
+        assert!(
+            SendBroadcastBlockRequest::call(&request, context.clone())
+                .await
+                .is_err()
+        );
+        context.backfill_policy = BackfillPolicy::PrioritizeNewest(0);
+        SendBroadcastBlockRequest::call(&request, context.clone())
+            .await
+            .unwrap();
+        assert_eq!(context.verification_pool.verifications(), 1);
+        assert_eq!(context.block_queue.len().await, 1);
+    }
+
+    #[tokio::test]
+    async fn broadcast_signed_by_an_unknown_key_is_rejected() {
+        let mut context = test_context().await;
+        let secp256k1 = AnySigningKey::from_str_with_scheme(
+            SignatureScheme::Secp256k1,
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .unwrap();
+        let unknown = AnySigningKey::from_str_with_scheme(
+            SignatureScheme::Secp256k1,
+            "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d",
+        )
+        .unwrap();
+        let verifier = ThresholdVerifier::new(vec![secp256k1.verifying_key()], 1).unwrap();
+        context.verification_pool = VerificationPool::default().with_threshold(verifier);
+        let block = next_block(&build_genesis().get_block());
+
+        // The co-signature reaches the threshold, but the epoch would be tracked for the
+        // unknown key.
+        let mut signed_block = SignedBlock::new(block, 1, &unknown).unwrap();
+        signed_block.co_sign(&secp256k1).unwrap();
+        let result =
+            SendBroadcastBlockRequest::call(&broadcast_request(&signed_block), context.clone())
+                .await;
+        assert!(matches!(result, Err(RpcError::Eth(RpcErr::BadParams(_)))));
+        assert_eq!(context.broadcast_metrics.verified(), 0);
+    }
+
+    #[tokio::test]
+    async fn pending_blocks_are_listed_in_processing_order() {
+        let mut context = test_context().await;
+        let first = next_block(&build_genesis().get_block());
+        let second = next_block(&first);
+        context.block_queue.push(OrderedBlock(second.clone())).await;
+        context.block_queue.push(OrderedBlock(first.clone())).await;
+        let request = RpcRequest {
+            id: ethrex_rpc::utils::RpcRequestId::Number(1),
+            jsonrpc: "2.0".to_string(),
+            method: "mojave_debug_pendingBlocks".to_string(),
+            params: None,
+        };
+
+        let result = pending_blocks(&request, &context).await;
+        assert!(matches!(result, Err(RpcErr::MethodNotFound(_))));
+
+        context.rpc_config.admin_enabled = true;
+        let pending = pending_blocks(&request, &context).await.unwrap();
+        assert_eq!(
+            pending,
+            json!([
+                {"number": first.header.number, "hash": first.hash()},
+                {"number": second.header.number, "hash": second.hash()},
+            ])
+        );
+        assert_eq!(context.block_queue.len().await, 2);
+    }
+
+    #[test]
+    fn future_timestamps_are_only_accepted_within_the_clock_skew() {
+        let max_clock_skew = Duration::from_secs(15);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let mut block = next_block(&build_genesis().get_block());
+
+        validate_timestamp(&block, max_clock_skew).unwrap();
+        block.header.timestamp = now + 5;
+        validate_timestamp(&block, max_clock_skew).unwrap();
+        block.header.timestamp = now + 60;
+        assert!(matches!(
+            validate_timestamp(&block, max_clock_skew),
+            Err(RpcErr::BadParams(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn broadcast_below_the_signature_threshold_is_rejected() {
+        let mut context = test_context().await;
+        let secp256k1 = AnySigningKey::from_str_with_scheme(
+            SignatureScheme::Secp256k1,
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .unwrap();
+        let ed25519 = AnySigningKey::from_str_with_scheme(
+            SignatureScheme::Ed25519,
+            "902ddc4259c907ef56ad9be31f6640978eb8d392e18ffde0a569ded804df23e1",
+        )
+        .unwrap();
+        let verifier =
+            ThresholdVerifier::new(vec![secp256k1.verifying_key(), ed25519.verifying_key()], 2)
+                .unwrap();
+        context.verification_pool = VerificationPool::default().with_threshold(verifier);
+        let block = next_block(&build_genesis().get_block());
+
+        let mut signed_block = SignedBlock::new(block, 1, &secp256k1).unwrap();
+        let result =
+            SendBroadcastBlockRequest::call(&broadcast_request(&signed_block), context.clone())
+                .await;
+        assert!(matches!(result, Err(RpcError::Eth(RpcErr::BadParams(_)))));
+        assert_eq!(
+            context
+                .broadcast_metrics
+                .rejected(RejectReason::InvalidSignature),
+            1
+        );
+
+        signed_block.co_sign(&ed25519).unwrap();
+        SendBroadcastBlockRequest::call(&broadcast_request(&signed_block), context.clone())
+            .await
+            .unwrap();
+        assert_eq!(context.broadcast_metrics.verified(), 1);
+    }
+
+    #[tokio::test]
+    async fn oversized_broadcast_batch_is_rejected_before_parsing() {
+        let mut context = test_context().await;
+        context.max_broadcast_batch = 2;
+        let signing_key = AnySigningKey::from_str_with_scheme(
+            SignatureScheme::Secp256k1,
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .unwrap();
+        let block = next_block(&build_genesis().get_block());
+        let signed_block = SignedBlock::new(block, 1, &signing_key).unwrap();
+
+        // The entries are not even blocks: only their number is looked at.
+        let request = RpcRequest {
+            params: Some(vec![json!(["not", "a", "block"])]),
+            method: "mojave_sendBroadcastBlockBatch".to_string(),
+            ..broadcast_request(&signed_block)
+        };
+        let Err(RpcError::LimitExceeded(message)) =
+            SendBroadcastBlockBatchRequest::call(&request, context.clone()).await
+        else {
+            panic!("the batch should be rejected");
+        };
+        assert!(
+            message.contains("exceeds the maximum of 2 blocks"),
+            "{message}"
+        );
+        assert_eq!(context.broadcast_metrics.verified(), 0);
+
+        let request = RpcRequest {
+            params: Some(vec![json!([signed_block])]),
+            ..request
+        };
+        SendBroadcastBlockBatchRequest::call(&request, context.clone())
+            .await
+            .unwrap();
+        assert_eq!(context.broadcast_metrics.verified(), 1);
+    }
+
+    #[tokio::test]
+    async fn broadcast_batch_is_verified_at_once_and_queued_in_order() {
+        let mut context = test_context().await;
+        // The blocks are only queued, so the chain does not reach them before the next one.
+        context.backfill_policy = BackfillPolicy::PrioritizeNewest(0);
+        let signing_key = AnySigningKey::from_str_with_scheme(
+            SignatureScheme::Ed25519,
+            "902ddc4259c907ef56ad9be31f6640978eb8d392e18ffde0a569ded804df23e1",
+        )
+        .unwrap();
+        let first = next_block(&build_genesis().get_block());
+        let second = next_block(&first);
+        let third = next_block(&second);
+        let mut tampered = SignedBlock::new(third, 3, &signing_key).unwrap();
+        tampered.signature.bytes[0] ^= 1;
+        let signed_blocks = vec![
+            SignedBlock::new(first, 1, &signing_key).unwrap(),
+            SignedBlock::new(second, 2, &signing_key).unwrap(),
+            tampered,
+        ];
+        let request = RpcRequest {
+            params: Some(vec![json!(signed_blocks)]),
+            method: "mojave_sendBroadcastBlockBatch".to_string(),
+            ..broadcast_request(&signed_blocks[0])
+        };
+
+        let result = SendBroadcastBlockBatchRequest::call(&request, context.clone()).await;
+        assert!(matches!(result, Err(RpcError::Eth(RpcErr::BadParams(_)))));
+        assert_eq!(context.verification_pool.verifications(), 3);
+        assert_eq!(context.broadcast_metrics.verified(), 2);
+        assert_eq!(
+            context
+                .broadcast_metrics
+                .rejected(RejectReason::InvalidSignature),
+            1
+        );
+        assert_eq!(context.block_queue.len().await, 2);
+    }
+
+    #[tokio::test]
+    async fn tampered_broadcast_increments_the_rejection_counter() {
+        let context = test_context().await;
+        let signing_key = AnySigningKey::from_str_with_scheme(
+            SignatureScheme::Ed25519,
+            "902ddc4259c907ef56ad9be31f6640978eb8d392e18ffde0a569ded804df23e1",
+        )
+        .unwrap();
+        let block = next_block(&build_genesis().get_block());
+
+        let mut tampered = SignedBlock::new(block.clone(), 1, &signing_key).unwrap();
+        tampered.signature.bytes[0] ^= 1;
+        let result =
+            SendBroadcastBlockRequest::call(&broadcast_request(&tampered), context.clone()).await;
+        assert!(matches!(result, Err(RpcError::Eth(RpcErr::BadParams(_)))));
+
+        let metrics = &context.broadcast_metrics;
+        assert_eq!(metrics.rejected(RejectReason::InvalidSignature), 1);
+        assert_eq!(metrics.rejected(RejectReason::ReplayedEpoch), 0);
+        assert_eq!(metrics.verified(), 0);
+
+        let signed_block = SignedBlock::new(block, 2, &signing_key).unwrap();
+        SendBroadcastBlockRequest::call(&broadcast_request(&signed_block), context.clone())
+            .await
+            .unwrap();
+        assert_eq!(metrics.verified(), 1);
+        assert!(metrics.render().contains(
+            "mojave_broadcast_block_verifications_total{result=\"rejected\",reason=\"invalid_signature\"} 1"
+        ));
+    }
+}
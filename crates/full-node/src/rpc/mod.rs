@@ -2,36 +2,55 @@ pub mod block;
 pub mod transaction;
 pub mod types;
 
-use crate::rpc::{
-    block::SendBroadcastBlockRequest, transaction::SendRawTransactionRequest, types::OrderedBlock,
+use crate::{
+    metrics::{BroadcastMetrics, metrics_router},
+    monitor::{IdleBackoff, MAX_IDLE_BACKOFF, SequencerHealth},
+    rpc::{
+        block::{
+            SendBroadcastBlockBatchRequest, SendBroadcastBlockRequest, backfill_blocks,
+            pending_blocks,
+        },
+        transaction::SendRawTransactionRequest,
+        types::{BackfillPolicy, BroadcastEpochs, OrderedBlock, VerificationPool},
+    },
 };
-use axum::{Json, Router, extract::State, http::StatusCode, routing::post};
-use ethrex_blockchain::Blockchain;
-use ethrex_common::Bytes;
-use ethrex_p2p::{
-    peer_handler::PeerHandler,
-    sync_manager::SyncManager,
-    types::{Node, NodeRecord},
+use axum::{
+    Json, Router,
+    extract::{ConnectInfo, State},
+    http::StatusCode,
+    middleware::from_fn_with_state,
+    routing::post,
 };
+use ethrex_blockchain::Blockchain;
+use ethrex_common::types::Block;
+use ethrex_p2p::{kademlia::KademliaTable, peer_handler::PeerHandler, sync_manager::SyncManager};
 use ethrex_rpc::{
     ActiveFilters, EthClient, GasTipEstimator, NodeData, RpcApiContext as L1Context, RpcErr,
-    RpcRequestWrapper, rpc_response,
     utils::{RpcRequest, RpcRequestId},
 };
-use ethrex_storage::Store;
+use ethrex_storage::{Store, error::StoreError};
 use ethrex_storage_rollup::StoreRollup;
-use mojave_chain_utils::unique_heap::AsyncUniqueHeap;
+use mojave_chain_utils::{
+    rpc::{
+        FilterQuotas, LogQueryLimiter, RequestLogger, RpcConfig, RpcError, RpcRequestBody,
+        authrpc_router, batch_response, get_chain_config, get_peers, is_filter_creation,
+        is_log_query, log_requests, map_net_requests, new_filter, process_batch,
+        reject_oversized_requests, request_size_limit, rpc_response, validate_get_proof,
+    },
+    unique_heap::{AsyncUniqueHeap, OverflowPolicy},
+};
+use mojave_client::{ForwardStrategy, MojaveClient};
+use mojave_signature::ThresholdVerifier;
 use serde_json::Value;
 use std::{
     collections::HashMap,
-    net::SocketAddr,
+    net::{IpAddr, SocketAddr},
     sync::{Arc, Mutex},
     time::Duration,
 };
 use tokio::{net::TcpListener, sync::Mutex as TokioMutex, task::JoinHandle};
 use tokio_util::sync::CancellationToken;
-use tower_http::cors::CorsLayer;
-use tracing::info;
+use tracing::{info, warn};
 
 pub const FILTER_DURATION: Duration = {
     if cfg!(test) {
@@ -44,72 +63,186 @@ pub const FILTER_DURATION: Duration = {
 #[derive(Clone, Debug)]
 pub struct RpcApiContext {
     pub l1_context: L1Context,
+    pub peer_table: Arc<TokioMutex<KademliaTable>>,
     pub rollup_store: StoreRollup,
     pub eth_client: EthClient,
+    pub mojave_client: MojaveClient,
+    pub forward_strategy: ForwardStrategy,
     pub block_queue: AsyncUniqueHeap<OrderedBlock, u64>,
+    pub broadcast_epochs: BroadcastEpochs,
+    pub verification_pool: VerificationPool,
+    pub broadcast_metrics: BroadcastMetrics,
+    /// How far in the future a broadcast block may be timestamped.
+    pub max_clock_skew: Duration,
+    /// Maximum number of blocks of a `mojave_sendBroadcastBlockBatch`.
+    pub max_broadcast_batch: usize,
+    pub backfill_policy: BackfillPolicy,
+    /// Maximum number of blocks a block below the head may rewind it by.
+    pub max_reorg_depth: u64,
+    pub rpc_config: RpcConfig,
+    pub filter_quotas: FilterQuotas,
+    pub log_queries: LogQueryLimiter,
+}
+
+/// Settings of the full node RPC server and of the processing of the broadcast blocks.
+#[derive(Clone, Debug)]
+pub struct ApiConfig {
+    pub http_addr: SocketAddr,
+    pub authrpc_addr: SocketAddr,
+    /// The metrics server is only started with an address.
+    pub metrics_addr: Option<SocketAddr>,
+    pub forward_strategy: ForwardStrategy,
+    /// Maximum number of blocks waiting to be processed, 0 for no limit. A full queue evicts its
+    /// highest block for a lower one.
+    pub block_queue_max_size: usize,
+    /// How long without a broadcast block before the missing blocks are polled from the
+    /// sequencer, never without a timeout.
+    pub idle_timeout: Option<Duration>,
+    /// Number of broadcast block signatures verified at once.
+    pub verification_workers: usize,
+    /// Number of verified broadcast block signatures remembered, 0 for none.
+    pub verification_cache_size: usize,
+    /// Keys of the sequencers a broadcast block must be signed by, any key being accepted
+    /// without them.
+    pub sequencer_threshold: Option<ThresholdVerifier>,
+    /// How far in the future a broadcast block may be timestamped.
+    pub max_clock_skew: Duration,
+    /// Maximum number of blocks of a `mojave_sendBroadcastBlockBatch`.
+    pub max_broadcast_batch: usize,
+    pub backfill_policy: BackfillPolicy,
+    /// Maximum number of blocks a block below the head may rewind it by.
+    pub max_reorg_depth: u64,
+    pub rpc_config: RpcConfig,
+}
+
+/// Parts of the node the RPC server serves the requests with.
+pub struct ApiServices {
+    pub storage: Store,
+    pub blockchain: Arc<Blockchain>,
+    pub node_data: NodeData,
+    pub syncer: SyncManager,
+    pub peer_handler: PeerHandler,
+    pub peer_table: Arc<TokioMutex<KademliaTable>>,
+    pub rollup_store: StoreRollup,
+    /// Client of the sequencer's RPC server.
+    pub eth_client: EthClient,
+    pub mojave_client: MojaveClient,
+    /// Readiness of the sequencer endpoint, exported by the metrics server.
+    pub sequencer_health: SequencerHealth,
 }
 
-#[expect(clippy::too_many_arguments)]
 pub async fn start_api(
-    http_addr: SocketAddr,
-    authrpc_addr: SocketAddr,
-    storage: Store,
-    blockchain: Arc<Blockchain>,
-    jwt_secret: Bytes,
-    local_p2p_node: Node,
-    local_node_record: NodeRecord,
-    syncer: SyncManager,
-    peer_handler: PeerHandler,
-    client_version: String,
-    rollup_store: StoreRollup,
-    eth_client: EthClient,
-    block_queue: AsyncUniqueHeap<OrderedBlock, u64>,
+    config: ApiConfig,
+    services: ApiServices,
     shutdown_token: CancellationToken,
 ) -> Result<(), RpcErr> {
+    let ApiConfig {
+        http_addr,
+        authrpc_addr,
+        metrics_addr,
+        idle_timeout,
+        sequencer_threshold,
+        rpc_config,
+        ..
+    } = config;
     let active_filters = Arc::new(Mutex::new(HashMap::new()));
     let context = RpcApiContext {
         l1_context: L1Context {
-            storage,
-            blockchain,
+            storage: services.storage,
+            blockchain: services.blockchain,
             active_filters: active_filters.clone(),
-            syncer: Arc::new(syncer),
-            peer_handler,
-            node_data: NodeData {
-                jwt_secret,
-                local_p2p_node,
-                local_node_record,
-                client_version,
-            },
+            syncer: Arc::new(services.syncer),
+            peer_handler: services.peer_handler,
+            node_data: services.node_data,
             gas_tip_estimator: Arc::new(TokioMutex::new(GasTipEstimator::new())),
         },
-        rollup_store,
-        eth_client,
-        block_queue,
+        peer_table: services.peer_table,
+        rollup_store: services.rollup_store,
+        eth_client: services.eth_client,
+        mojave_client: services.mojave_client,
+        forward_strategy: config.forward_strategy,
+        block_queue: match config.block_queue_max_size {
+            0 => AsyncUniqueHeap::new(),
+            max_size => AsyncUniqueHeap::with_max_size(max_size, OverflowPolicy::EvictLowest),
+        },
+        broadcast_epochs: match &sequencer_threshold {
+            Some(verifier) => BroadcastEpochs::with_capacity(verifier.keys().len()),
+            None => BroadcastEpochs::new(),
+        },
+        verification_pool: {
+            let pool = VerificationPool::new(config.verification_workers)
+                .with_cache(config.verification_cache_size);
+            match sequencer_threshold {
+                Some(verifier) => pool.with_threshold(verifier),
+                None => pool,
+            }
+        },
+        broadcast_metrics: BroadcastMetrics::default(),
+        max_clock_skew: config.max_clock_skew,
+        max_broadcast_batch: config.max_broadcast_batch,
+        backfill_policy: config.backfill_policy,
+        max_reorg_depth: config.max_reorg_depth,
+        log_queries: LogQueryLimiter::new(&rpc_config),
+        rpc_config,
+        filter_quotas: FilterQuotas::default(),
     };
 
     // Periodically clean up the active filters for the filters endpoints.
     let filter_handle = spawn_filter_cleanup_task(active_filters.clone(), shutdown_token.clone());
-    let block_handle = spawn_block_processing_task(context.clone(), shutdown_token.clone());
-
-    // All request headers allowed.
-    // All methods allowed.
-    // All origins allowed.
-    // All headers exposed.
-    let cors = CorsLayer::permissive();
+    let block_handle =
+        spawn_block_processing_task(context.clone(), idle_timeout, shutdown_token.clone());
 
     let http_router = Router::new()
         .route("/", post(handle_http_request))
-        .layer(cors)
+        .layer(from_fn_with_state(
+            RequestLogger::new(context.rpc_config.logging.clone()),
+            log_requests,
+        ))
+        .layer(request_size_limit(&context.rpc_config))
+        .layer(from_fn_with_state(
+            context.rpc_config.max_request_bytes,
+            reject_oversized_requests,
+        ))
+        .layer(context.rpc_config.cors.layer())
         .with_state(context.clone());
     let http_listener = TcpListener::bind(http_addr)
         .await
         .map_err(|error| RpcErr::Internal(error.to_string()))?;
-    let http_server = axum::serve(http_listener, http_router)
-        .with_graceful_shutdown(ethrex_rpc::shutdown_signal())
-        .into_future();
+    let http_server = axum::serve(
+        http_listener,
+        http_router.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_token.clone().cancelled_owned())
+    .into_future();
     info!("Starting HTTP server at {http_addr}");
 
-    info!("Not starting Auth-RPC server. The address passed as argument is {authrpc_addr}");
+    if let Some(metrics_addr) = metrics_addr {
+        let metrics_listener = TcpListener::bind(metrics_addr)
+            .await
+            .map_err(|error| RpcErr::Internal(error.to_string()))?;
+        let metrics_server = axum::serve(
+            metrics_listener,
+            metrics_router(context.broadcast_metrics.clone(), services.sequencer_health),
+        )
+        .with_graceful_shutdown(shutdown_token.clone().cancelled_owned());
+        tokio::spawn(async move {
+            if let Err(error) = metrics_server.await {
+                warn!("Metrics server stopped: {error}");
+            }
+        });
+        info!("Starting metrics server at {metrics_addr}");
+    }
+
+    let authrpc_listener = TcpListener::bind(authrpc_addr)
+        .await
+        .map_err(|error| RpcErr::Internal(error.to_string()))?;
+    let authrpc_server = axum::serve(
+        authrpc_listener,
+        authrpc_router(context.l1_context.clone(), &context.rpc_config),
+    )
+    .with_graceful_shutdown(shutdown_token.clone().cancelled_owned())
+    .into_future();
+    info!("Starting Auth-RPC server at {authrpc_addr}");
 
     let _ = tokio::try_join!(
         async {
@@ -117,6 +250,11 @@ pub async fn start_api(
                 .await
                 .map_err(|e| RpcErr::Internal(e.to_string()))
         },
+        async {
+            authrpc_server
+                .await
+                .map_err(|e| RpcErr::Internal(e.to_string()))
+        },
         async {
             filter_handle
                 .await
@@ -155,15 +293,43 @@ fn spawn_filter_cleanup_task(
     })
 }
 
+/// Processes the queued blocks in order. Once no block was received for `idle_timeout`, the
+/// missing blocks are polled from the sequencer with an exponential backoff while it is down.
 fn spawn_block_processing_task(
     context: RpcApiContext,
+    idle_timeout: Option<Duration>,
     shutdown_token: CancellationToken,
 ) -> JoinHandle<()> {
     tokio::task::spawn(async move {
         tracing::info!("Starting block processing loop");
+        let mut idle_backoff =
+            idle_timeout.map(|idle_timeout| IdleBackoff::new(idle_timeout, MAX_IDLE_BACKOFF));
         loop {
+            let idle_delay = idle_backoff.as_ref().map(IdleBackoff::delay);
             tokio::select! {
-                block = context.block_queue.pop_wait() => {
+                block = context.block_queue.pop_wait_cancellable(&shutdown_token) => {
+                    let Some(block) = block else {
+                        tracing::info!("Shutting down block processing loop");
+                        break;
+                    };
+                    if let Some(idle_backoff) = idle_backoff.as_mut() {
+                        idle_backoff.reset();
+                    }
+                    match is_stale_block(&context, &block.0).await {
+                        Ok(false) => {}
+                        Ok(true) => {
+                            tracing::warn!(
+                                number = block.0.header.number,
+                                hash = %block.0.hash(),
+                                "Ignoring a block that would rewind the head"
+                            );
+                            continue;
+                        }
+                        Err(error) => {
+                            tracing::error!(%error, "failed to compare the block to the head");
+                            continue;
+                        }
+                    }
                     let added_block = context.l1_context.blockchain.add_block(&block.0).await;
                     if let Err(added_block) = added_block {
                         tracing::error!(error= %added_block, "failed to add block to blockchain");
@@ -194,69 +360,167 @@ fn spawn_block_processing_task(
                         tracing::error!(error = %forkchoice_context, "failed to update forkchoice");
                     }
                 }
-                _ = shutdown_token.cancelled() => {
-                    tracing::info!("Shutting down block processing loop");
-                    break;
+                _ = tokio::time::sleep(idle_delay.unwrap_or_default()), if idle_delay.is_some() => {
+                    if let Some(idle_backoff) = idle_backoff.as_mut() {
+                        backfill_after_idle(&context, idle_backoff).await;
+                    }
                 }
             }
         }
     })
 }
 
+/// Returns whether the block is below the head and either already canonical, e.g. a replay, or
+/// deeper than [`RpcApiContext::max_reorg_depth`], in which case making it the head would
+/// rewind the chain.
+async fn is_stale_block(context: &RpcApiContext, block: &Block) -> Result<bool, StoreError> {
+    let storage = &context.l1_context.storage;
+    let head = storage.get_latest_block_number().await?;
+    let number = block.header.number;
+    if number >= head {
+        return Ok(false);
+    }
+    if head - number > context.max_reorg_depth {
+        return Ok(true);
+    }
+    Ok(storage.get_canonical_block_hash(number).await? == Some(block.hash()))
+}
+
+/// Back-fills the blocks the sequencer produced since the latest processed one, backing off
+/// further if it cannot be reached.
+async fn backfill_after_idle(context: &RpcApiContext, idle_backoff: &mut IdleBackoff) {
+    warn!(
+        idle_secs = idle_backoff.delay().as_secs(),
+        "No block received from the sequencer, polling it"
+    );
+    let result = async {
+        let sequencer_block_number = context
+            .eth_client
+            .get_block_number()
+            .await
+            .map_err(|error| RpcErr::Internal(error.to_string()))?
+            .as_u64();
+        let latest_block_number = context.l1_context.storage.get_latest_block_number().await?;
+        backfill_blocks(context, latest_block_number + 1..sequencer_block_number + 1).await
+    }
+    .await;
+    match result {
+        Ok(()) => idle_backoff.reset(),
+        Err(error) => {
+            idle_backoff.increase();
+            warn!(
+                %error,
+                next_poll_secs = idle_backoff.delay().as_secs(),
+                "Sequencer is unreachable, backing off"
+            );
+        }
+    }
+}
+
 async fn handle_http_request(
     State(service_context): State<RpcApiContext>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
     body: String,
 ) -> Result<Json<Value>, StatusCode> {
-    let res = match serde_json::from_str::<RpcRequestWrapper>(&body) {
-        Ok(RpcRequestWrapper::Single(request)) => {
-            let res = map_http_requests(&request, service_context).await;
+    let client = client.ip();
+    let res = match RpcRequestBody::parse(&body, service_context.rpc_config.max_batch_size) {
+        Ok(RpcRequestBody::Single(request)) => {
+            let res = map_http_requests(&request, service_context, client).await;
             rpc_response(request.id, res).map_err(|_| StatusCode::BAD_REQUEST)?
         }
-        Ok(RpcRequestWrapper::Multiple(requests)) => {
-            let mut responses = Vec::new();
-            for req in requests {
-                let res = map_http_requests(&req, service_context.clone()).await;
-                responses.push(rpc_response(req.id, res).map_err(|_| StatusCode::BAD_REQUEST)?);
-            }
-            serde_json::to_value(responses).map_err(|_| StatusCode::BAD_REQUEST)?
+        Ok(RpcRequestBody::Batch(entries)) => {
+            let requests: Vec<&RpcRequest> = entries
+                .iter()
+                .filter_map(|entry| entry.as_ref().ok())
+                .collect();
+            let results = process_batch(
+                &requests,
+                service_context.rpc_config.batch_concurrency,
+                is_state_mutating,
+                |req| map_http_requests(req, service_context.clone(), client),
+            )
+            .await;
+            batch_response(entries, results).map_err(|_| StatusCode::BAD_REQUEST)?
         }
-        Err(_) => rpc_response(
-            RpcRequestId::String("".to_string()),
-            Err(RpcErr::BadParams("Invalid request body".to_string())),
-        )
-        .map_err(|_| StatusCode::BAD_REQUEST)?,
+        Err(error) => rpc_response(RpcRequestId::String("".to_string()), Err(error))
+            .map_err(|_| StatusCode::BAD_REQUEST)?,
     };
     Ok(Json(res))
 }
 
-async fn map_http_requests(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
+/// Returns whether the method changes the state, in which case it must not run concurrently with
+/// other requests of its batch, e.g. to apply the broadcast blocks in order.
+fn is_state_mutating(method: &str) -> bool {
+    matches!(
+        method,
+        "eth_sendRawTransaction" | "mojave_sendBroadcastBlock" | "mojave_sendBroadcastBlockBatch"
+    )
+}
+
+async fn map_http_requests(
+    req: &RpcRequest,
+    context: RpcApiContext,
+    client: IpAddr,
+) -> Result<Value, RpcError> {
     match RpcNamespace::resolve_namespace(req) {
-        Ok(RpcNamespace::Eth) => map_eth_requests(req, context).await,
+        Ok(RpcNamespace::Eth) => map_eth_requests(req, context, client).await,
         Ok(RpcNamespace::Mojave) => map_mojave_requests(req, context).await,
-        Err(error) => Err(error),
+        Ok(RpcNamespace::Net) => Ok(map_net_requests(req, &context.l1_context.storage)?),
+        Err(error) => Err(error.into()),
     }
 }
 
-pub async fn map_eth_requests(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
+pub async fn map_eth_requests(
+    req: &RpcRequest,
+    context: RpcApiContext,
+    client: IpAddr,
+) -> Result<Value, RpcError> {
     match req.method.as_str() {
-        "eth_sendRawTransaction" => SendRawTransactionRequest::call(req, context).await,
-        _others => ethrex_rpc::map_eth_requests(req, context.l1_context).await,
+        "eth_sendRawTransaction" => Ok(SendRawTransactionRequest::call(req, context).await?),
+        "eth_getProof" => {
+            validate_get_proof(req, context.rpc_config.max_proof_storage_keys)?;
+            Ok(ethrex_rpc::map_eth_requests(req, context.l1_context).await?)
+        }
+        method if is_filter_creation(method) => {
+            new_filter(
+                req,
+                context.l1_context,
+                client,
+                &context.filter_quotas,
+                &context.rpc_config,
+            )
+            .await
+        }
+        method if is_log_query(method) => {
+            context
+                .log_queries
+                .run(ethrex_rpc::map_eth_requests(req, context.l1_context))
+                .await
+        }
+        _others => Ok(ethrex_rpc::map_eth_requests(req, context.l1_context).await?),
     }
 }
 
 pub async fn map_mojave_requests(
     req: &RpcRequest,
     context: RpcApiContext,
-) -> Result<Value, RpcErr> {
+) -> Result<Value, RpcError> {
     match req.method.as_str() {
         "mojave_sendBroadcastBlock" => SendBroadcastBlockRequest::call(req, context).await,
-        others => Err(RpcErr::MethodNotFound(others.to_owned())),
+        "mojave_sendBroadcastBlockBatch" => {
+            SendBroadcastBlockBatchRequest::call(req, context).await
+        }
+        "mojave_getPeers" => Ok(get_peers(&context.peer_table, &context.rpc_config).await?),
+        "mojave_getChainConfig" => Ok(get_chain_config(&context.l1_context.storage)?),
+        "mojave_debug_pendingBlocks" => Ok(pending_blocks(req, &context).await?),
+        others => Err(RpcErr::MethodNotFound(others.to_owned()).into()),
     }
 }
 
 pub enum RpcNamespace {
     Eth,
     Mojave,
+    Net,
 }
 
 impl RpcNamespace {
@@ -268,13 +532,14 @@ impl RpcNamespace {
         match namespace {
             "eth" => Ok(Self::Eth),
             "mojave" => Ok(Self::Mojave),
+            "net" => Ok(Self::Net),
             _others => Err(RpcErr::MethodNotFound(request.method.to_owned())),
         }
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use ethrex_common::{
         Address, Bloom, Bytes, H256, H512, U256,
@@ -294,6 +559,8 @@ mod tests {
     use ethrex_storage::{EngineType, Store};
     use ethrex_storage_rollup::{EngineTypeRollup, StoreRollup};
     use mojave_chain_utils::unique_heap::AsyncUniqueHeap;
+    use mojave_client::types::SignedBlock;
+    use mojave_signature::{AnySigningKey, SignatureScheme};
     use std::{
         collections::{BTreeMap, HashMap},
         net::{IpAddr, Ipv4Addr},
@@ -303,7 +570,9 @@ mod tests {
     use tokio::sync::Mutex as TokioMutex;
     use tokio_util::sync::CancellationToken;
 
-    fn build_genesis() -> Genesis {
+    const CLIENT: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    pub(crate) fn build_genesis() -> Genesis {
         Genesis {
             config: ChainConfig {
                 chain_id: 1,
@@ -325,7 +594,7 @@ mod tests {
         }
     }
 
-    fn next_block(parent: &Block) -> Block {
+    pub(super) fn next_block(parent: &Block) -> Block {
         let parent_header = &parent.header;
         let base_fee = calculate_base_fee_per_gas(
             parent_header.gas_limit,
@@ -368,27 +637,21 @@ mod tests {
         Block::new(header, body)
     }
 
-    #[tokio::test]
-    async fn block_processing_updates_storage_and_blockchain() {
+    /// Returns a context over an in-memory store initialized with [`build_genesis`].
+    pub(super) async fn test_context() -> RpcApiContext {
         let store = Store::new("", EngineType::InMemory).unwrap();
-        let genesis = build_genesis();
-        store.add_initial_state(genesis.clone()).await.unwrap();
-        let genesis_block = genesis.get_block();
+        store.add_initial_state(build_genesis()).await.unwrap();
         let blockchain = Arc::new(Blockchain::default_with_store(store.clone()));
 
         let rollup_store = StoreRollup::new("", EngineTypeRollup::InMemory).unwrap();
         rollup_store.init().await.unwrap();
         let eth_client = EthClient::new("http://localhost:8545").unwrap();
 
-        let block_queue = AsyncUniqueHeap::new();
-        let block = next_block(&genesis_block);
-        block_queue.push(OrderedBlock(block.clone())).await;
-
         let active_filters: ActiveFilters = Arc::new(Mutex::new(HashMap::new()));
         let l1_context = L1Context {
-            storage: store.clone(),
-            blockchain: blockchain.clone(),
-            active_filters: active_filters.clone(),
+            storage: store,
+            blockchain,
+            active_filters,
             syncer: Arc::new(SyncManager::dummy()),
             peer_handler: PeerHandler::dummy(),
             node_data: NodeData {
@@ -403,15 +666,55 @@ mod tests {
             },
             gas_tip_estimator: Arc::new(TokioMutex::new(GasTipEstimator::new())),
         };
-        let context = RpcApiContext {
+        RpcApiContext {
             l1_context,
+            peer_table: ethrex_p2p::network::peer_table(H512::zero()),
             rollup_store,
             eth_client,
-            block_queue: block_queue.clone(),
-        };
+            mojave_client: MojaveClient::without_signing_key(&["http://localhost:8545".to_owned()])
+                .unwrap(),
+            forward_strategy: ForwardStrategy::default(),
+            block_queue: AsyncUniqueHeap::new(),
+            broadcast_epochs: BroadcastEpochs::new(),
+            verification_pool: VerificationPool::default(),
+            broadcast_metrics: BroadcastMetrics::default(),
+            max_clock_skew: Duration::from_secs(15),
+            max_broadcast_batch: 64,
+            backfill_policy: BackfillPolicy::default(),
+            max_reorg_depth: 0,
+            rpc_config: RpcConfig::default(),
+            filter_quotas: FilterQuotas::default(),
+            log_queries: LogQueryLimiter::new(&RpcConfig::default()),
+        }
+    }
+
+    #[tokio::test]
+    async fn idle_backoff_increases_while_the_sequencer_is_down() {
+        let mut context = test_context().await;
+        context.eth_client = EthClient::new("http://127.0.0.1:1").unwrap();
+        let mut idle_backoff = IdleBackoff::new(Duration::from_secs(1), Duration::from_secs(8));
+
+        let mut delays = Vec::new();
+        for _ in 0..5 {
+            backfill_after_idle(&context, &mut idle_backoff).await;
+            delays.push(idle_backoff.delay().as_secs());
+        }
+        assert_eq!(delays, vec![2, 4, 8, 8, 8]);
+
+        idle_backoff.reset();
+        assert_eq!(idle_backoff.delay(), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn block_processing_updates_storage_and_blockchain() {
+        let context = test_context().await;
+        let genesis_block = build_genesis().get_block();
+
+        let block = next_block(&genesis_block);
+        context.block_queue.push(OrderedBlock(block.clone())).await;
 
         let cancel_token = CancellationToken::new();
-        let handle = spawn_block_processing_task(context.clone(), cancel_token.clone());
+        let handle = spawn_block_processing_task(context.clone(), None, cancel_token.clone());
         tokio::time::timeout(Duration::from_secs(1), async {
             while !context.block_queue.is_empty().await {
                 tokio::time::sleep(Duration::from_millis(10)).await;
@@ -452,4 +755,247 @@ mod tests {
             .unwrap();
         assert_eq!(canonical_hash, block.hash());
     }
+
+    #[tokio::test]
+    async fn stale_block_does_not_rewind_the_head() {
+        let context = test_context().await;
+        let genesis_block = build_genesis().get_block();
+        let first = next_block(&genesis_block);
+        let second = next_block(&first);
+        let third = next_block(&second);
+        for block in [&first, &second, &third] {
+            context
+                .l1_context
+                .blockchain
+                .add_block(block)
+                .await
+                .unwrap();
+            context
+                .l1_context
+                .storage
+                .forkchoice_update(None, block.header.number, block.hash(), None, None)
+                .await
+                .unwrap();
+        }
+
+        // Replays of the past blocks, the first one being below any reorg depth.
+        assert!(is_stale_block(&context, &first).await.unwrap());
+        let mut deep_reorgs = context.clone();
+        deep_reorgs.max_reorg_depth = 8;
+        assert!(is_stale_block(&deep_reorgs, &second).await.unwrap());
+        assert!(!is_stale_block(&context, &next_block(&third)).await.unwrap());
+
+        context.block_queue.push(OrderedBlock(first.clone())).await;
+        let cancel_token = CancellationToken::new();
+        let handle = spawn_block_processing_task(context.clone(), None, cancel_token.clone());
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while !context.block_queue.is_empty().await {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("block not processed");
+        cancel_token.cancel();
+        handle.await.unwrap();
+
+        let storage = &context.l1_context.storage;
+        assert_eq!(
+            storage.get_latest_block_number().await.unwrap(),
+            third.header.number
+        );
+        assert_eq!(
+            storage
+                .get_canonical_block_hash(third.header.number)
+                .await
+                .unwrap(),
+            Some(third.hash())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn concurrent_broadcasts_do_not_starve_other_requests() {
+        let context = test_context().await;
+        let block = next_block(&build_genesis().get_block());
+        let request = |method: &str, params: Option<Vec<Value>>| RpcRequest {
+            id: RpcRequestId::Number(1),
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+        };
+
+        // One sequencer key per broadcast, so that no broadcast is rejected as a replay.
+        let broadcasts: Vec<_> = (1..=64u8)
+            .map(|byte| {
+                let signing_key = AnySigningKey::from_str_with_scheme(
+                    SignatureScheme::Secp256k1,
+                    &hex::encode([byte; 32]),
+                )
+                .unwrap();
+                let mut signed_block = SignedBlock::new(block.clone(), 1, &signing_key).unwrap();
+                if byte % 8 == 0 {
+                    signed_block.signature.bytes[0] ^= 1;
+                    signed_block.verifying_key = Some(signing_key.verifying_key());
+                }
+                let request = request(
+                    "mojave_sendBroadcastBlock",
+                    Some(vec![serde_json::json!(signed_block)]),
+                );
+                let context = context.clone();
+                tokio::spawn(async move { map_http_requests(&request, context, CLIENT).await })
+            })
+            .collect();
+
+        let block_number = tokio::time::timeout(
+            Duration::from_secs(1),
+            map_http_requests(&request("eth_blockNumber", None), context.clone(), CLIENT),
+        )
+        .await
+        .expect("eth_blockNumber starved by the broadcasts")
+        .unwrap();
+        assert_eq!(block_number, "0x0");
+
+        for (index, broadcast) in broadcasts.into_iter().enumerate() {
+            let result = broadcast.await.unwrap();
+            // The tampered broadcasts carry their key, so their signature fails verification.
+            assert_eq!(result.is_ok(), (index + 1) % 8 != 0);
+        }
+        assert_eq!(context.block_queue.len().await, 1);
+    }
+
+    #[tokio::test]
+    async fn net_version_matches_chain_id() {
+        let context = test_context().await;
+        let request = |method: &str| RpcRequest {
+            id: RpcRequestId::Number(1),
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params: None,
+        };
+
+        let net_version = map_http_requests(&request("net_version"), context.clone(), CLIENT)
+            .await
+            .unwrap();
+        let chain_id = map_http_requests(&request("eth_chainId"), context, CLIENT)
+            .await
+            .unwrap();
+
+        let net_version: u64 = net_version.as_str().unwrap().parse().unwrap();
+        let chain_id =
+            u64::from_str_radix(chain_id.as_str().unwrap().trim_start_matches("0x"), 16).unwrap();
+        assert_eq!(net_version, chain_id);
+        assert_eq!(net_version, build_genesis().config.chain_id);
+    }
+
+    #[tokio::test]
+    async fn malformed_batch_entry_only_fails_itself() {
+        let context = test_context().await;
+        let body = r#"[
+            {"jsonrpc":"2.0","id":1,"method":"net_version"},
+            {"jsonrpc":"2.0","id":2,"method":5}
+        ]"#;
+
+        let Json(responses) = handle_http_request(
+            State(context),
+            ConnectInfo(SocketAddr::new(CLIENT, 0)),
+            body.to_string(),
+        )
+        .await
+        .unwrap();
+
+        let responses = responses.as_array().unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[0]["result"], "1");
+        assert_eq!(responses[1]["id"], 2);
+        assert!(responses[1].get("error").is_some());
+    }
+
+    #[tokio::test]
+    async fn batch_over_the_maximum_size_is_rejected_as_a_whole() {
+        let mut context = test_context().await;
+        context.rpc_config.max_batch_size = 2;
+        let body = r#"[
+            {"jsonrpc":"2.0","id":1,"method":"net_version"},
+            {"jsonrpc":"2.0","id":2,"method":"net_version"},
+            {"jsonrpc":"2.0","id":3,"method":"net_version"}
+        ]"#;
+
+        let Json(response) = handle_http_request(
+            State(context),
+            ConnectInfo(SocketAddr::new(CLIENT, 0)),
+            body.to_string(),
+        )
+        .await
+        .unwrap();
+
+        assert!(!response.is_array());
+        assert!(response.get("result").is_none());
+        assert_eq!(
+            response["error"]["code"],
+            mojave_chain_utils::rpc::LIMIT_EXCEEDED_CODE
+        );
+        assert!(
+            response["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains("exceeds the maximum of 2")
+        );
+    }
+
+    #[tokio::test]
+    async fn get_proof_storage_keys_are_capped() {
+        let mut context = test_context().await;
+        context.rpc_config.max_proof_storage_keys = 2;
+        let request = |keys: usize| RpcRequest {
+            id: RpcRequestId::Number(1),
+            jsonrpc: "2.0".to_string(),
+            method: "eth_getProof".to_string(),
+            params: Some(vec![
+                serde_json::json!(Address::zero()),
+                serde_json::json!(vec![H256::zero(); keys]),
+                serde_json::json!("latest"),
+            ]),
+        };
+
+        let result = map_http_requests(&request(3), context.clone(), CLIENT).await;
+        assert!(matches!(result, Err(RpcError::Eth(RpcErr::BadParams(_)))));
+
+        let proof = map_http_requests(&request(2), context, CLIENT)
+            .await
+            .unwrap();
+        assert_eq!(proof["storageProof"].as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn filters_are_capped_globally_and_per_client() {
+        let mut context = test_context().await;
+        context.rpc_config.max_active_filters = 3;
+        context.rpc_config.max_active_filters_per_client = 2;
+        let request = RpcRequest {
+            id: RpcRequestId::Number(1),
+            jsonrpc: "2.0".to_string(),
+            method: "eth_newFilter".to_string(),
+            params: Some(vec![serde_json::json!({
+                "fromBlock": "0x0",
+                "toBlock": "latest",
+            })]),
+        };
+        let other_client = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+
+        for _ in 0..2 {
+            map_http_requests(&request, context.clone(), CLIENT)
+                .await
+                .unwrap();
+        }
+        let result = map_http_requests(&request, context.clone(), CLIENT).await;
+        assert!(matches!(result, Err(RpcError::LimitExceeded(_))));
+
+        // The per client cap is not reached by the other client, but the global cap is.
+        map_http_requests(&request, context.clone(), other_client)
+            .await
+            .unwrap();
+        let result = map_http_requests(&request, context.clone(), other_client).await;
+        assert!(matches!(result, Err(RpcError::LimitExceeded(_))));
+        assert_eq!(context.l1_context.active_filters.lock().unwrap().len(), 3);
+    }
 }
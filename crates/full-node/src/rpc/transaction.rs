@@ -28,8 +28,8 @@ impl SendRawTransactionRequest {
     pub async fn call(request: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
         let data = Self::get_transaction_data(&request.params)?;
         let tx_hash = context
-            .eth_client
-            .send_raw_transaction(&data.0)
+            .mojave_client
+            .send_forward_transaction(&data.0, context.forward_strategy)
             .await
             .map_err(|error| RpcErr::Internal(error.to_string()))?;
         serde_json::to_value(tx_hash).map_err(|error| RpcErr::Internal(error.to_string()))
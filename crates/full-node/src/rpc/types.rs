@@ -1,5 +1,29 @@
-use ethrex_common::types::Block;
+use ethrex_common::{H256, types::Block};
+use ethrex_rpc::RpcErr;
+use lru::LruCache;
 use mojave_chain_utils::unique_heap::UniqueHeapItem;
+use mojave_client::types::SignedBlock;
+use mojave_signature::{AnyVerifyingKey, SignatureError, ThresholdVerifier};
+use std::{
+    fmt,
+    num::NonZeroUsize,
+    ops::Range,
+    str::FromStr,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+use tokio::sync::Semaphore;
+
+/// Default number of broadcast block signatures verified at once.
+pub const DEFAULT_VERIFICATION_WORKERS: usize = 4;
+
+/// Default number of verified broadcast block signatures remembered by the [`VerificationPool`].
+pub const DEFAULT_VERIFICATION_CACHE_SIZE: usize = 1024;
+
+/// Default number of sequencers whose last broadcast epoch is remembered by [`BroadcastEpochs`].
+pub const DEFAULT_MAX_BROADCAST_SEQUENCERS: usize = 64;
 
 /// A wrapper around a Block that provides ordering based on block number.
 ///
@@ -53,11 +77,366 @@ impl UniqueHeapItem<u64> for OrderedBlock {
     }
 }
 
+/// How a broadcast block received ahead of the local chain competes with the back-fill of the
+/// blocks it is missing. Either way the blocks are added to the chain in ascending order, as a
+/// block needs its parent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BackfillPolicy {
+    /// Back-fills every missing block before queuing the broadcast block, however long the gap,
+    /// favoring completeness.
+    #[default]
+    StrictAscending,
+    /// Back-fills at most the given number of missing blocks, the oldest ones, before queuing
+    /// the broadcast block, favoring liveness: a long gap does not hold up the broadcasts of the
+    /// new blocks, and is closed by the next broadcasts or the idle back-fill.
+    PrioritizeNewest(u64),
+}
+
+impl BackfillPolicy {
+    /// Blocks to back-fill before queuing the broadcast block `block_number`, with the local
+    /// chain at `latest_block_number`.
+    pub fn backfill_range(&self, latest_block_number: u64, block_number: u64) -> Range<u64> {
+        let start = latest_block_number + 1;
+        match self {
+            Self::StrictAscending => start..block_number,
+            Self::PrioritizeNewest(max_backfill) => {
+                start..block_number.min(start.saturating_add(*max_backfill))
+            }
+        }
+    }
+}
+
+impl FromStr for BackfillPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "strict-ascending" => Ok(Self::StrictAscending),
+            other => match other
+                .strip_prefix("prioritize-newest:")
+                .map(str::parse::<u64>)
+            {
+                Some(Ok(max_backfill)) => Ok(Self::PrioritizeNewest(max_backfill)),
+                _ => Err(format!(
+                    "unknown back-fill policy {other}, expected strict-ascending or prioritize-newest:<max back-filled blocks>"
+                )),
+            },
+        }
+    }
+}
+
+impl fmt::Display for BackfillPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StrictAscending => write!(f, "strict-ascending"),
+            Self::PrioritizeNewest(max_backfill) => write!(f, "prioritize-newest:{max_backfill}"),
+        }
+    }
+}
+
+/// The last broadcast epoch accepted from each sequencer, keyed by the sequencer address.
+///
+/// A broadcast whose epoch is not strictly greater than the last accepted one is a replay of
+/// an older signed block and is rejected.
+///
+/// The epochs of at most `capacity` sequencers are remembered, the least recently seen one
+/// being forgotten first. With the sequencer keys configured, only those keys may sign a block
+/// and the capacity is their number. Without them any key is accepted, so a replay is only
+/// caught for the last sequencers seen.
+#[derive(Clone, Debug)]
+pub struct BroadcastEpochs(Arc<Mutex<LruCache<String, u64>>>);
+
+impl Default for BroadcastEpochs {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_MAX_BROADCAST_SEQUENCERS)
+    }
+}
+
+impl BroadcastEpochs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remembers the epochs of at most `capacity` sequencers, at least one.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self(Arc::new(Mutex::new(LruCache::new(capacity))))
+    }
+
+    /// Accepts `epoch` as the latest one from `sequencer` if it is newer than the last accepted
+    /// epoch, fails otherwise. The previous epoch is restored when the returned advance is
+    /// dropped without being committed, for a broadcast failing after this check to be retried.
+    pub fn advance(&self, sequencer: &str, epoch: u64) -> Result<EpochAdvance<'_>, RpcErr> {
+        let mut epochs = self.lock();
+        let previous = epochs.get(sequencer).copied();
+        if let Some(last_epoch) = previous.filter(|last_epoch| epoch <= *last_epoch) {
+            return Err(RpcErr::BadParams(format!(
+                "Stale broadcast epoch {epoch} from {sequencer}, last accepted epoch is {last_epoch}"
+            )));
+        }
+        epochs.put(sequencer.to_owned(), epoch);
+        Ok(EpochAdvance {
+            epochs: self,
+            sequencer: sequencer.to_owned(),
+            epoch,
+            previous,
+            committed: false,
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LruCache<String, u64>> {
+        self.0.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+/// An epoch accepted by [`BroadcastEpochs::advance`], rolled back when dropped unless it was
+/// committed.
+#[derive(Debug)]
+pub struct EpochAdvance<'a> {
+    epochs: &'a BroadcastEpochs,
+    sequencer: String,
+    epoch: u64,
+    previous: Option<u64>,
+    committed: bool,
+}
+
+impl EpochAdvance<'_> {
+    /// Keeps the epoch, once the broadcast block is queued.
+    pub fn commit(mut self) {
+        self.committed = true;
+    }
+}
+
+impl Drop for EpochAdvance<'_> {
+    fn drop(&mut self) {
+        if self.committed {
+            return;
+        }
+        let mut epochs = self.epochs.lock();
+        // A newer epoch accepted in the meantime is kept.
+        if epochs.peek(&self.sequencer) == Some(&self.epoch) {
+            match self.previous {
+                Some(previous) => {
+                    epochs.put(self.sequencer.clone(), previous);
+                }
+                None => {
+                    epochs.pop(&self.sequencer);
+                }
+            }
+        }
+    }
+}
+
+/// Verifies the signatures of broadcast blocks on the blocking thread pool, so that a burst of
+/// broadcasts does not stall the async workers serving the other requests. At most `size`
+/// verifications run at once, the other broadcasts wait for their turn.
+///
+/// With a [`ThresholdVerifier`], a block is only accepted once signed by enough of its keys,
+/// counting the co-signatures of the block along with its signature.
+///
+/// With a cache, the successful verifications are remembered by block hash, so that a block
+/// broadcast again with the same epoch and signatures is not verified twice: a retry of a
+/// broadcast that failed after its verification, e.g. on a back-fill error, which is accepted
+/// as its epoch was rolled back, or a replay, which is then rejected by its epoch alone.
+#[derive(Clone, Debug)]
+pub struct VerificationPool {
+    permits: Arc<Semaphore>,
+    threshold: Option<Arc<ThresholdVerifier>>,
+    cache: Option<Arc<Mutex<LruCache<H256, VerifiedSignatures>>>>,
+    verifications: Arc<AtomicU64>,
+}
+
+/// What a cached verification was run on, a broadcast of the same block only skips the
+/// verification if it carries the same epoch and signatures.
+#[derive(Debug)]
+struct VerifiedSignatures {
+    epoch: u64,
+    signatures: Vec<Vec<u8>>,
+    verifying_key: AnyVerifyingKey,
+}
+
+impl VerifiedSignatures {
+    fn signatures(signed_block: &SignedBlock) -> Vec<Vec<u8>> {
+        std::iter::once(&signed_block.signature)
+            .chain(&signed_block.co_signatures)
+            .map(|signature| signature.bytes.clone())
+            .collect()
+    }
+
+    fn matches(&self, signed_block: &SignedBlock) -> bool {
+        self.epoch == signed_block.epoch && self.signatures == Self::signatures(signed_block)
+    }
+}
+
+impl Default for VerificationPool {
+    fn default() -> Self {
+        Self::new(DEFAULT_VERIFICATION_WORKERS)
+    }
+}
+
+impl VerificationPool {
+    pub fn new(size: usize) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(size)),
+            threshold: None,
+            cache: None,
+            verifications: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Remembers the last `size` verified blocks, a size of 0 disables the cache.
+    pub fn with_cache(mut self, size: usize) -> Self {
+        self.cache = NonZeroUsize::new(size).map(|size| Arc::new(Mutex::new(LruCache::new(size))));
+        self
+    }
+
+    /// Number of verifications run, the ones skipped thanks to the cache excluded.
+    pub fn verifications(&self) -> u64 {
+        self.verifications.load(Ordering::Relaxed)
+    }
+
+    pub fn with_threshold(mut self, verifier: ThresholdVerifier) -> Self {
+        self.threshold = Some(Arc::new(verifier));
+        self
+    }
+
+    /// Verifies the signed block and returns it along with the key of the sequencer that signed
+    /// it, see [`SignedBlock::verify`].
+    pub async fn verify(
+        &self,
+        signed_block: SignedBlock,
+    ) -> Result<(SignedBlock, AnyVerifyingKey), RpcErr> {
+        let block_hash = signed_block.block.hash();
+        if let Some(verifying_key) = self.cached(&block_hash, &signed_block) {
+            return Ok((signed_block, verifying_key));
+        }
+
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|error| RpcErr::Internal(error.to_string()))?;
+        let threshold = self.threshold.clone();
+        self.verifications.fetch_add(1, Ordering::Relaxed);
+        let (signed_block, verifying_key) = tokio::task::spawn_blocking(move || {
+            let verifying_key = signed_block
+                .verify()
+                .and_then(|verifying_key| match &threshold {
+                    Some(threshold) if !signed_block.verify_threshold(threshold)? => {
+                        Err(SignatureError::ThresholdNotReached {
+                            threshold: threshold.threshold(),
+                            keys: threshold.keys().len(),
+                        })
+                    }
+                    _ => Ok(verifying_key),
+                });
+            (signed_block, verifying_key)
+        })
+        .await
+        .map_err(|error| RpcErr::Internal(error.to_string()))?;
+        self.accept(block_hash, signed_block, verifying_key)
+    }
+
+    /// Verifies a run of signed blocks at once, see [`SignedBlock::verify_batch`], and returns
+    /// the outcome of each one in their order, as [`VerificationPool::verify`] would.
+    pub async fn verify_batch(
+        &self,
+        signed_blocks: Vec<SignedBlock>,
+    ) -> Result<Vec<Result<(SignedBlock, AnyVerifyingKey), RpcErr>>, RpcErr> {
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|error| RpcErr::Internal(error.to_string()))?;
+        let threshold = self.threshold.clone();
+        self.verifications
+            .fetch_add(signed_blocks.len() as u64, Ordering::Relaxed);
+        let (signed_blocks, verifying_keys) = tokio::task::spawn_blocking(move || {
+            let verifying_keys = SignedBlock::verify_batch(&signed_blocks).map(|verifying_keys| {
+                signed_blocks
+                    .iter()
+                    .zip(verifying_keys)
+                    .map(|(signed_block, verifying_key)| {
+                        let verifying_key = verifying_key.ok_or_else(|| {
+                            SignatureError::InvalidFormat("Invalid block signature".to_owned())
+                        })?;
+                        match &threshold {
+                            Some(threshold) if !signed_block.verify_threshold(threshold)? => {
+                                Err(SignatureError::ThresholdNotReached {
+                                    threshold: threshold.threshold(),
+                                    keys: threshold.keys().len(),
+                                })
+                            }
+                            _ => Ok(verifying_key),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            });
+            (signed_blocks, verifying_keys)
+        })
+        .await
+        .map_err(|error| RpcErr::Internal(error.to_string()))?;
+        let verifying_keys =
+            verifying_keys.map_err(|error| RpcErr::BadParams(error.to_string()))?;
+        Ok(signed_blocks
+            .into_iter()
+            .zip(verifying_keys)
+            .map(|(signed_block, verifying_key)| {
+                self.accept(signed_block.block.hash(), signed_block, verifying_key)
+            })
+            .collect())
+    }
+
+    /// Checks the outcome of the verification of a block against the sequencer keys and caches
+    /// it if successful.
+    fn accept(
+        &self,
+        block_hash: H256,
+        signed_block: SignedBlock,
+        verifying_key: Result<AnyVerifyingKey, SignatureError>,
+    ) -> Result<(SignedBlock, AnyVerifyingKey), RpcErr> {
+        // Whatever the reason, a signature that does not verify is the caller's fault.
+        let verifying_key = verifying_key.map_err(|error| RpcErr::BadParams(error.to_string()))?;
+        // The broadcast epochs are tracked per signer, which must then be one of the keys for
+        // their number to stay bounded.
+        if self
+            .threshold
+            .as_ref()
+            .is_some_and(|threshold| !threshold.contains(&verifying_key))
+        {
+            return Err(RpcErr::BadParams(format!(
+                "Block signed by {}, which is not one of the sequencer keys",
+                verifying_key.to_address()
+            )));
+        }
+        if let Some(Ok(mut cache)) = self.cache.as_ref().map(|cache| cache.lock()) {
+            cache.put(
+                block_hash,
+                VerifiedSignatures {
+                    epoch: signed_block.epoch,
+                    signatures: VerifiedSignatures::signatures(&signed_block),
+                    verifying_key: verifying_key.clone(),
+                },
+            );
+        }
+        Ok((signed_block, verifying_key))
+    }
+
+    fn cached(&self, block_hash: &H256, signed_block: &SignedBlock) -> Option<AnyVerifyingKey> {
+        let mut cache = self.cache.as_ref()?.lock().ok()?;
+        cache
+            .get(block_hash)
+            .filter(|verified| verified.matches(signed_block))
+            .map(|verified| verified.verifying_key.clone())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use ethrex_common::types::{BlockBody, BlockHeader};
     use mojave_chain_utils::unique_heap::AsyncUniqueHeap;
+    use mojave_signature::{AnySigningKey, SignatureScheme};
 
     fn create_test_block(number: u64) -> OrderedBlock {
         let header = BlockHeader {
@@ -241,4 +620,149 @@ mod tests {
 
         assert!(heap.is_empty().await);
     }
+
+    async fn queued_order(policy: BackfillPolicy, latest: u64, broadcasts: &[u64]) -> Vec<u64> {
+        let heap = AsyncUniqueHeap::new();
+        for &block_number in broadcasts {
+            for backfilled in policy.backfill_range(latest, block_number) {
+                heap.push(create_test_block(backfilled)).await;
+            }
+            heap.push(create_test_block(block_number)).await;
+        }
+        let mut order = Vec::new();
+        while let Some(block) = heap.pop().await {
+            order.push(block.0.header.number);
+        }
+        order
+    }
+
+    #[tokio::test]
+    async fn test_strict_ascending_backfills_the_whole_gap() {
+        let policy = BackfillPolicy::StrictAscending;
+        assert_eq!(policy.backfill_range(3, 10), 4..10);
+        assert!(policy.backfill_range(3, 4).is_empty());
+        assert!(policy.backfill_range(10, 4).is_empty());
+
+        assert_eq!(
+            queued_order(policy, 0, &[5, 3, 6]).await,
+            vec![1, 2, 3, 4, 5, 6]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prioritize_newest_bounds_the_backfill() {
+        let policy = BackfillPolicy::PrioritizeNewest(2);
+        assert_eq!(policy.backfill_range(3, 10), 4..6);
+        assert_eq!(policy.backfill_range(3, 5), 4..5);
+        assert!(
+            BackfillPolicy::PrioritizeNewest(0)
+                .backfill_range(3, 10)
+                .is_empty()
+        );
+
+        // The oldest missing blocks are back-filled first, and the new blocks are queued
+        // without waiting for the rest of the gap.
+        assert_eq!(queued_order(policy, 0, &[10, 11]).await, vec![1, 2, 10, 11]);
+    }
+
+    #[test]
+    fn test_backfill_policy_is_parsed() {
+        for policy in [
+            BackfillPolicy::StrictAscending,
+            BackfillPolicy::PrioritizeNewest(16),
+        ] {
+            assert_eq!(BackfillPolicy::from_str(&policy.to_string()), Ok(policy));
+        }
+        assert!(BackfillPolicy::from_str("prioritize-newest").is_err());
+        assert!(BackfillPolicy::from_str("newest").is_err());
+    }
+
+    #[test]
+    fn test_broadcast_epochs_reject_replays() {
+        let epochs = BroadcastEpochs::new();
+
+        epochs.advance("sequencer", 10).unwrap().commit();
+        assert!(epochs.advance("sequencer", 10).is_err());
+        assert!(epochs.advance("sequencer", 9).is_err());
+        assert!(epochs.advance("sequencer", 11).is_ok());
+    }
+
+    #[test]
+    fn test_broadcast_epochs_are_tracked_per_sequencer() {
+        let epochs = BroadcastEpochs::new();
+
+        epochs.advance("sequencer-a", 10).unwrap().commit();
+        epochs.advance("sequencer-b", 5).unwrap().commit();
+        assert!(epochs.advance("sequencer-b", 5).is_err());
+    }
+
+    #[test]
+    fn test_uncommitted_broadcast_epoch_is_rolled_back() {
+        let epochs = BroadcastEpochs::new();
+        epochs.advance("sequencer", 10).unwrap().commit();
+
+        let advance = epochs.advance("sequencer", 11).unwrap();
+        assert!(epochs.advance("sequencer", 11).is_err());
+        drop(advance);
+        epochs.advance("sequencer", 11).unwrap().commit();
+        assert!(epochs.advance("sequencer", 11).is_err());
+
+        // A first epoch rolled back leaves no epoch behind.
+        drop(epochs.advance("other", 3).unwrap());
+        epochs.advance("other", 1).unwrap().commit();
+    }
+
+    #[test]
+    fn test_broadcast_epochs_are_bounded() {
+        let epochs = BroadcastEpochs::with_capacity(2);
+        for sequencer in ["sequencer-a", "sequencer-b", "sequencer-c"] {
+            epochs.advance(sequencer, 10).unwrap().commit();
+        }
+
+        assert_eq!(epochs.lock().len(), 2);
+        assert!(epochs.advance("sequencer-c", 10).is_err());
+        // The least recently seen sequencer is forgotten.
+        assert!(epochs.advance("sequencer-a", 10).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_verified_block_is_not_verified_again() {
+        let signing_key = AnySigningKey::from_str_with_scheme(
+            SignatureScheme::Secp256k1,
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .unwrap();
+        let signed_block = SignedBlock::new(create_test_block(1).0, 1, &signing_key).unwrap();
+        let pool = VerificationPool::default().with_cache(DEFAULT_VERIFICATION_CACHE_SIZE);
+
+        for _ in 0..2 {
+            let (_, verifying_key) = pool.verify(signed_block.clone()).await.unwrap();
+            assert_eq!(
+                verifying_key.to_address(),
+                signing_key.verifying_key().to_address()
+            );
+        }
+        assert_eq!(pool.verifications(), 1);
+
+        // The same block signed by another key is verified again, instead of being attributed
+        // to the key of the cached verification.
+        let other_key = AnySigningKey::from_str_with_scheme(
+            SignatureScheme::Secp256k1,
+            "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d",
+        )
+        .unwrap();
+        let resigned = SignedBlock::new(signed_block.block.clone(), 1, &other_key).unwrap();
+        let (_, verifying_key) = pool.verify(resigned).await.unwrap();
+        assert_eq!(
+            verifying_key.to_address(),
+            other_key.verifying_key().to_address()
+        );
+        assert_eq!(pool.verifications(), 2);
+
+        let uncached = VerificationPool::default();
+        for _ in 0..2 {
+            uncached.verify(signed_block.clone()).await.unwrap();
+        }
+        assert_eq!(uncached.verifications(), 2);
+    }
 }
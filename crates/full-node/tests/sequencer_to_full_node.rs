@@ -0,0 +1,185 @@
+//! End-to-end test of the block path: the sequencer produces and signs a block, broadcasts it
+//! to a full node served on OS-assigned ports, which verifies it and applies it to its store.
+
+use ethrex_blockchain::Blockchain;
+use ethrex_common::{
+    Address, Bytes, H256, H512, U256,
+    types::{ChainConfig, ELASTICITY_MULTIPLIER, Genesis},
+};
+use ethrex_p2p::{
+    peer_handler::PeerHandler,
+    sync_manager::SyncManager,
+    types::{Node, NodeRecord},
+};
+use ethrex_rpc::{EthClient, NodeData};
+use ethrex_storage::{EngineType, Store};
+use ethrex_storage_rollup::{EngineTypeRollup, StoreRollup};
+use mojave_chain_utils::rpc::RpcConfig;
+use mojave_client::{BroadcastMode, ForwardStrategy, MojaveClient};
+use mojave_full_node::{
+    monitor::SequencerHealth,
+    rpc::{ApiConfig, ApiServices, start_api, types::BackfillPolicy},
+};
+use mojave_sequencer::block_producer::{BlockProducer, BlockProducerContext};
+use mojave_signature::{AnySigningKey, SignatureScheme};
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+const PRIVATE_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+fn genesis() -> Genesis {
+    Genesis {
+        config: ChainConfig {
+            chain_id: 1,
+            london_block: Some(0),
+            ..Default::default()
+        },
+        alloc: BTreeMap::new(),
+        coinbase: Address::zero(),
+        difficulty: U256::zero(),
+        extra_data: Bytes::new(),
+        gas_limit: 30_000_000,
+        nonce: 0,
+        mix_hash: H256::zero(),
+        timestamp: 0,
+        base_fee_per_gas: None,
+        blob_gas_used: None,
+        excess_blob_gas: None,
+        requests_hash: None,
+    }
+}
+
+async fn free_addr() -> SocketAddr {
+    let temp = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = temp.local_addr().unwrap();
+    drop(temp);
+    addr
+}
+
+async fn init_store() -> (Store, Arc<Blockchain>, StoreRollup) {
+    let store = Store::new("", EngineType::InMemory).unwrap();
+    store.add_initial_state(genesis()).await.unwrap();
+    let blockchain = Arc::new(Blockchain::default_with_store(store.clone()));
+    let rollup_store = StoreRollup::new("", EngineTypeRollup::InMemory).unwrap();
+    rollup_store.init().await.unwrap();
+    (store, blockchain, rollup_store)
+}
+
+/// Starts a full node accepting the broadcasts of any sequencer and returns the address of its
+/// HTTP RPC server once it answers.
+async fn start_full_node(shutdown_token: CancellationToken) -> SocketAddr {
+    let (store, blockchain, rollup_store) = init_store().await;
+    let http_addr = free_addr().await;
+    let authrpc_addr = free_addr().await;
+    // Never reached, the full node only polls the sequencer after an idle timeout.
+    let sequencer_url = "http://127.0.0.1:1".to_owned();
+    let local_p2p_node = Node::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0, 0, H512::zero());
+
+    let config = ApiConfig {
+        http_addr,
+        authrpc_addr,
+        metrics_addr: None,
+        forward_strategy: ForwardStrategy::default(),
+        block_queue_max_size: 0,
+        idle_timeout: None,
+        verification_workers: 1,
+        verification_cache_size: 0,
+        sequencer_threshold: None,
+        max_clock_skew: Duration::from_secs(15),
+        max_broadcast_batch: 64,
+        backfill_policy: BackfillPolicy::default(),
+        max_reorg_depth: 0,
+        rpc_config: RpcConfig::default(),
+    };
+    let services = ApiServices {
+        storage: store,
+        blockchain,
+        node_data: NodeData {
+            jwt_secret: Bytes::new(),
+            local_p2p_node,
+            local_node_record: NodeRecord {
+                signature: H512::zero(),
+                seq: 0,
+                pairs: vec![],
+            },
+            client_version: "test".to_owned(),
+        },
+        syncer: SyncManager::dummy(),
+        peer_handler: PeerHandler::dummy(),
+        peer_table: ethrex_p2p::network::peer_table(H512::zero()),
+        rollup_store,
+        eth_client: EthClient::new(&sequencer_url).unwrap(),
+        mojave_client: MojaveClient::without_signing_key(&[sequencer_url]).unwrap(),
+        sequencer_health: SequencerHealth::default(),
+    };
+    tokio::spawn(start_api(config, services, shutdown_token));
+
+    let client = EthClient::new(&format!("http://{http_addr}")).unwrap();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while client.get_block_number().await.is_err() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("the full node RPC server did not start");
+    http_addr
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn broadcast_block_is_applied_by_the_full_node() {
+    let shutdown_token = CancellationToken::new();
+    let full_node_addr = start_full_node(shutdown_token.clone()).await;
+    let full_node = EthClient::new(&format!("http://{full_node_addr}")).unwrap();
+    assert_eq!(full_node.get_block_number().await.unwrap().as_u64(), 0);
+
+    let (store, blockchain, rollup_store) = init_store().await;
+    let block_producer = BlockProducer::start(
+        BlockProducerContext::new(
+            store.clone(),
+            blockchain,
+            rollup_store,
+            Address::zero(),
+            ELASTICITY_MULTIPLIER,
+        ),
+        100,
+    );
+    let signing_key =
+        AnySigningKey::from_str_with_scheme(SignatureScheme::Secp256k1, PRIVATE_KEY).unwrap();
+    let mojave_client =
+        MojaveClient::new(&[format!("http://{full_node_addr}")], signing_key).unwrap();
+
+    for _ in 0..2 {
+        let block = block_producer
+            .build_block()
+            .await
+            .unwrap()
+            .expect("empty blocks are produced");
+        let report = mojave_client
+            .send_broadcast_block(&block, BroadcastMode::All)
+            .await
+            .unwrap();
+        assert_eq!(report.accepted(), 1);
+    }
+
+    let sequencer_block_number = store.get_latest_block_number().await.unwrap();
+    assert_eq!(sequencer_block_number, 2);
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while full_node.get_block_number().await.unwrap().as_u64() < sequencer_block_number {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("the full node did not apply the broadcast blocks");
+    assert_eq!(
+        full_node.get_block_number().await.unwrap().as_u64(),
+        sequencer_block_number
+    );
+
+    shutdown_token.cancel();
+}
@@ -35,4 +35,14 @@ pub enum ProofCoordinatorError {
     ComandError(std::io::Error),
     #[error("Missing blob for batch {0}")]
     MissingBlob(u64),
+    #[error("No proof was recorded yet to estimate the proof time from")]
+    NoProofDurationRecorded,
+    #[error("ProofCoordinator failed to access the witness cache: {0}")]
+    WitnessCache(std::io::Error),
+    #[error(
+        "ProofCoordinator generated a prover input of {size} bytes, above the maximum of {max} bytes"
+    )]
+    WitnessTooLarge { size: u64, max: u64 },
+    #[error("ProofCoordinator failed to (de)serialize a witness: {0}")]
+    WitnessSerialization(#[from] serde_json::Error),
 }
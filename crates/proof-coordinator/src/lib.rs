@@ -1,18 +1,27 @@
-use std::sync::Arc;
+use std::{
+    io,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use ethrex_blockchain::Blockchain;
 use ethrex_common::types::{BlobsBundle, Block};
 use ethrex_l2_common::prover::BatchProof;
 use ethrex_storage::Store;
 use ethrex_storage_rollup::StoreRollup;
+use serde::Serialize;
 use tokio::sync::mpsc::Receiver;
 use zkvm_interface::io::ProgramInput;
 
-use crate::errors::ProofCoordinatorError;
+use crate::{errors::ProofCoordinatorError, proof_time::ProofDurations};
 
 use mojave_prover::{ProverClient, ProverData};
 
 mod errors;
+mod proof_time;
+mod witness_cache;
+
+pub use witness_cache::WitnessCache;
 
 pub struct ProofCoordinator {
     /// Come from the block builder
@@ -38,18 +47,23 @@ impl ProofCoordinator {
             None => return Ok(()),
         };
 
-        let input = match context.create_prover_input(batch_number).await {
-            Ok(input) => input,
-            Err(e) => return Err(e),
-        };
+        let input = context.prover_input(batch_number).await?;
 
+        let blocks = input.input.blocks.len() as u64;
+        let transactions = count_transactions(&input.input.blocks);
+        let started_at = Instant::now();
         let (batch_number, batch_proof) = self.request_proof_from_prover(input).await?;
+        context.record_proof_duration(blocks, transactions, started_at.elapsed());
 
         context.store_proof(batch_proof, batch_number).await?;
+        if let Some(witness_cache) = &context.witness_cache {
+            witness_cache.remove(batch_number).await?;
+        }
 
         Ok(())
     }
 
+    #[tracing::instrument(skip_all, fields(batch_number = prover_data.batch_number))]
     async fn request_proof_from_prover(
         &self,
         prover_data: ProverData,
@@ -64,14 +78,82 @@ impl ProofCoordinator {
     }
 }
 
+#[derive(Clone)]
 pub struct ProofCoordinatorContext {
     rollup_store: StoreRollup,
     store: Store,
     blockchain: Arc<Blockchain>,
     elasticity_multiplier: u64,
+    proof_durations: Arc<Mutex<ProofDurations>>,
+    /// Where the generated prover inputs are kept until proven, if enabled.
+    witness_cache: Option<WitnessCache>,
+    /// Largest serialized prover input handed to the prover, unbounded if `None`.
+    max_witness_bytes: Option<u64>,
 }
 
 impl ProofCoordinatorContext {
+    pub fn new(
+        rollup_store: StoreRollup,
+        store: Store,
+        blockchain: Arc<Blockchain>,
+        elasticity_multiplier: u64,
+    ) -> Self {
+        Self {
+            rollup_store,
+            store,
+            blockchain,
+            elasticity_multiplier,
+            proof_durations: Arc::default(),
+            witness_cache: None,
+            max_witness_bytes: None,
+        }
+    }
+
+    /// Keeps the generated prover inputs in `witness_cache` until their batch is proven.
+    pub fn with_witness_cache(mut self, witness_cache: Option<WitnessCache>) -> Self {
+        self.witness_cache = witness_cache;
+        self
+    }
+
+    /// Rejects the batches whose serialized prover input exceeds `max_witness_bytes`, before
+    /// they are sent to a prover unable to handle them.
+    pub fn with_max_witness_bytes(mut self, max_witness_bytes: Option<u64>) -> Self {
+        self.max_witness_bytes = max_witness_bytes;
+        self
+    }
+
+    fn record_proof_duration(&self, blocks: u64, transactions: u64, duration: Duration) {
+        self.proof_durations
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .record(blocks, transactions, duration);
+    }
+
+    /// Estimates, in milliseconds, how long proving the batch will take from its number of
+    /// blocks and transactions and the duration of the recent proofs.
+    pub async fn estimate_batch_proof_time(
+        &self,
+        batch_number: u64,
+    ) -> Result<u64, ProofCoordinatorError> {
+        let Some(block_numbers) = self
+            .rollup_store
+            .get_block_numbers_by_batch(batch_number)
+            .await?
+        else {
+            return Err(ProofCoordinatorError::ItemNotFoundInStore(format!(
+                "Batch number {batch_number} not found in store"
+            )));
+        };
+        let blocks = self.fetch_blocks(block_numbers).await?;
+
+        self.proof_durations
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .estimate(blocks.len() as u64, count_transactions(&blocks))
+            .map(|estimate| estimate.as_millis() as u64)
+            .ok_or(ProofCoordinatorError::NoProofDurationRecorded)
+    }
+
     async fn store_proof(
         &self,
         batch_proof: BatchProof,
@@ -99,6 +181,27 @@ impl ProofCoordinatorContext {
         Ok(())
     }
 
+    /// Returns the prover input of the batch, reusing the one in the witness cache if any. Newly
+    /// generated inputs are written to the cache, so that they survive a restart.
+    pub async fn prover_input(
+        &self,
+        batch_number: u64,
+    ) -> Result<ProverData, ProofCoordinatorError> {
+        let Some(witness_cache) = &self.witness_cache else {
+            return self.create_prover_input(batch_number).await;
+        };
+        if let Some(input) = witness_cache.load(batch_number).await? {
+            tracing::debug!("Loaded cached prover input for batch {batch_number}");
+            // It may have been cached before the maximum size was lowered.
+            check_witness_size(&input, self.max_witness_bytes)?;
+            return Ok(input);
+        }
+
+        let input = self.create_prover_input(batch_number).await?;
+        witness_cache.store(&input).await?;
+        Ok(input)
+    }
+
     pub async fn create_prover_input(
         &self,
         batch_number: u64,
@@ -139,9 +242,7 @@ impl ProofCoordinatorContext {
             }
         };
 
-        tracing::debug!("Created prover input for batch {batch_number}");
-
-        Ok(ProverData {
+        let prover_data = ProverData {
             batch_number,
             input: ProgramInput {
                 db: witness,
@@ -150,7 +251,12 @@ impl ProofCoordinatorContext {
                 blob_proof,
                 elasticity_multiplier: self.elasticity_multiplier,
             },
-        })
+        };
+        check_witness_size(&prover_data, self.max_witness_bytes)?;
+
+        tracing::debug!("Created prover input for batch {batch_number}");
+
+        Ok(prover_data)
     }
 
     async fn fetch_blocks(
@@ -173,3 +279,123 @@ impl ProofCoordinatorContext {
         Ok(blocks)
     }
 }
+
+fn count_transactions(blocks: &[Block]) -> u64 {
+    blocks
+        .iter()
+        .map(|block| block.body.transactions.len() as u64)
+        .sum()
+}
+
+/// Fails with the serialized size of `value`, as sent to the prover, if it exceeds
+/// `max_witness_bytes`.
+fn check_witness_size<T: Serialize>(
+    value: &T,
+    max_witness_bytes: Option<u64>,
+) -> Result<(), ProofCoordinatorError> {
+    let Some(max) = max_witness_bytes else {
+        return Ok(());
+    };
+    // Counts the bytes instead of buffering them, the prover input being possibly huge.
+    let mut counter = ByteCounter(0);
+    serde_json::to_writer(&mut counter, value)?;
+    let size = counter.0;
+    if size > max {
+        return Err(ProofCoordinatorError::WitnessTooLarge { size, max });
+    }
+    Ok(())
+}
+
+struct ByteCounter(u64);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethrex_storage::EngineType;
+    use ethrex_storage_rollup::EngineTypeRollup;
+
+    fn context() -> ProofCoordinatorContext {
+        let store = Store::new("", EngineType::InMemory).unwrap();
+        let blockchain = Arc::new(Blockchain::default_with_store(store.clone()));
+        let rollup_store = StoreRollup::new("", EngineTypeRollup::InMemory).unwrap();
+        ProofCoordinatorContext::new(rollup_store, store, blockchain, 2)
+    }
+
+    fn prover_data() -> ProverData {
+        ProverData {
+            batch_number: 1,
+            input: ProgramInput {
+                elasticity_multiplier: 2,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn witness_above_the_maximum_size_is_rejected() {
+        let prover_data = prover_data();
+        let size = serde_json::to_vec(&prover_data).unwrap().len() as u64;
+
+        assert!(check_witness_size(&prover_data, None).is_ok());
+        assert!(check_witness_size(&prover_data, Some(size)).is_ok());
+        assert!(matches!(
+            check_witness_size(&prover_data, Some(16)),
+            Err(ProofCoordinatorError::WitnessTooLarge { size: actual, max: 16 }) if actual == size
+        ));
+    }
+
+    async fn witness_cache(name: &str) -> WitnessCache {
+        let dir = std::env::temp_dir().join(format!(
+            "mojave-proof-coordinator-{name}-{}",
+            std::process::id()
+        ));
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        let witness_cache = WitnessCache::new(dir, u64::MAX).await.unwrap();
+        witness_cache.store(&prover_data()).await.unwrap();
+        witness_cache
+    }
+
+    #[tokio::test]
+    async fn prover_input_is_read_from_the_witness_cache() {
+        let witness_cache = witness_cache("cache").await;
+
+        // The batch is unknown to the store, so only the cache can provide its input.
+        assert!(matches!(
+            context().prover_input(1).await,
+            Err(ProofCoordinatorError::ItemNotFoundInStore(_))
+        ));
+        let input = context()
+            .with_witness_cache(Some(witness_cache))
+            .prover_input(1)
+            .await
+            .unwrap();
+        assert_eq!(input.batch_number, 1);
+        assert_eq!(input.input.elasticity_multiplier, 2);
+    }
+
+    #[tokio::test]
+    async fn cached_prover_input_above_the_maximum_size_is_rejected() {
+        let context = context().with_witness_cache(Some(witness_cache("oversized").await));
+
+        assert!(matches!(
+            context
+                .clone()
+                .with_max_witness_bytes(Some(16))
+                .prover_input(1)
+                .await,
+            Err(ProofCoordinatorError::WitnessTooLarge { max: 16, .. })
+        ));
+        assert!(context.prover_input(1).await.is_ok());
+    }
+}
@@ -0,0 +1,98 @@
+use std::{collections::VecDeque, time::Duration};
+
+/// Number of recent proofs the estimates are based on.
+pub const PROOF_DURATION_SAMPLES: usize = 16;
+
+#[derive(Clone, Copy, Debug)]
+struct ProofDurationSample {
+    work: u64,
+    duration: Duration,
+}
+
+/// Moving average of the time taken to prove the recent batches, used to estimate how long
+/// proving a batch will take.
+///
+/// The work of a batch is counted as its number of blocks plus its number of transactions, so
+/// that an empty block still adds its fixed overhead.
+#[derive(Clone, Debug)]
+pub struct ProofDurations {
+    samples: VecDeque<ProofDurationSample>,
+    capacity: usize,
+}
+
+impl Default for ProofDurations {
+    fn default() -> Self {
+        Self::new(PROOF_DURATION_SAMPLES)
+    }
+}
+
+impl ProofDurations {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Records the time taken to prove a batch, forgetting the oldest sample once full.
+    pub fn record(&mut self, blocks: u64, transactions: u64, duration: Duration) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(ProofDurationSample {
+            work: (blocks + transactions).max(1),
+            duration,
+        });
+    }
+
+    /// Estimates the time taken to prove a batch, or returns `None` until a proof was recorded.
+    pub fn estimate(&self, blocks: u64, transactions: u64) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let nanos_per_work = self
+            .samples
+            .iter()
+            .map(|sample| sample.duration.as_nanos() / u128::from(sample.work))
+            .sum::<u128>()
+            / self.samples.len() as u128;
+        let work = u128::from((blocks + transactions).max(1));
+        Some(Duration::from_nanos(
+            u64::try_from(nanos_per_work * work).unwrap_or(u64::MAX),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimate_is_none_without_samples() {
+        assert_eq!(ProofDurations::default().estimate(1, 10), None);
+    }
+
+    #[test]
+    fn estimate_scales_the_average_with_the_batch_size() {
+        let mut durations = ProofDurations::default();
+        // 1 block and 9 transactions in 10s, then 2 blocks and 18 transactions in 30s: 1s and
+        // 1.5s per unit of work.
+        durations.record(1, 9, Duration::from_secs(10));
+        durations.record(2, 18, Duration::from_secs(30));
+
+        assert_eq!(
+            durations.estimate(4, 36),
+            Some(Duration::from_millis(50_000))
+        );
+    }
+
+    #[test]
+    fn oldest_samples_are_forgotten() {
+        let mut durations = ProofDurations::new(2);
+        durations.record(1, 0, Duration::from_secs(100));
+        durations.record(1, 0, Duration::from_secs(2));
+        durations.record(1, 0, Duration::from_secs(4));
+
+        assert_eq!(durations.estimate(1, 0), Some(Duration::from_secs(3)));
+    }
+}
@@ -0,0 +1,171 @@
+use crate::errors::ProofCoordinatorError;
+use mojave_prover::ProverData;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// Generated prover inputs kept in the data dir, one file per batch, so that they do not have to
+/// be held in memory until proven nor regenerated after a restart.
+///
+/// Once the files exceed `max_size` bytes in total, the ones of the oldest batches are removed.
+#[derive(Clone, Debug)]
+pub struct WitnessCache {
+    dir: PathBuf,
+    max_size: u64,
+}
+
+impl WitnessCache {
+    pub async fn new(
+        dir: impl Into<PathBuf>,
+        max_size: u64,
+    ) -> Result<Self, ProofCoordinatorError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .await
+            .map_err(ProofCoordinatorError::WitnessCache)?;
+        Ok(Self { dir, max_size })
+    }
+
+    fn path(&self, batch_number: u64) -> PathBuf {
+        self.dir.join(format!("witness_{batch_number}.json"))
+    }
+
+    pub async fn store(&self, prover_data: &ProverData) -> Result<(), ProofCoordinatorError> {
+        let serialized = serde_json::to_vec(prover_data)?;
+        // Write to a temporary file first so that a crash never leaves a truncated witness.
+        let path = self.path(prover_data.batch_number);
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serialized)
+            .await
+            .map_err(ProofCoordinatorError::WitnessCache)?;
+        fs::rename(&tmp_path, &path)
+            .await
+            .map_err(ProofCoordinatorError::WitnessCache)?;
+        self.cleanup(prover_data.batch_number).await
+    }
+
+    pub async fn load(
+        &self,
+        batch_number: u64,
+    ) -> Result<Option<ProverData>, ProofCoordinatorError> {
+        match fs::read(self.path(batch_number)).await {
+            Ok(serialized) => Ok(Some(serde_json::from_slice(&serialized)?)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(ProofCoordinatorError::WitnessCache(error)),
+        }
+    }
+
+    pub async fn remove(&self, batch_number: u64) -> Result<(), ProofCoordinatorError> {
+        match fs::remove_file(self.path(batch_number)).await {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+                Err(ProofCoordinatorError::WitnessCache(error))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Removes the witnesses of the oldest batches until the cache fits in `max_size`, always
+    /// keeping the one of `keep_batch_number`.
+    async fn cleanup(&self, keep_batch_number: u64) -> Result<(), ProofCoordinatorError> {
+        let mut witnesses = Vec::new();
+        let mut entries = fs::read_dir(&self.dir)
+            .await
+            .map_err(ProofCoordinatorError::WitnessCache)?;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(ProofCoordinatorError::WitnessCache)?
+        {
+            let Some(batch_number) = batch_number_of(&entry.path()) else {
+                continue;
+            };
+            let size = entry
+                .metadata()
+                .await
+                .map_err(ProofCoordinatorError::WitnessCache)?
+                .len();
+            witnesses.push((batch_number, size));
+        }
+
+        witnesses.sort_unstable();
+        let mut total_size: u64 = witnesses.iter().map(|(_, size)| size).sum();
+        for (batch_number, size) in witnesses {
+            if total_size <= self.max_size {
+                break;
+            }
+            if batch_number == keep_batch_number {
+                continue;
+            }
+            tracing::debug!(batch_number, "Removing cached witness to free disk space");
+            self.remove(batch_number).await?;
+            total_size -= size;
+        }
+        Ok(())
+    }
+}
+
+fn batch_number_of(path: &Path) -> Option<u64> {
+    path.file_name()?
+        .to_str()?
+        .strip_prefix("witness_")?
+        .strip_suffix(".json")?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zkvm_interface::io::ProgramInput;
+
+    async fn cache(name: &str, max_size: u64) -> WitnessCache {
+        let dir = std::env::temp_dir().join(format!(
+            "mojave-witness-cache-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir).await;
+        WitnessCache::new(dir, max_size).await.unwrap()
+    }
+
+    fn prover_data(batch_number: u64) -> ProverData {
+        ProverData {
+            batch_number,
+            input: ProgramInput {
+                elasticity_multiplier: 2,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn witness_round_trips_through_disk() {
+        let cache = cache("round-trip", u64::MAX).await;
+        let witness = prover_data(7);
+
+        assert!(cache.load(7).await.unwrap().is_none());
+        cache.store(&witness).await.unwrap();
+        let loaded = cache.load(7).await.unwrap().unwrap();
+
+        assert_eq!(loaded.batch_number, witness.batch_number);
+        assert_eq!(
+            serde_json::to_value(&loaded.input).unwrap(),
+            serde_json::to_value(&witness.input).unwrap()
+        );
+
+        cache.remove(7).await.unwrap();
+        assert!(cache.load(7).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn oldest_witnesses_are_removed_when_over_size() {
+        let witness_size = serde_json::to_vec(&prover_data(1)).unwrap().len() as u64;
+        let cache = cache("cleanup", 2 * witness_size).await;
+
+        for batch_number in 1..=3 {
+            cache.store(&prover_data(batch_number)).await.unwrap();
+        }
+
+        assert!(cache.load(1).await.unwrap().is_none());
+        assert!(cache.load(2).await.unwrap().is_some());
+        assert!(cache.load(3).await.unwrap().is_some());
+    }
+}
@@ -1,10 +1,11 @@
 use crate::{
-    message::{self, MessageError, Request, Response},
+    message::{self, MessageError, ProofRequest, Request, Response},
     types::*,
 };
 use ethrex_l2_common::prover::BatchProof;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::{net::TcpStream, time::timeout};
+use tracing::{Span, field::Empty};
 
 pub struct ProverClient {
     server_address: String,
@@ -21,7 +22,8 @@ impl ProverClient {
 
     async fn request_inner(&mut self, request: Request) -> Result<Response, ProverClientError> {
         let mut stream = TcpStream::connect(&self.server_address).await?;
-        message::send(&mut stream, request).await?;
+        let input_size = message::send(&mut stream, request).await?;
+        Span::current().record("input_size", input_size);
         let response = message::receive::<Response>(&mut stream).await?;
         Ok(response)
     }
@@ -38,11 +40,38 @@ impl ProverClient {
         }
     }
 
+    /// Requests the proof of a batch, in a span recording the prover address, the batch number,
+    /// the size of the request, the time until the response and its outcome.
+    #[tracing::instrument(
+        name = "prover_request",
+        skip_all,
+        fields(
+            prover = %self.server_address,
+            batch_number = data.batch_number,
+            input_size = Empty,
+            duration_ms = Empty,
+            outcome = Empty,
+        )
+    )]
     pub async fn get_proof(&mut self, data: ProverData) -> Result<BatchProof, ProverClientError> {
-        match self.request(Request::Proof(data)).await? {
-            Response::Proof(proof) => Ok(proof),
-            Response::Error(error) => Err(ProverClientError::Internal(error)),
-        }
+        let started_at = Instant::now();
+        let deadline = SystemTime::now()
+            .checked_add(Duration::from_secs(self.request_timeout))
+            .and_then(|deadline| deadline.duration_since(UNIX_EPOCH).ok())
+            .map(|deadline| deadline.as_millis() as u64);
+        let result = match self
+            .request(Request::Proof(ProofRequest { data, deadline }))
+            .await
+        {
+            Ok(Response::Proof(proof)) => Ok(proof),
+            Ok(Response::Error(error)) => Err(ProverClientError::Internal(error)),
+            Err(error) => Err(error),
+        };
+
+        let span = Span::current();
+        span.record("duration_ms", started_at.elapsed().as_millis() as u64);
+        span.record("outcome", if result.is_ok() { "proof" } else { "error" });
+        result
     }
 }
 
@@ -59,3 +88,69 @@ pub enum ProverClientError {
     #[error("Connection timed out")]
     TimeOut,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+    use tokio::net::TcpListener;
+    use tracing_subscriber::fmt::format::FmtSpan;
+    use zkvm_interface::io::ProgramInput;
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn proof_request_span_records_the_exchange() {
+        let logs = CapturedLogs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_ansi(false)
+            .with_span_events(FmtSpan::CLOSE)
+            .with_writer({
+                let logs = logs.clone();
+                move || logs.clone()
+            })
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let _request: Request = message::receive(&mut stream).await.unwrap();
+            message::send(&mut stream, Response::Error("no proof".to_owned()))
+                .await
+                .unwrap();
+        });
+
+        let mut client = ProverClient::new(&address, 10);
+        let data = ProverData {
+            batch_number: 7,
+            input: ProgramInput::default(),
+        };
+        assert!(client.get_proof(data).await.is_err());
+        server.await.unwrap();
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("prover_request{"));
+        assert!(logs.contains(&format!("prover={address}")));
+        assert!(logs.contains("batch_number=7"));
+        assert!(logs.contains("input_size="));
+        assert!(logs.contains("duration_ms="));
+        assert!(logs.contains("outcome=\"error\""));
+    }
+}
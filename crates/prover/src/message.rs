@@ -9,7 +9,17 @@ use tokio::{
 #[derive(Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum Request {
-    Proof(ProverData),
+    Proof(ProofRequest),
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ProofRequest {
+    #[serde(flatten)]
+    pub data: ProverData,
+    /// UNIX time in milliseconds after which the client stops waiting for the proof, so the
+    /// server can give up on it too.
+    #[serde(default)]
+    pub deadline: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -22,6 +32,14 @@ pub enum Response {
 const MAX_MESSAGE_SIZE: u32 = 10 * 1024 * 1024; // 10MB
 
 pub async fn receive<T>(stream: &mut TcpStream) -> Result<T, MessageError>
+where
+    T: DeserializeOwned,
+{
+    receive_with_size(stream).await.map(|(data, _size)| data)
+}
+
+/// Same as [`receive`], along with the size in bytes of the serialized message.
+pub async fn receive_with_size<T>(stream: &mut TcpStream) -> Result<(T, usize), MessageError>
 where
     T: DeserializeOwned,
 {
@@ -31,10 +49,12 @@ where
     }
     let mut buffer = vec![0; length as usize];
     stream.read_exact(&mut buffer).await?;
-    serde_json::from_slice(&buffer).map_err(MessageError::Deserialize)
+    let data = serde_json::from_slice(&buffer).map_err(MessageError::Deserialize)?;
+    Ok((data, buffer.len()))
 }
 
-pub async fn send<T>(stream: &mut TcpStream, data: T) -> Result<(), MessageError>
+/// Returns the size in bytes of the serialized message.
+pub async fn send<T>(stream: &mut TcpStream, data: T) -> Result<usize, MessageError>
 where
     T: Serialize,
 {
@@ -43,7 +63,7 @@ where
     stream.write_u32(length).await?;
     stream.write_all(&serialized).await?;
     stream.flush().await?;
-    Ok(())
+    Ok(serialized.len())
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -1,6 +1,13 @@
-use crate::message::{self, MessageError, Request, Response};
+use crate::message::{self, MessageError, ProofRequest, Request, Response};
+use ethrex_l2_common::prover::BatchProof;
 use ethrex_prover_lib::{backends::Backend, prove, to_batch_proof};
-use tokio::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    task::JoinError,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{Span, field::Empty};
 
 #[allow(unused)]
 const QUEUE_SIZE: usize = 100;
@@ -58,29 +65,163 @@ async fn handle_connection(mut stream: TcpStream, aligned_mode: bool) {
         .unwrap_or_else(|error| Response::Error(error.to_string()));
 
     // If send() fails, we need to know.
-    message::send(&mut stream, &response)
-        .await
-        .unwrap_or_else(|error| tracing::error!("{error}"));
+    if let Err(error) = message::send(&mut stream, &response).await {
+        tracing::error!("{error}");
+    }
 }
 
+/// Handles a request in a span recording the batch number, the size of the request, the
+/// proving duration and its outcome.
+#[tracing::instrument(
+    name = "proof_job",
+    skip_all,
+    fields(
+        batch_number = Empty,
+        input_size = Empty,
+        duration_ms = Empty,
+        outcome = Empty,
+    )
+)]
 async fn handle_request(
     stream: &mut TcpStream,
     aligned_mode: bool,
 ) -> Result<Response, InternalError> {
-    let request = message::receive::<Request>(stream).await?;
+    let (request, input_size) = message::receive_with_size::<Request>(stream).await?;
+    let span = Span::current();
+    span.record("input_size", input_size);
     match request {
-        Request::Proof(prover_data) => {
-            let batch_proof = prove(Backend::Exec, prover_data.input, aligned_mode)
-                .and_then(|output| to_batch_proof(output, aligned_mode))?;
-            Ok(Response::Proof(batch_proof))
+        Request::Proof(ProofRequest { data, deadline }) => {
+            span.record("batch_number", data.batch_number);
+            let started_at = Instant::now();
+            let result = prove_before_deadline(deadline, move |cancelled| {
+                // The backend cannot be interrupted while running, so the cancellation is
+                // checked before and after each stage of the job.
+                if cancelled.is_cancelled() {
+                    return Err(ABANDONED.to_owned());
+                }
+                let output = prove(Backend::Exec, data.input, aligned_mode)
+                    .map_err(|error| error.to_string())?;
+                if cancelled.is_cancelled() {
+                    return Err(ABANDONED.to_owned());
+                }
+                to_batch_proof(output, aligned_mode).map_err(|error| error.to_string())
+            })
+            .await;
+            span.record("duration_ms", started_at.elapsed().as_millis() as u64);
+            span.record("outcome", if result.is_ok() { "proof" } else { "error" });
+            Ok(Response::Proof(result?))
         }
     }
 }
 
+const ABANDONED: &str = "Proof abandoned after the deadline of the request";
+
+/// Runs the proving job, giving up on it once the deadline of the client, in milliseconds since
+/// the UNIX epoch, has passed. A job whose deadline already passed is not started at all.
+///
+/// The job is handed a token cancelled when it is given up on, including when the request is
+/// dropped, and is expected to return as soon as it observes it to free its thread.
+async fn prove_before_deadline<F>(
+    deadline: Option<u64>,
+    job: F,
+) -> Result<BatchProof, InternalError>
+where
+    F: FnOnce(CancellationToken) -> Result<BatchProof, String> + Send + 'static,
+{
+    let remaining = match deadline {
+        Some(deadline) => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64;
+            if now >= deadline {
+                return Err(InternalError::DeadlineExceeded);
+            }
+            Some(Duration::from_millis(deadline - now))
+        }
+        None => None,
+    };
+
+    let cancelled = CancellationToken::new();
+    let job = tokio::task::spawn_blocking({
+        let cancelled = cancelled.clone();
+        move || job(cancelled)
+    });
+    let _cancel_on_drop = cancelled.drop_guard();
+    let result = match remaining {
+        Some(remaining) => tokio::time::timeout(remaining, job).await.map_err(|_| {
+            tracing::warn!("Abandoning a proof whose client deadline passed");
+            InternalError::DeadlineExceeded
+        })?,
+        None => job.await,
+    };
+    result?.map_err(|error| InternalError::Prover(error.into()))
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum InternalError {
     #[error("{0}")]
     Message(#[from] MessageError),
     #[error("{0}")]
     Prover(#[from] Box<dyn std::error::Error>),
+    #[error("The deadline of the request passed before the proof was generated")]
+    DeadlineExceeded,
+    #[error("{0}")]
+    Join(#[from] JoinError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    };
+    use tokio::sync::Semaphore;
+
+    fn deadline_in(duration: Duration) -> Option<u64> {
+        Some(
+            (SystemTime::now() + duration)
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+        )
+    }
+
+    #[tokio::test]
+    async fn job_is_abandoned_once_the_deadline_passed() {
+        let slots = Arc::new(Semaphore::new(1));
+        let slot = slots.clone().try_acquire_owned().unwrap();
+        let started_at = Instant::now();
+        let result = prove_before_deadline(deadline_in(Duration::from_millis(50)), |cancelled| {
+            let _slot = slot;
+            while !cancelled.is_cancelled() {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            Err("finished too late".to_owned())
+        })
+        .await;
+
+        assert!(matches!(result, Err(InternalError::DeadlineExceeded)));
+        assert!(started_at.elapsed() < Duration::from_secs(1));
+        // The job stops on its own instead of holding its slot until it completes.
+        let released = tokio::time::timeout(Duration::from_secs(1), slots.acquire()).await;
+        assert!(released.is_ok());
+    }
+
+    #[tokio::test]
+    async fn job_is_not_started_after_the_deadline() {
+        let started = Arc::new(AtomicBool::new(false));
+        let result = prove_before_deadline(Some(0), {
+            let started = started.clone();
+            move |_| {
+                started.store(true, Ordering::SeqCst);
+                Err("should not run".to_owned())
+            }
+        })
+        .await;
+
+        assert!(matches!(result, Err(InternalError::DeadlineExceeded)));
+        assert!(!started.load(Ordering::SeqCst));
+    }
 }
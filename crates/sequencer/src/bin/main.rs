@@ -9,19 +9,23 @@ use ethrex_storage_rollup::{EngineTypeRollup, StoreRollup};
 use ethrex_vm::EvmEngine;
 use mojave_chain_utils::{
     initializer::{
-        get_authrpc_socket_addr, get_http_socket_addr, get_local_p2p_node, resolve_data_dir,
+        get_authrpc_socket_addr, get_http_socket_addr, get_local_p2p_node, get_rpc_config,
+        resolve_data_dir,
     },
     logging::init_logging,
 };
-use mojave_client::MojaveClient;
+use mojave_client::{ClientConfig, MojaveClient};
 use mojave_sequencer::{
-    block_producer::{BlockProducer, BlockProducerContext},
+    block_producer::{BlockInterval, BlockProducer, BlockProducerContext},
     cli::{Cli, Command},
     error::Error,
+    mempool::spawn_mempool_eviction_task,
     rpc::start_api,
 };
+use mojave_signature::AnySigningKey;
 use std::{path::PathBuf, sync::Arc, time::Duration};
 use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
@@ -51,36 +55,111 @@ async fn main() -> Result<(), Error> {
 
             let blockchain = init_blockchain(EvmEngine::LEVM, store.clone(), BlockchainType::L2);
 
-            let mojave_client = MojaveClient::new(
+            let signing_key = AnySigningKey::from_str_with_scheme(
+                sequencer_options.signature_scheme,
+                &sequencer_options.private_key,
+            )?
+            .with_digest(sequencer_options.signature_digest);
+            let verifying_key = sequencer_options
+                .public_key
+                .clone()
+                .unwrap_or_else(|| signing_key.verifying_key());
+            if let Err(error) = signing_key.self_test(&verifying_key) {
+                tracing::error!(
+                    "The signing key failed its self-test against the public key of address {}: {}",
+                    verifying_key.to_address(),
+                    error
+                );
+                return Err(error.into());
+            }
+            tracing::info!(
+                "The signing key passed its self-test, signing as {}",
+                verifying_key.to_address()
+            );
+            let mut client_config = ClientConfig::default();
+            if let Some(token) = &sequencer_options.full_node_auth_token {
+                client_config = client_config.with_bearer_token(token)?;
+            }
+            let mojave_client = MojaveClient::with_config(
                 &sequencer_options.full_node_addresses,
-                sequencer_options.private_key.as_str(),
+                Some(signing_key),
+                client_config,
             )?;
 
-            let context = BlockProducerContext::new(
+            let mut context = BlockProducerContext::new(
                 store.clone(),
                 blockchain.clone(),
                 rollup_store.clone(),
-                genesis.coinbase,
+                sequencer_options.coinbase.unwrap_or(genesis.coinbase),
                 ELASTICITY_MULTIPLIER,
+            )
+            .with_max_txs_per_block(
+                (sequencer_options.block_max_transactions > 0)
+                    .then_some(sequencer_options.block_max_transactions as usize),
+            )
+            .with_ordering_policy(sequencer_options.block_ordering_policy)
+            .with_build_deadline(
+                (sequencer_options.block_build_deadline > 0)
+                    .then(|| Duration::from_millis(sequencer_options.block_build_deadline)),
+            )
+            .with_skip_empty_blocks(
+                sequencer_options.block_skip_empty,
+                (sequencer_options.block_max_idle_interval > 0)
+                    .then(|| Duration::from_secs(sequencer_options.block_max_idle_interval)),
             );
+            if let Some(max_state_diff_bytes) = sequencer_options.block_max_state_diff_bytes {
+                context = context.with_max_state_diff_bytes(max_state_diff_bytes);
+            }
             let block_producer = BlockProducer::start(context, 100);
+            let block_interval = BlockInterval::new(
+                Duration::from_millis(sequencer_options.block_time),
+                Duration::from_millis(sequencer_options.block_time_jitter),
+            )?;
+
+            let cancel_token = CancellationToken::new();
+
+            if sequencer_options.mempool_tx_ttl > 0 {
+                spawn_mempool_eviction_task(
+                    blockchain.clone(),
+                    Duration::from_secs(sequencer_options.mempool_tx_ttl),
+                    cancel_token.clone(),
+                );
+            }
+
+            let min_fee_bump = sequencer_options.mempool_min_fee_bump;
+            let rpc_mojave_client = mojave_client.clone();
             tokio::spawn(async move {
                 loop {
                     match block_producer.build_block().await {
-                        Ok(block) => mojave_client
-                            .send_broadcast_block(&block)
+                        Ok(None) => tracing::debug!("Skipped an empty block"),
+                        Ok(Some(block)) => match mojave_client
+                            .send_broadcast_block(&block, sequencer_options.broadcast_mode)
                             .await
-                            .unwrap_or_else(|error| tracing::error!("{}", error)),
+                        {
+                            Ok(report) => {
+                                for error in report.errors() {
+                                    tracing::warn!(
+                                        "Full node rejected block {}: {}",
+                                        block.header.number,
+                                        error
+                                    );
+                                }
+                                tracing::debug!(
+                                    accepted = report.accepted(),
+                                    "Broadcast block {}",
+                                    block.header.number
+                                );
+                            }
+                            Err(error) => tracing::error!("{}", error),
+                        },
                         Err(error) => {
                             tracing::error!("Failed to build a block: {}", error);
                         }
                     }
-                    tokio::time::sleep(Duration::from_millis(sequencer_options.block_time)).await;
+                    tokio::time::sleep(block_interval.next()).await;
                 }
             });
 
-            let cancel_token = tokio_util::sync::CancellationToken::new();
-
             let signer = get_signer(&data_dir);
 
             let local_p2p_node = get_local_p2p_node(&options, &signer);
@@ -103,6 +182,15 @@ async fn main() -> Result<(), Error> {
             )
             .await;
 
+            // The RPC server runs until Ctrl-C, which then shuts the rest of the sequencer down.
+            let rpc_shutdown = CancellationToken::new();
+            tokio::spawn({
+                let rpc_shutdown = rpc_shutdown.clone();
+                async move {
+                    let _ = tokio::signal::ctrl_c().await;
+                    rpc_shutdown.cancel();
+                }
+            });
             start_api(
                 get_http_socket_addr(&options),
                 get_authrpc_socket_addr(&options),
@@ -113,23 +201,25 @@ async fn main() -> Result<(), Error> {
                 local_node_record.lock().await.clone(),
                 syncer,
                 peer_handler,
+                peer_table.clone(),
                 get_client_version(),
                 rollup_store.clone(),
+                rpc_mojave_client,
+                min_fee_bump,
+                get_rpc_config(&options),
+                rpc_shutdown.clone(),
             )
             .await?;
 
-            tokio::select! {
-                _ = tokio::signal::ctrl_c() => {
-                    tracing::info!("Shutting down the sequencer..");
-                    let node_config_path = PathBuf::from(data_dir).join("node_config.json");
-                    tracing::info!("Storing config at {:?}...", node_config_path);
-                    cancel_token.cancel();
-                    let node_config = NodeConfigFile::new(peer_table, local_node_record.lock().await.clone()).await;
-                    store_node_config_file(node_config, node_config_path).await;
-                    tokio::time::sleep(Duration::from_secs(1)).await;
-                    tracing::info!("Successfully shut down the sequencer.");
-                }
-            }
+            tracing::info!("Shutting down the sequencer..");
+            let node_config_path = PathBuf::from(data_dir).join("node_config.json");
+            tracing::info!("Storing config at {:?}...", node_config_path);
+            cancel_token.cancel();
+            let node_config =
+                NodeConfigFile::new(peer_table, local_node_record.lock().await.clone()).await;
+            store_node_config_file(node_config, node_config_path).await;
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            tracing::info!("Successfully shut down the sequencer.");
         }
     }
     Ok(())
@@ -1,4 +1,7 @@
-use crate::block_producer::BlockProducerError;
+use crate::block_producer::{
+    BlockProducerError, TransactionExecutionError,
+    ordering::{OrderedTransactions, OrderingPolicy},
+};
 use ethrex_blockchain::{
     Blockchain,
     constants::TX_GAS_COST,
@@ -33,10 +36,21 @@ use std::{
     collections::{BTreeMap, HashMap},
     ops::Div,
     sync::Arc,
-    time::{Instant, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 use tracing::{debug, error, info};
 
+/// Figures of a produced block, for the metrics of the sequencer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BuildStats {
+    pub gas_used: u64,
+    pub tx_count: usize,
+    /// Time spent building, validating and storing the block.
+    pub build_time_ms: u64,
+    /// Size of the state diff of the block, as published in its blob.
+    pub state_diff_bytes: usize,
+}
+
 #[derive(Clone)]
 pub struct BlockProducerContext {
     store: Store,
@@ -44,6 +58,17 @@ pub struct BlockProducerContext {
     rollup_store: StoreRollup,
     coinbase_address: Address,
     elasticity_multiplier: u64,
+    /// Maximum number of transactions of a produced block, bounding its propagation time.
+    max_txs_per_block: Option<usize>,
+    ordering_policy: OrderingPolicy,
+    /// Whether a block without transactions is discarded instead of committed.
+    skip_empty_blocks: bool,
+    /// Time after which an empty block is committed anyway, for the chain time to advance.
+    max_idle_interval: Option<Duration>,
+    /// Data budget of a block: the maximum size of its state diff.
+    max_state_diff_bytes: usize,
+    /// Time after which no more transactions are added to a block being filled.
+    build_deadline: Option<Duration>,
 }
 
 impl BlockProducerContext {
@@ -60,10 +85,62 @@ impl BlockProducerContext {
             rollup_store,
             coinbase_address,
             elasticity_multiplier,
+            max_txs_per_block: None,
+            ordering_policy: OrderingPolicy::default(),
+            skip_empty_blocks: false,
+            max_idle_interval: None,
+            max_state_diff_bytes: SAFE_BYTES_PER_BLOB,
+            build_deadline: None,
         }
     }
 
-    pub(crate) async fn build_block(&self) -> Result<Block, BlockProducerError> {
+    /// Stops filling a block once it holds `max_txs_per_block` transactions, unbounded if
+    /// `None`.
+    pub fn with_max_txs_per_block(mut self, max_txs_per_block: Option<usize>) -> Self {
+        self.max_txs_per_block = max_txs_per_block;
+        self
+    }
+
+    /// Chooses which mempool transactions are included first, and so which ones make it into a
+    /// block that fills up.
+    pub fn with_ordering_policy(mut self, ordering_policy: OrderingPolicy) -> Self {
+        self.ordering_policy = ordering_policy;
+        self
+    }
+
+    /// Stops filling a block once its state diff would exceed `max_state_diff_bytes`, e.g. to
+    /// publish smaller blobs. Defaults to the size of a blob, `SAFE_BYTES_PER_BLOB`.
+    pub fn with_max_state_diff_bytes(mut self, max_state_diff_bytes: usize) -> Self {
+        self.max_state_diff_bytes = max_state_diff_bytes;
+        self
+    }
+
+    /// Stops adding transactions to a block once filling it took `build_deadline`, so that a few
+    /// execution-heavy transactions do not delay the next blocks. The block holds the
+    /// transactions executed so far, unbounded if `None`.
+    pub fn with_build_deadline(mut self, build_deadline: Option<Duration>) -> Self {
+        self.build_deadline = build_deadline;
+        self
+    }
+
+    /// Discards the blocks without transactions if `skip_empty_blocks` is set, unless the head
+    /// block is older than `max_idle_interval`.
+    pub fn with_skip_empty_blocks(
+        mut self,
+        skip_empty_blocks: bool,
+        max_idle_interval: Option<Duration>,
+    ) -> Self {
+        self.skip_empty_blocks = skip_empty_blocks;
+        self.max_idle_interval = max_idle_interval;
+        self
+    }
+
+    /// Returns the block along with its figures, or `None` if the block was empty and
+    /// discarded, see [`BlockProducerContext::with_skip_empty_blocks`].
+    pub(crate) async fn build_block(
+        &self,
+    ) -> Result<Option<(Block, BuildStats)>, BlockProducerError> {
+        let started_at = Instant::now();
         let version = 3;
         let head_header = {
             let current_block_number = self.store.get_latest_block_number().await?;
@@ -96,21 +173,62 @@ impl BlockProducerContext {
         let payload = self.create_payload(&args)?;
 
         // Blockchain builds the payload from mempool txs and executes them
-        let payload_build_result = self.build_payload(payload).await?;
+        let (payload_build_result, state_diff_bytes) = self.build_payload(payload).await?;
         info!(
             "Built payload for new block {}",
             payload_build_result.payload.header.number
         );
 
-        // Blockchain stores block
+        if self.skip_empty_blocks
+            && payload_build_result.payload.body.transactions.is_empty()
+            && self.max_idle_interval.is_none_or(|max_idle_interval| {
+                args.timestamp.saturating_sub(head_header.timestamp) < max_idle_interval.as_secs()
+            })
+        {
+            debug!("Discarding empty block");
+            return Ok(None);
+        }
+
+        let block = self
+            .commit_block(&head_header, payload_build_result)
+            .await?;
+
+        // metrics!(
+        //     let _ = METRICS_BLOCKS
+        //     .set_block_number(block.header.number)
+        //     .inspect_err(|e| {
+        //         tracing::error!("Failed to set metric: block_number {}", e.to_string())
+        //     });
+        //     #[allow(clippy::as_conversions)]
+        //     let tps = block.body.transactions.len() as f64 / (state.block_time_ms as f64 / 1000_f64);
+        //     METRICS_TX.set_transactions_per_second(tps);
+        // );
+        let stats = BuildStats {
+            gas_used: block.header.gas_used,
+            tx_count: block.body.transactions.len(),
+            build_time_ms: started_at.elapsed().as_millis() as u64,
+            state_diff_bytes,
+        };
+        Ok(Some((block, stats)))
+    }
+
+    /// Validates the built payload against its parent and, only if it is valid, stores it and
+    /// makes it the new head. A block failing validation is discarded as a whole: nothing is
+    /// written to the store and its transactions stay in the mempool.
+    async fn commit_block(
+        &self,
+        head_header: &BlockHeader,
+        payload_build_result: PayloadBuildResult,
+    ) -> Result<Block, BlockProducerError> {
         let block = payload_build_result.payload;
         let chain_config = self.store.get_chain_config()?;
         validate_block(
             &block,
-            &head_header,
+            head_header,
             &chain_config,
             self.elasticity_multiplier,
-        )?;
+        )
+        .map_err(|error| BlockProducerError::Validation(error.into()))?;
 
         let account_updates = payload_build_result.account_updates;
 
@@ -119,6 +237,7 @@ impl BlockProducerContext {
             requests: Vec::new(),
         };
 
+        // Blockchain stores block
         let account_updates_list = self
             .store
             .apply_account_updates_batch(block.header.parent_hash, &account_updates)
@@ -138,16 +257,12 @@ impl BlockProducerContext {
         // Make the new head be part of the canonical chain
         apply_fork_choice(&self.store, block.hash(), block.hash(), block.hash()).await?;
 
-        // metrics!(
-        //     let _ = METRICS_BLOCKS
-        //     .set_block_number(block.header.number)
-        //     .inspect_err(|e| {
-        //         tracing::error!("Failed to set metric: block_number {}", e.to_string())
-        //     });
-        //     #[allow(clippy::as_conversions)]
-        //     let tps = block.body.transactions.len() as f64 / (state.block_time_ms as f64 / 1000_f64);
-        //     METRICS_TX.set_transactions_per_second(tps);
-        // );
+        // Pull the included transactions from the mempool now that the block is committed
+        for tx in &block.body.transactions {
+            self.blockchain
+                .remove_transaction_from_pool(&tx.compute_hash())?;
+        }
+
         Ok(block)
     }
 
@@ -223,10 +338,11 @@ impl BlockProducerContext {
     /// L2 payload builder
     /// Completes the payload building process, return the block value
     /// Same as `blockchain::build_payload` without applying system operations and using a different `fill_transactions`
+    /// Also returns the size of the state diff of the payload.
     async fn build_payload(
         &self,
         payload: Block,
-    ) -> Result<PayloadBuildResult, BlockProducerError> {
+    ) -> Result<(PayloadBuildResult, usize), BlockProducerError> {
         let since = Instant::now();
         let gas_limit = payload.header.gas_limit;
 
@@ -238,7 +354,7 @@ impl BlockProducerContext {
             self.blockchain.r#type.clone(),
         )?;
 
-        self.fill_transactions(&mut context).await?;
+        let state_diff_bytes = self.fill_transactions(&mut context).await?;
         self.blockchain.finalize_payload(&mut context).await?;
 
         let interval = Instant::now().duration_since(since).as_millis();
@@ -274,15 +390,18 @@ impl BlockProducerContext {
         //         .inspect_err(|e| tracing::error!("Failed to set metrics for: blob tx mempool size {}", e.to_string()));
         // );
 
-        Ok(context.into())
+        Ok((context.into(), state_diff_bytes))
     }
 
     /// Same as `blockchain::fill_transactions` but enforces that the `StateDiff` size
-    /// stays within the blob size limit after processing each transaction.
+    /// stays within the `max_state_diff_bytes` budget after processing each transaction, and
+    /// offers the transactions in the order of the [`OrderingPolicy`] of the context. Returns
+    /// the size of the resulting `StateDiff`.
     async fn fill_transactions(
         &self,
         context: &mut PayloadBuildContext,
-    ) -> Result<(), BlockProducerError> {
+    ) -> Result<usize, BlockProducerError> {
+        let started_at = Instant::now();
         // version (u8) + header fields (struct) + messages_len (u16) + deposits_len (u16) + accounts_diffs_len (u16)
         let mut acc_size_without_accounts = 1 + *BLOCK_HEADER_LEN + 2 + 2 + 2;
         let mut size_accounts_diffs = 0;
@@ -293,7 +412,11 @@ impl BlockProducerContext {
         debug!("Fetching transactions from mempool");
         // Fetch mempool transactions
         let latest_block_number = self.store.get_latest_block_number().await?;
-        let mut txs = self.fetch_mempool_transactions(context)?;
+        let mut txs = OrderedTransactions::new(
+            self.fetch_mempool_transactions(context)?,
+            self.ordering_policy,
+            context.payload.header.base_fee_per_gas,
+        )?;
         // Execute and add transactions to payload (if suitable)
         loop {
             // Check if we have enough gas to run more transactions
@@ -302,9 +425,25 @@ impl BlockProducerContext {
                 break;
             };
 
+            if self
+                .build_deadline
+                .is_some_and(|deadline| started_at.elapsed() >= deadline)
+            {
+                debug!("Block build deadline reached");
+                break;
+            }
+
+            if self
+                .max_txs_per_block
+                .is_some_and(|max| context.payload.body.transactions.len() >= max)
+            {
+                debug!("Maximum number of transactions per block reached");
+                break;
+            }
+
             // Check if we have enough space for the StateDiff to run more transactions
             if acc_size_without_accounts + size_accounts_diffs + SIMPLE_TX_STATE_DIFF_SIZE
-                > SAFE_BYTES_PER_BLOB
+                > self.max_state_diff_bytes
             {
                 debug!("No more StateDiff space to run transactions");
                 break;
@@ -355,16 +494,19 @@ impl BlockProducerContext {
             }
 
             // Execute tx
-            let receipt = match apply_plain_transaction(&head_tx, context) {
+            let receipt = match apply_plain_transaction(&head_tx, context)
+                .map_err(TransactionExecutionError::from)
+            {
                 Ok(receipt) => receipt,
-                Err(e) => {
-                    debug!("Failed to execute transaction: {}, {e}", tx_hash);
+                Err(TransactionExecutionError::Invalid(reason)) => {
+                    debug!(%reason, "Dropping transaction that failed to execute: {tx_hash:#x}");
                     // metrics!(METRICS_TX.inc_tx_errors(e.to_metric()));
 
                     // Ignore following txs from sender
                     txs.pop();
                     continue;
                 }
+                Err(error) => return Err(error.into()),
             };
 
             let account_diffs_in_tx = self.get_account_diffs_in_tx(context)?;
@@ -379,7 +521,7 @@ impl BlockProducerContext {
             )?;
 
             if acc_size_without_accounts + tx_size_without_accounts + new_accounts_diff_size
-                > SAFE_BYTES_PER_BLOB
+                > self.max_state_diff_bytes
             {
                 debug!(
                     "No more StateDiff space to run this transactions. Skipping transaction: {:?}",
@@ -393,10 +535,8 @@ impl BlockProducerContext {
                 continue;
             }
 
-            txs.shift()?;
-            // Pull transaction from the mempool
-            self.blockchain
-                .remove_transaction_from_pool(&head_tx.tx.compute_hash())?;
+            // The transaction is pulled from the mempool once the block is committed
+            txs.shift();
 
             // We only add the messages and deposits length because the accounts diffs may change
             acc_size_without_accounts += tx_size_without_accounts;
@@ -419,7 +559,7 @@ impl BlockProducerContext {
         //         .for_each(|tx| METRICS_TX.inc_tx_with_type(MetricsTxType(tx.tx_type())))
         // );
 
-        Ok(())
+        Ok(acc_size_without_accounts + size_accounts_diffs)
     }
 
     // TODO: Once #2857 is implemented, we can completely ignore the blobs pool.
@@ -599,3 +739,365 @@ impl BlockProducerContext {
         matches!(tx, Transaction::PrivilegedL2Transaction(_tx))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethrex_blockchain::mempool::PendingTxFilter;
+    use ethrex_common::types::{
+        ChainConfig, EIP1559Transaction, ELASTICITY_MULTIPLIER, Genesis, GenesisAccount, Signable,
+        TxKind,
+    };
+    use ethrex_storage::EngineType;
+    use ethrex_storage_rollup::EngineTypeRollup;
+    use secp256k1::SecretKey;
+
+    fn build_genesis() -> Genesis {
+        Genesis {
+            config: ChainConfig {
+                chain_id: 1,
+                london_block: Some(0),
+                ..Default::default()
+            },
+            alloc: BTreeMap::new(),
+            coinbase: Address::zero(),
+            difficulty: U256::zero(),
+            extra_data: Bytes::new(),
+            gas_limit: 30_000_000,
+            nonce: 0,
+            mix_hash: H256::zero(),
+            timestamp: 0,
+            base_fee_per_gas: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            requests_hash: None,
+        }
+    }
+
+    async fn test_context(coinbase_address: Address) -> BlockProducerContext {
+        test_context_with_genesis(coinbase_address, build_genesis()).await
+    }
+
+    async fn test_context_with_genesis(
+        coinbase_address: Address,
+        genesis: Genesis,
+    ) -> BlockProducerContext {
+        let store = Store::new("", EngineType::InMemory).unwrap();
+        store.add_initial_state(genesis).await.unwrap();
+        let blockchain = Arc::new(Blockchain::default_with_store(store.clone()));
+        let rollup_store = StoreRollup::new("", EngineTypeRollup::InMemory).unwrap();
+        rollup_store.init().await.unwrap();
+        BlockProducerContext::new(
+            store,
+            blockchain,
+            rollup_store,
+            coinbase_address,
+            ELASTICITY_MULTIPLIER,
+        )
+    }
+
+    fn transfer(key: &SecretKey, nonce: u64, max_priority_fee_per_gas: u64) -> Transaction {
+        let mut tx = Transaction::EIP1559Transaction(EIP1559Transaction {
+            chain_id: 1,
+            nonce,
+            gas_limit: 21_000,
+            max_fee_per_gas: 2_000_000_000,
+            max_priority_fee_per_gas,
+            to: TxKind::Call(Address::from_low_u64_be(1)),
+            value: U256::one(),
+            ..Default::default()
+        });
+        tx.sign_inplace(key);
+        tx
+    }
+
+    fn funded_genesis(senders: &[Address]) -> Genesis {
+        let alloc = senders
+            .iter()
+            .map(|sender| {
+                let account = GenesisAccount {
+                    code: Bytes::new(),
+                    storage: Default::default(),
+                    balance: U256::from(10).pow(U256::from(20)),
+                    nonce: 0,
+                };
+                (*sender, account)
+            })
+            .collect();
+        Genesis {
+            alloc,
+            ..build_genesis()
+        }
+    }
+
+    fn payload_args(context: &BlockProducerContext, head_header: &BlockHeader) -> BuildPayloadArgs {
+        BuildPayloadArgs {
+            parent: head_header.hash(),
+            timestamp: head_header.timestamp + 1,
+            fee_recipient: context.coinbase_address,
+            random: H256::zero(),
+            withdrawals: Default::default(),
+            beacon_root: Some(H256::zero()),
+            version: 3,
+            elasticity_multiplier: context.elasticity_multiplier,
+        }
+    }
+
+    #[tokio::test]
+    async fn validation_failure_discards_the_block() {
+        let context = test_context(Address::zero()).await;
+        let head_header = context.store.get_block_header(0).unwrap().unwrap();
+
+        let payload = context
+            .create_payload(&payload_args(&context, &head_header))
+            .unwrap();
+        let (mut payload_build_result, _) = context.build_payload(payload).await.unwrap();
+        // Way above the gas limit delta allowed between a block and its parent
+        payload_build_result.payload.header.gas_limit = head_header.gas_limit * 2;
+        let invalid_hash = payload_build_result.payload.hash();
+
+        let result = context
+            .commit_block(&head_header, payload_build_result)
+            .await;
+        assert!(matches!(result, Err(BlockProducerError::Validation(_))));
+
+        assert_eq!(context.store.get_latest_block_number().await.unwrap(), 0);
+        assert!(context.store.get_block_header(1).unwrap().is_none());
+        assert!(
+            context
+                .store
+                .get_block_header_by_hash(invalid_hash)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn produced_block_honors_the_transaction_cap() {
+        let key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let transfers: Vec<_> = (0..5)
+            .map(|nonce| transfer(&key, nonce, 1_000_000_000))
+            .collect();
+        let sender = transfers[0].sender().unwrap();
+        let genesis = funded_genesis(&[sender]);
+        let context = test_context_with_genesis(Address::zero(), genesis)
+            .await
+            .with_max_txs_per_block(Some(2));
+        for tx in transfers {
+            context
+                .blockchain
+                .add_transaction_to_pool(tx)
+                .await
+                .unwrap();
+        }
+
+        let (block, _) = context.build_block().await.unwrap().unwrap();
+
+        assert_eq!(block.body.transactions.len(), 2);
+        let pending = context
+            .blockchain
+            .mempool
+            .filter_transactions(&PendingTxFilter::default())
+            .unwrap();
+        assert_eq!(pending[&sender].len(), 3);
+    }
+
+    #[tokio::test]
+    async fn full_block_includes_the_transaction_of_the_ordering_policy() {
+        for (policy, included) in [(OrderingPolicy::PriorityFee, 1), (OrderingPolicy::Fifo, 0)] {
+            let low_tip = transfer(&SecretKey::from_slice(&[1; 32]).unwrap(), 0, 100_000_000);
+            let high_tip = transfer(&SecretKey::from_slice(&[2; 32]).unwrap(), 0, 1_000_000_000);
+            let transfers = [low_tip, high_tip];
+            let senders: Vec<_> = transfers.iter().map(|tx| tx.sender().unwrap()).collect();
+            // Leaves room for a single transfer in the next block
+            let genesis = Genesis {
+                gas_limit: 40_000,
+                ..funded_genesis(&senders)
+            };
+            let context = test_context_with_genesis(Address::zero(), genesis)
+                .await
+                .with_ordering_policy(policy);
+            // The low tip transfer is the oldest one
+            for tx in transfers.clone() {
+                context
+                    .blockchain
+                    .add_transaction_to_pool(tx)
+                    .await
+                    .unwrap();
+            }
+
+            let (block, _) = context.build_block().await.unwrap().unwrap();
+
+            let included_hashes: Vec<_> = block
+                .body
+                .transactions
+                .iter()
+                .map(Transaction::compute_hash)
+                .collect();
+            assert_eq!(
+                included_hashes,
+                vec![transfers[included].compute_hash()],
+                "{policy}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn transaction_failing_to_execute_is_dropped_from_the_block() {
+        let key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let balance = U256::from(10).pow(U256::from(20));
+        // Each transfer is affordable on its own, but the first one leaves nothing for the gas
+        // of the second one.
+        let mut drain = Transaction::EIP1559Transaction(EIP1559Transaction {
+            chain_id: 1,
+            gas_limit: 21_000,
+            max_fee_per_gas: 2_000_000_000,
+            max_priority_fee_per_gas: 1_000_000_000,
+            to: TxKind::Call(Address::from_low_u64_be(1)),
+            value: balance - U256::from(21_000u64 * 2_000_000_000),
+            ..Default::default()
+        });
+        drain.sign_inplace(&key);
+        let unaffordable = transfer(&key, 1, 1_000_000_000);
+        let other = transfer(&SecretKey::from_slice(&[2; 32]).unwrap(), 0, 1_000_000_000);
+        let senders = [drain.sender().unwrap(), other.sender().unwrap()];
+        let context = test_context_with_genesis(Address::zero(), funded_genesis(&senders)).await;
+        for tx in [drain.clone(), unaffordable.clone(), other.clone()] {
+            context
+                .blockchain
+                .add_transaction_to_pool(tx)
+                .await
+                .unwrap();
+        }
+
+        let (block, _) = context.build_block().await.unwrap().unwrap();
+
+        let included_hashes: Vec<_> = block
+            .body
+            .transactions
+            .iter()
+            .map(Transaction::compute_hash)
+            .collect();
+        assert_eq!(included_hashes.len(), 2);
+        assert!(included_hashes.contains(&drain.compute_hash()));
+        assert!(included_hashes.contains(&other.compute_hash()));
+        assert!(!included_hashes.contains(&unaffordable.compute_hash()));
+    }
+
+    #[tokio::test]
+    async fn empty_block_is_skipped_until_the_max_idle_interval() {
+        let context = test_context(Address::zero())
+            .await
+            .with_skip_empty_blocks(true, None);
+
+        assert!(context.build_block().await.unwrap().is_none());
+        assert_eq!(context.store.get_latest_block_number().await.unwrap(), 0);
+
+        // The genesis block is way older than the interval.
+        let context = context.with_skip_empty_blocks(true, Some(Duration::from_secs(60)));
+        let (block, _) = context.build_block().await.unwrap().unwrap();
+        assert!(block.body.transactions.is_empty());
+        assert_eq!(context.store.get_latest_block_number().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn build_stats_describe_the_produced_block() {
+        let key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let transfers: Vec<_> = (0..3)
+            .map(|nonce| transfer(&key, nonce, 1_000_000_000))
+            .collect();
+        let genesis = funded_genesis(&[transfers[0].sender().unwrap()]);
+        let context = test_context_with_genesis(Address::zero(), genesis).await;
+        for tx in transfers {
+            context
+                .blockchain
+                .add_transaction_to_pool(tx)
+                .await
+                .unwrap();
+        }
+
+        let (block, stats) = context.build_block().await.unwrap().unwrap();
+
+        assert_eq!(stats.tx_count, 3);
+        assert_eq!(stats.gas_used, 3 * 21_000);
+        assert_eq!(stats.gas_used, block.header.gas_used);
+        // At least the header of the block, see `fill_transactions`.
+        assert!(stats.state_diff_bytes > *BLOCK_HEADER_LEN);
+    }
+
+    #[tokio::test]
+    async fn produced_block_honors_the_state_diff_budget() {
+        let key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let transfers: Vec<_> = (0..3)
+            .map(|nonce| transfer(&key, nonce, 1_000_000_000))
+            .collect();
+        let genesis = funded_genesis(&[transfers[0].sender().unwrap()]);
+
+        // Measures the state diff of a block holding the first transfer only.
+        let context = test_context_with_genesis(Address::zero(), genesis.clone()).await;
+        context
+            .blockchain
+            .add_transaction_to_pool(transfers[0].clone())
+            .await
+            .unwrap();
+        let (_, stats) = context.build_block().await.unwrap().unwrap();
+
+        let context = test_context_with_genesis(Address::zero(), genesis)
+            .await
+            .with_max_state_diff_bytes(stats.state_diff_bytes);
+        for tx in transfers {
+            context
+                .blockchain
+                .add_transaction_to_pool(tx)
+                .await
+                .unwrap();
+        }
+
+        let (block, stats_with_budget) = context.build_block().await.unwrap().unwrap();
+
+        assert_eq!(block.body.transactions.len(), 1);
+        assert_eq!(stats_with_budget.state_diff_bytes, stats.state_diff_bytes);
+    }
+
+    #[tokio::test]
+    async fn build_deadline_stops_filling_the_block() {
+        let key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let transfers: Vec<_> = (0..3)
+            .map(|nonce| transfer(&key, nonce, 1_000_000_000))
+            .collect();
+        let sender = transfers[0].sender().unwrap();
+        let context = test_context_with_genesis(Address::zero(), funded_genesis(&[sender]))
+            .await
+            .with_build_deadline(Some(Duration::ZERO));
+        for tx in transfers {
+            context
+                .blockchain
+                .add_transaction_to_pool(tx)
+                .await
+                .unwrap();
+        }
+
+        let (block, _) = context.build_block().await.unwrap().unwrap();
+
+        // The block is still committed, without the transactions left for later blocks.
+        assert!(block.body.transactions.is_empty());
+        assert_eq!(context.store.get_latest_block_number().await.unwrap(), 1);
+        let pending = context
+            .blockchain
+            .mempool
+            .filter_transactions(&PendingTxFilter::default())
+            .unwrap();
+        assert_eq!(pending[&sender].len(), 3);
+    }
+
+    #[tokio::test]
+    async fn produced_block_uses_configured_coinbase() {
+        let coinbase = Address::from_low_u64_be(0xc0ffee);
+        let context = test_context(coinbase).await;
+
+        let (block, _) = context.build_block().await.unwrap().unwrap();
+
+        assert_eq!(block.header.coinbase, coinbase);
+        assert_eq!(context.store.get_latest_block_number().await.unwrap(), 1);
+    }
+}
@@ -3,7 +3,10 @@ use ethrex_l2_common::state_diff::StateDiffError;
 use ethrex_storage::error::StoreError;
 use ethrex_storage_rollup::RollupStoreError;
 use ethrex_vm::EvmError;
-use std::{num::TryFromIntError, time::SystemTimeError};
+use std::{
+    num::TryFromIntError,
+    time::{Duration, SystemTimeError},
+};
 use tokio::sync::oneshot::error::RecvError;
 
 #[derive(Debug, thiserror::Error)]
@@ -22,6 +25,8 @@ pub enum BlockProducerError {
     FailedToGetSystemTime(#[from] SystemTimeError),
     #[error("BlockProducer failed because of a ChainError error: {0}")]
     ChainError(#[from] ChainError),
+    #[error("BlockProducer discarded the block because it failed validation: {0}")]
+    Validation(ChainError),
     #[error("BlockProducer failed because of a EvmError error: {0}")]
     EvmError(#[from] EvmError),
     #[error("BlockProducer failed because of a rollup store error: {0}")]
@@ -34,4 +39,62 @@ pub enum BlockProducerError {
     TryIntoError(#[from] TryFromIntError),
     #[error("Failed to encode AccountStateDiff: {0}")]
     FailedToEncodeAccountStateDiff(#[from] StateDiffError),
+    #[error("BlockProducer failed to execute a transaction: {0}")]
+    TransactionExecution(#[from] TransactionExecutionError),
+    #[error("Block time jitter {jitter:?} must be below the block time {block_time:?}")]
+    InvalidJitter {
+        jitter: Duration,
+        block_time: Duration,
+    },
+}
+
+/// Why a transaction could not be executed while filling a block, from the error of the VM. A
+/// transaction that reverts or runs out of gas is not an error: it is included in the block with
+/// a failed receipt.
+#[derive(Debug, thiserror::Error)]
+pub enum TransactionExecutionError {
+    /// The transaction fails the validation of the VM against the state of the block, e.g. its
+    /// sender can no longer pay for it. It is dropped from the block being built, block
+    /// production carries on.
+    #[error("invalid transaction: {0}")]
+    Invalid(String),
+    /// The VM or the state it reads failed, which is not the transaction's fault and fails the
+    /// block being built.
+    #[error("{0}")]
+    Vm(ChainError),
+}
+
+impl From<ChainError> for TransactionExecutionError {
+    fn from(error: ChainError) -> Self {
+        match error {
+            ChainError::EvmError(EvmError::Transaction(reason)) => Self::Invalid(reason),
+            error => Self::Vm(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_transaction_validation_failures_are_invalid_transactions() {
+        let error = ChainError::EvmError(EvmError::Transaction(
+            "Insufficient account funds".to_owned(),
+        ));
+        assert!(matches!(
+            TransactionExecutionError::from(error),
+            TransactionExecutionError::Invalid(reason) if reason == "Insufficient account funds"
+        ));
+
+        let error = ChainError::EvmError(EvmError::DB("missing trie node".to_owned()));
+        assert!(matches!(
+            BlockProducerError::from(TransactionExecutionError::from(error)),
+            BlockProducerError::TransactionExecution(TransactionExecutionError::Vm(_))
+        ));
+        assert!(matches!(
+            TransactionExecutionError::from(ChainError::ParentNotFound),
+            TransactionExecutionError::Vm(_)
+        ));
+    }
 }
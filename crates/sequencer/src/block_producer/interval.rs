@@ -0,0 +1,63 @@
+use crate::block_producer::BlockProducerError;
+use rand::Rng;
+use std::time::Duration;
+
+/// Time to wait between two produced blocks, the block time shifted by a random offset within
+/// the jitter for the sequencers of a multi-sequencer setup not to broadcast in lockstep.
+#[derive(Clone, Copy, Debug)]
+pub struct BlockInterval {
+    block_time: Duration,
+    jitter: Duration,
+}
+
+impl BlockInterval {
+    /// Fails if the jitter is not below the block time, which would let two blocks be produced
+    /// back to back.
+    pub fn new(block_time: Duration, jitter: Duration) -> Result<Self, BlockProducerError> {
+        if !jitter.is_zero() && jitter >= block_time {
+            return Err(BlockProducerError::InvalidJitter { jitter, block_time });
+        }
+        Ok(Self { block_time, jitter })
+    }
+
+    /// Returns a random interval between the block time minus and plus the jitter.
+    pub fn next(&self) -> Duration {
+        if self.jitter.is_zero() {
+            return self.block_time;
+        }
+        let min = self.block_time - self.jitter;
+        let max = self.block_time + self.jitter;
+        rand::thread_rng().gen_range(min..=max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn interval_varies_within_the_jitter() {
+        let interval =
+            BlockInterval::new(Duration::from_millis(1000), Duration::from_millis(200)).unwrap();
+        let intervals: HashSet<Duration> = (0..100).map(|_| interval.next()).collect();
+        assert!(intervals.len() > 1);
+        for interval in intervals {
+            assert!(interval >= Duration::from_millis(800));
+            assert!(interval <= Duration::from_millis(1200));
+        }
+
+        let steady = BlockInterval::new(Duration::from_millis(1000), Duration::ZERO).unwrap();
+        assert_eq!(steady.next(), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn jitter_must_be_below_the_block_time() {
+        let block_time = Duration::from_millis(1000);
+        assert!(BlockInterval::new(block_time, Duration::from_millis(999)).is_ok());
+        assert!(matches!(
+            BlockInterval::new(block_time, block_time),
+            Err(BlockProducerError::InvalidJitter { .. })
+        ));
+    }
+}
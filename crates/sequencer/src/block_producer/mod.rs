@@ -1,7 +1,11 @@
 mod context;
 mod error;
+mod interval;
+mod ordering;
 mod service;
 
-pub use context::BlockProducerContext;
-pub use error::BlockProducerError;
+pub use context::{BlockProducerContext, BuildStats};
+pub use error::{BlockProducerError, TransactionExecutionError};
+pub use interval::BlockInterval;
+pub use ordering::OrderingPolicy;
 pub use service::BlockProducer;
@@ -0,0 +1,123 @@
+use ethrex_blockchain::{
+    error::ChainError,
+    payload::{HeadTransaction, TransactionQueue},
+};
+use ethrex_common::Address;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, VecDeque},
+    fmt,
+    str::FromStr,
+};
+
+/// Order in which the mempool transactions are offered to a block being filled. The
+/// transactions of a sender are always included in nonce order, the policy only chooses between
+/// the next transactions of the different senders.
+///
+/// When the block fills up, by gas or by state diff space, the policy decides which
+/// transactions make it in and which wait for a later block.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OrderingPolicy {
+    /// Highest effective tip given the base fee of the block first, the oldest transaction first
+    /// on equal tips.
+    #[default]
+    PriorityFee,
+    /// Oldest transaction in the mempool first, whatever its fees.
+    Fifo,
+}
+
+impl FromStr for OrderingPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "priority-fee" => Ok(Self::PriorityFee),
+            "fifo" => Ok(Self::Fifo),
+            other => Err(format!(
+                "unknown ordering policy {other}, expected priority-fee or fifo"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OrderingPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PriorityFee => write!(f, "priority-fee"),
+            Self::Fifo => write!(f, "fifo"),
+        }
+    }
+}
+
+/// Mempool transactions of a block being filled, with the same `peek`/`pop`/`shift` interface
+/// as the [`TransactionQueue`] they are taken from but ordered by an [`OrderingPolicy`].
+pub(crate) struct OrderedTransactions {
+    policy: OrderingPolicy,
+    base_fee: Option<u64>,
+    /// Next transaction of each sender, the one to include first last.
+    heads: Vec<HeadTransaction>,
+    /// Transactions of each sender following its head, in nonce order.
+    pending: HashMap<Address, VecDeque<HeadTransaction>>,
+}
+
+impl OrderedTransactions {
+    pub(crate) fn new(
+        mut queue: TransactionQueue,
+        policy: OrderingPolicy,
+        base_fee: Option<u64>,
+    ) -> Result<Self, ChainError> {
+        let mut pending: HashMap<Address, VecDeque<HeadTransaction>> = HashMap::new();
+        while let Some(head) = queue.peek() {
+            pending.entry(head.tx.sender()).or_default().push_back(head);
+            queue.shift()?;
+        }
+        let senders: Vec<Address> = pending.keys().copied().collect();
+        let mut ordered = Self {
+            policy,
+            base_fee,
+            heads: Vec::with_capacity(senders.len()),
+            pending,
+        };
+        for sender in senders {
+            ordered.push_next(sender);
+        }
+        Ok(ordered)
+    }
+
+    pub(crate) fn peek(&self) -> Option<HeadTransaction> {
+        self.heads.last().cloned()
+    }
+
+    /// Drops the next transaction along with the following ones of its sender, which can no
+    /// longer be included.
+    pub(crate) fn pop(&mut self) {
+        if let Some(head) = self.heads.pop() {
+            self.pending.remove(&head.tx.sender());
+        }
+    }
+
+    /// Moves on from the next transaction, once included, to the following one of its sender.
+    pub(crate) fn shift(&mut self) {
+        if let Some(head) = self.heads.pop() {
+            self.push_next(head.tx.sender());
+        }
+    }
+
+    fn push_next(&mut self, sender: Address) {
+        let Some(next) = self.pending.get_mut(&sender).and_then(VecDeque::pop_front) else {
+            return;
+        };
+        let rank = self.rank(&next);
+        let index = self.heads.partition_point(|head| self.rank(head) < rank);
+        self.heads.insert(index, next);
+    }
+
+    /// Transactions of higher rank are included first.
+    fn rank(&self, head: &HeadTransaction) -> (u64, Reverse<u128>) {
+        let tip = match self.policy {
+            OrderingPolicy::PriorityFee => head.tx.effective_gas_tip(self.base_fee).unwrap_or(0),
+            OrderingPolicy::Fifo => 0,
+        };
+        (tip, Reverse(head.tx.time()))
+    }
+}
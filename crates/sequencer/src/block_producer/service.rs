@@ -1,4 +1,4 @@
-use crate::block_producer::{BlockProducerContext, BlockProducerError};
+use crate::block_producer::{BlockProducerContext, BlockProducerError, BuildStats};
 use ethrex_common::types::Block;
 use tokio::sync::{
     mpsc::{self, error::TrySendError},
@@ -27,7 +27,19 @@ impl BlockProducer {
         Self { sender }
     }
 
-    pub async fn build_block(&self) -> Result<Block, BlockProducerError> {
+    /// Returns `None` if the block was empty and discarded, see
+    /// [`BlockProducerContext::with_skip_empty_blocks`].
+    pub async fn build_block(&self) -> Result<Option<Block>, BlockProducerError> {
+        Ok(self
+            .build_block_with_stats()
+            .await?
+            .map(|(block, _stats)| block))
+    }
+
+    /// Same as [`BlockProducer::build_block`], along with the figures of the block.
+    pub async fn build_block_with_stats(
+        &self,
+    ) -> Result<Option<(Block, BuildStats)>, BlockProducerError> {
         let (sender, receiver) = oneshot::channel();
         self.sender
             .try_send(Message::BuildBlock(sender))
@@ -49,5 +61,5 @@ async fn handle_message(context: &BlockProducerContext, message: Message) {
 
 #[allow(clippy::large_enum_variant)]
 enum Message {
-    BuildBlock(oneshot::Sender<Result<Block, BlockProducerError>>),
+    BuildBlock(oneshot::Sender<Result<Option<(Block, BuildStats)>, BlockProducerError>>),
 }
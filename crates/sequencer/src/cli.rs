@@ -1,5 +1,10 @@
+use crate::block_producer::OrderingPolicy;
 use clap::{ArgGroup, Parser, Subcommand};
+use ethrex_common::Address;
 use mojave_chain_utils::options::Options;
+use mojave_client::BroadcastMode;
+use mojave_signature::{AnyVerifyingKey, DigestKind, SignatureScheme};
+use std::str::FromStr;
 use tracing::Level;
 
 #[allow(clippy::upper_case_acronyms)]
@@ -52,22 +57,290 @@ pub struct SequencerOpts {
         value_delimiter = ','
     )]
     pub full_node_addresses: Vec<String>,
+    #[arg(
+        long = "full_node.auth_token",
+        help = "Bearer token authenticating the broadcasts to a gateway in front of the full nodes",
+        help_heading = "Full Node Options",
+        env = "MOJAVE_FULL_NODE_AUTH_TOKEN"
+    )]
+    pub full_node_auth_token: Option<String>,
     #[arg(
         long = "block_time",
         help = "Block creation interval in milliseconds",
         default_value = "1000"
     )]
     pub block_time: u64,
+    #[arg(
+        long = "block.time_jitter",
+        help = "Maximum random offset in milliseconds added to or removed from the block creation interval, for the sequencers of a multi-sequencer setup not to broadcast at the same time. Must be below --block_time, 0 disables the jitter",
+        default_value = "0"
+    )]
+    pub block_time_jitter: u64,
+    #[arg(
+        long = "block.max_transactions",
+        help = "Maximum number of transactions of a produced block, 0 leaves it bounded by the gas and state diff limits only",
+        default_value = "0"
+    )]
+    pub block_max_transactions: u64,
+    #[arg(
+        long = "block.ordering_policy",
+        help = "Order in which the mempool transactions are included in a produced block, deciding which ones make it into a full block: priority-fee or fifo",
+        default_value = "priority-fee"
+    )]
+    pub block_ordering_policy: OrderingPolicy,
+    #[arg(
+        long = "block.max_state_diff_bytes",
+        help = "Maximum size in bytes of the state diff of a produced block, defaults to the size of a blob"
+    )]
+    pub block_max_state_diff_bytes: Option<usize>,
+    #[arg(
+        long = "block.build_deadline",
+        help = "Time in milliseconds after which no more transactions are added to a block being produced, 0 disables the deadline",
+        default_value = "0"
+    )]
+    pub block_build_deadline: u64,
+    #[arg(
+        long = "block.skip_empty",
+        help = "Skip producing and broadcasting the blocks without transactions"
+    )]
+    pub block_skip_empty: bool,
+    #[arg(
+        long = "block.max_idle_interval",
+        help = "Time in seconds after which an empty block is produced anyway with --block.skip_empty, for the chain time to advance, 0 never produces one",
+        default_value = "60"
+    )]
+    pub block_max_idle_interval: u64,
     #[arg(long = "private_key", help = "Private key used for signing blocks")]
     pub private_key: String,
+    #[arg(
+        long = "signature_scheme",
+        help = "Signature scheme of the private key used for signing blocks, either secp256k1 or ed25519",
+        default_value = "secp256k1"
+    )]
+    pub signature_scheme: SignatureScheme,
+    #[arg(
+        long = "signature_digest",
+        help = "Hash applied to the signed messages with the secp256k1 scheme, either sha256 or keccak256 for signatures verifiable by Ethereum tooling",
+        default_value = "sha256",
+        value_parser = parse_digest
+    )]
+    pub signature_digest: DigestKind,
+    #[arg(
+        long = "public_key",
+        help = "Public key the full nodes verify the blocks with, in the form 'scheme:hex', e.g. 'secp256k1:02ab..'. The sequencer fails to start if its signatures don't verify against it",
+        value_parser = AnyVerifyingKey::from_str
+    )]
+    pub public_key: Option<AnyVerifyingKey>,
+    #[arg(
+        long = "mempool.tx_ttl",
+        help = "Time in seconds a transaction may wait in the mempool before it is evicted, 0 disables eviction",
+        help_heading = "Mempool Options",
+        default_value = "10800"
+    )]
+    pub mempool_tx_ttl: u64,
+    #[arg(
+        long = "mempool.min_fee_bump",
+        help = "Minimum increase in percent of the fees of a transaction replacing a pending one with the same sender and nonce",
+        help_heading = "Mempool Options",
+        default_value = "10"
+    )]
+    pub mempool_min_fee_bump: u64,
+    #[arg(
+        long = "coinbase",
+        help = "Address receiving the fees of the produced blocks, defaults to the genesis coinbase",
+        value_parser = parse_address
+    )]
+    pub coinbase: Option<Address>,
+    #[arg(
+        long = "broadcast.mode",
+        help = "Number of full nodes that must accept a block for its broadcast to succeed: first-ok, all or quorum:<number of full nodes>",
+        help_heading = "Full Node Options",
+        default_value = "first-ok"
+    )]
+    pub broadcast_mode: BroadcastMode,
 }
 
 impl std::fmt::Debug for SequencerOpts {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SequencerOptions")
             .field("full_node_addresses", &self.full_node_addresses)
+            .field(
+                "full_node_auth_token",
+                &self.full_node_auth_token.as_ref().map(|_| "<redacted>"),
+            )
             .field("block_time", &self.block_time)
-            .field("private_key", &self.private_key)
+            .field("block_time_jitter", &self.block_time_jitter)
+            .field("block_max_transactions", &self.block_max_transactions)
+            .field("block_ordering_policy", &self.block_ordering_policy)
+            .field(
+                "block_max_state_diff_bytes",
+                &self.block_max_state_diff_bytes,
+            )
+            .field("block_build_deadline", &self.block_build_deadline)
+            .field("block_skip_empty", &self.block_skip_empty)
+            .field("block_max_idle_interval", &self.block_max_idle_interval)
+            .field("private_key", &"<redacted>")
+            .field("signature_scheme", &self.signature_scheme)
+            .field("signature_digest", &self.signature_digest)
+            .field("public_key", &self.public_key)
+            .field("mempool_tx_ttl", &self.mempool_tx_ttl)
+            .field("mempool_min_fee_bump", &self.mempool_min_fee_bump)
+            .field("coinbase", &self.coinbase)
+            .field("broadcast_mode", &self.broadcast_mode)
             .finish()
     }
 }
+
+fn parse_digest(value: &str) -> Result<DigestKind, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "sha256" => Ok(DigestKind::Sha256),
+        "keccak256" => Ok(DigestKind::Keccak256),
+        _ => Err(format!(
+            "unknown digest {value}, expected sha256 or keccak256"
+        )),
+    }
+}
+
+fn parse_address(value: &str) -> Result<Address, String> {
+    let hex = value.strip_prefix("0x").unwrap_or(value);
+    if hex.len() != 40 {
+        return Err(format!(
+            "expected a 20 bytes hex encoded address, got {value}"
+        ));
+    }
+    Address::from_str(hex).map_err(|error| format!("invalid address {value}: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        let base = ["mojave-sequencer", "init", "--private_key", "0x01"];
+        Cli::try_parse_from(base.iter().chain(args))
+    }
+
+    #[test]
+    fn private_key_is_redacted_from_debug() {
+        let Command::Init {
+            sequencer_options, ..
+        } = parse(&["--full_node.auth_token", "secret-token"])
+            .unwrap()
+            .command;
+        let debug = format!("{sequencer_options:?}");
+        assert!(!debug.contains("0x01"), "{debug}");
+        assert!(!debug.contains("secret-token"), "{debug}");
+    }
+
+    #[test]
+    fn coinbase_defaults_to_none() {
+        let Command::Init {
+            sequencer_options, ..
+        } = parse(&[]).unwrap().command;
+        assert_eq!(sequencer_options.coinbase, None);
+    }
+
+    #[test]
+    fn coinbase_override_is_parsed() {
+        let Command::Init {
+            sequencer_options, ..
+        } = parse(&["--coinbase", "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"])
+            .unwrap()
+            .command;
+        assert_eq!(
+            sequencer_options.coinbase,
+            Some(Address::from_str("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap())
+        );
+    }
+
+    #[test]
+    fn signature_scheme_is_parsed() {
+        let Command::Init {
+            sequencer_options, ..
+        } = parse(&[]).unwrap().command;
+        assert_eq!(
+            sequencer_options.signature_scheme,
+            SignatureScheme::Secp256k1
+        );
+
+        let Command::Init {
+            sequencer_options, ..
+        } = parse(&["--signature_scheme", "ed25519"]).unwrap().command;
+        assert_eq!(sequencer_options.signature_scheme, SignatureScheme::Ed25519);
+
+        let Command::Init {
+            sequencer_options, ..
+        } = parse(&["--signature_scheme", "Secp256k1"]).unwrap().command;
+        assert_eq!(
+            sequencer_options.signature_scheme,
+            SignatureScheme::Secp256k1
+        );
+
+        assert!(parse(&["--signature_scheme", "rsa"]).is_err());
+    }
+
+    #[test]
+    fn signature_digest_is_parsed() {
+        let Command::Init {
+            sequencer_options, ..
+        } = parse(&[]).unwrap().command;
+        assert_eq!(sequencer_options.signature_digest, DigestKind::Sha256);
+
+        let Command::Init {
+            sequencer_options, ..
+        } = parse(&["--signature_digest", "keccak256"]).unwrap().command;
+        assert_eq!(sequencer_options.signature_digest, DigestKind::Keccak256);
+
+        assert!(parse(&["--signature_digest", "blake2"]).is_err());
+    }
+
+    #[test]
+    fn rpc_batch_concurrency_must_be_positive() {
+        let Command::Init { options, .. } = parse(&[]).unwrap().command;
+        assert_eq!(options.rpc_batch_concurrency, 8);
+
+        assert!(parse(&["--rpc.batch_concurrency", "0"]).is_err());
+    }
+
+    #[test]
+    fn broadcast_mode_is_parsed() {
+        let Command::Init {
+            sequencer_options, ..
+        } = parse(&[]).unwrap().command;
+        assert_eq!(sequencer_options.broadcast_mode, BroadcastMode::FirstOk);
+
+        let Command::Init {
+            sequencer_options, ..
+        } = parse(&["--broadcast.mode", "quorum:2"]).unwrap().command;
+        assert_eq!(sequencer_options.broadcast_mode, BroadcastMode::Quorum(2));
+
+        assert!(parse(&["--broadcast.mode", "quorum:0"]).is_err());
+    }
+
+    #[test]
+    fn block_ordering_policy_is_parsed() {
+        let Command::Init {
+            sequencer_options, ..
+        } = parse(&[]).unwrap().command;
+        assert_eq!(
+            sequencer_options.block_ordering_policy,
+            OrderingPolicy::PriorityFee
+        );
+
+        let Command::Init {
+            sequencer_options, ..
+        } = parse(&["--block.ordering_policy", "fifo"]).unwrap().command;
+        assert_eq!(
+            sequencer_options.block_ordering_policy,
+            OrderingPolicy::Fifo
+        );
+
+        assert!(parse(&["--block.ordering_policy", "random"]).is_err());
+    }
+
+    #[test]
+    fn invalid_coinbase_is_rejected() {
+        assert!(parse(&["--coinbase", "0x1234"]).is_err());
+        assert!(parse(&["--coinbase", "not an address but forty characters long"]).is_err());
+    }
+}
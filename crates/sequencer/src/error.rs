@@ -17,4 +17,6 @@ pub enum Error {
     Rpc(#[from] RpcErr),
     #[error(transparent)]
     MojaveClient(#[from] mojave_client::MojaveClientError),
+    #[error(transparent)]
+    Signature(#[from] mojave_signature::SignatureError),
 }
@@ -1,4 +1,5 @@
 pub mod block_producer;
 pub mod cli;
 pub mod error;
+pub mod mempool;
 pub mod rpc;
@@ -0,0 +1,491 @@
+use ethrex_blockchain::{Blockchain, error::MempoolError, mempool::PendingTxFilter};
+use ethrex_common::{Address, H256, types::Transaction};
+use ethrex_storage::{Store, error::StoreError};
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info};
+
+/// Bounds of the interval between two scans of the mempool for expired transactions.
+const MIN_EVICTION_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Scans the mempool as often as `tx_ttl` within the bounds, so that a transaction outlives its
+/// TTL by at most the TTL itself.
+fn eviction_interval(tx_ttl: Duration) -> Duration {
+    tx_ttl.clamp(MIN_EVICTION_INTERVAL, MAX_EVICTION_INTERVAL)
+}
+
+/// Periodically removes transactions that have been waiting in the mempool for longer than
+/// `tx_ttl`, e.g. transactions whose fee is too low to ever be included.
+pub fn spawn_mempool_eviction_task(
+    blockchain: Arc<Blockchain>,
+    tx_ttl: Duration,
+    shutdown_token: CancellationToken,
+) -> JoinHandle<()> {
+    tokio::task::spawn(async move {
+        let mut interval = tokio::time::interval(eviction_interval(tx_ttl));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    match evict_expired_transactions(&blockchain, tx_ttl, SystemTime::now()) {
+                        Ok(0) => debug!("No expired transactions in the mempool"),
+                        Ok(evicted) => info!(evicted, ttl_secs = tx_ttl.as_secs(), "Evicted expired transactions from the mempool"),
+                        Err(error) => error!(%error, "Failed to evict expired transactions from the mempool"),
+                    }
+                }
+                _ = shutdown_token.cancelled() => {
+                    info!("Shutting down mempool eviction task");
+                    break;
+                }
+            }
+        }
+    })
+}
+
+/// Removes every transaction that entered the mempool more than `tx_ttl` before `now` and
+/// returns how many were evicted.
+pub fn evict_expired_transactions(
+    blockchain: &Blockchain,
+    tx_ttl: Duration,
+    now: SystemTime,
+) -> Result<usize, StoreError> {
+    let now = now
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros();
+    let pending = blockchain
+        .mempool
+        .filter_transactions(&PendingTxFilter::default())?;
+    let expired = expired_transactions(
+        pending
+            .values()
+            .flatten()
+            .map(|tx| (tx.compute_hash(), tx.time())),
+        now,
+        tx_ttl,
+    );
+
+    for tx_hash in &expired {
+        debug!("Evicting expired transaction from the mempool: {tx_hash:#x}");
+        blockchain.remove_transaction_from_pool(tx_hash)?;
+    }
+    Ok(expired.len())
+}
+
+/// Returns the hashes of the transactions whose insertion time (in microseconds since the
+/// UNIX epoch, as tracked by the mempool) is older than `tx_ttl` relative to `now`.
+fn expired_transactions(
+    transactions: impl IntoIterator<Item = (H256, u128)>,
+    now: u128,
+    tx_ttl: Duration,
+) -> Vec<H256> {
+    let ttl = tx_ttl.as_micros();
+    transactions
+        .into_iter()
+        .filter(|(_, inserted_at)| now.saturating_sub(*inserted_at) > ttl)
+        .map(|(tx_hash, _)| tx_hash)
+        .collect()
+}
+
+/// Removes every pending transaction from the mempool, or only the ones of `sender` when given,
+/// and returns how many were removed.
+pub fn flush_mempool(
+    blockchain: &Blockchain,
+    sender: Option<Address>,
+) -> Result<usize, StoreError> {
+    let pending = blockchain
+        .mempool
+        .filter_transactions(&PendingTxFilter::default())?;
+    let flushed: Vec<H256> = pending
+        .iter()
+        .filter(|(tx_sender, _)| sender.is_none_or(|sender| **tx_sender == sender))
+        .flat_map(|(_, txs)| txs.iter().map(|tx| tx.compute_hash()))
+        .collect();
+
+    for tx_hash in &flushed {
+        blockchain.remove_transaction_from_pool(tx_hash)?;
+    }
+    Ok(flushed.len())
+}
+
+/// Returns the nonce of the next transaction of `sender` once its transactions pending in the
+/// mempool are included, i.e. the `pending` transaction count. Like geth, only the pending
+/// nonces following on from the latest one without a gap are counted, the transactions after a
+/// gap not being includable until it is filled.
+pub async fn pending_transaction_count(
+    blockchain: &Blockchain,
+    store: &Store,
+    sender: Address,
+) -> Result<u64, StoreError> {
+    let latest_block_number = store.get_latest_block_number().await?;
+    let latest_nonce = store
+        .get_nonce_by_account_address(latest_block_number, sender)
+        .await?
+        .unwrap_or_default();
+    let mut pending_nonces: Vec<u64> = blockchain
+        .mempool
+        .filter_transactions(&PendingTxFilter::default())?
+        .get(&sender)
+        .map(|txs| txs.iter().map(|tx| tx.nonce()).collect())
+        .unwrap_or_default();
+    pending_nonces.sort_unstable();
+    Ok(next_contiguous_nonce(latest_nonce, &pending_nonces))
+}
+
+/// Returns the nonce following the run of `sorted_nonces` starting at `latest_nonce`.
+fn next_contiguous_nonce(latest_nonce: u64, sorted_nonces: &[u64]) -> u64 {
+    let mut next_nonce = latest_nonce;
+    for &nonce in sorted_nonces {
+        if nonce == next_nonce {
+            next_nonce += 1;
+        } else if nonce > next_nonce {
+            break;
+        }
+    }
+    next_nonce
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReplaceTransactionError {
+    #[error("No pending transaction from {sender:#x} with nonce {nonce} to replace")]
+    NotFound { sender: Address, nonce: u64 },
+    #[error(
+        "Replacement transaction underpriced, its max fee per gas and max priority fee per gas must be at least {min_max_fee_per_gas} and {min_max_priority_fee_per_gas}"
+    )]
+    Underpriced {
+        min_max_fee_per_gas: u64,
+        min_max_priority_fee_per_gas: u64,
+    },
+    #[error(transparent)]
+    Store(#[from] StoreError),
+    #[error(transparent)]
+    Mempool(#[from] MempoolError),
+}
+
+/// Replaces the pending transaction of `sender` with the nonce of `replacement` and returns the
+/// hash of the replacement.
+///
+/// Both the max fee per gas and the max priority fee per gas of the replacement must exceed the
+/// ones of the replaced transaction by at least `min_fee_bump` percent. The replaced transaction
+/// is put back in the mempool if the replacement fails its validation.
+pub async fn replace_transaction(
+    blockchain: &Blockchain,
+    replacement: Transaction,
+    sender: Address,
+    min_fee_bump: u64,
+) -> Result<H256, ReplaceTransactionError> {
+    let nonce = replacement.nonce();
+    let replaced = blockchain
+        .mempool
+        .filter_transactions(&PendingTxFilter::default())?
+        .remove(&sender)
+        .and_then(|txs| txs.into_iter().find(|tx| tx.nonce() == nonce))
+        .ok_or(ReplaceTransactionError::NotFound { sender, nonce })?;
+
+    let (max_fee_per_gas, max_priority_fee_per_gas) = fee_caps(&replaced);
+    let min_max_fee_per_gas = bumped_fee(max_fee_per_gas, min_fee_bump);
+    let min_max_priority_fee_per_gas = bumped_fee(max_priority_fee_per_gas, min_fee_bump);
+    let (new_max_fee_per_gas, new_max_priority_fee_per_gas) = fee_caps(&replacement);
+    if new_max_fee_per_gas < min_max_fee_per_gas
+        || new_max_priority_fee_per_gas < min_max_priority_fee_per_gas
+    {
+        return Err(ReplaceTransactionError::Underpriced {
+            min_max_fee_per_gas,
+            min_max_priority_fee_per_gas,
+        });
+    }
+
+    let replaced_hash = replaced.compute_hash();
+    blockchain.remove_transaction_from_pool(&replaced_hash)?;
+    match blockchain.add_transaction_to_pool(replacement).await {
+        Ok(tx_hash) => {
+            debug!("Replaced transaction {replaced_hash:#x} with {tx_hash:#x}");
+            Ok(tx_hash)
+        }
+        Err(error) => {
+            blockchain
+                .mempool
+                .add_transaction(replaced_hash, replaced)?;
+            Err(error.into())
+        }
+    }
+}
+
+/// Returns the max fee per gas and the max priority fee per gas the transaction is willing to
+/// pay, both being the gas price for transactions predating EIP-1559.
+fn fee_caps(tx: &Transaction) -> (u64, u64) {
+    (
+        tx.max_fee_per_gas().unwrap_or(tx.gas_price()),
+        tx.max_priority_fee().unwrap_or(tx.gas_price()),
+    )
+}
+
+/// Returns the fee increased by `bump` percent, rounded up so that a bump is always required.
+fn bumped_fee(fee: u64, bump: u64) -> u64 {
+    let bumped = (u128::from(fee) * u128::from(100 + bump)).div_ceil(100);
+    u64::try_from(bumped).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethrex_common::{
+        Address, Bytes, U256,
+        types::{
+            ChainConfig, EIP1559Transaction, Genesis, GenesisAccount, MempoolTransaction, Signable,
+            Transaction, TxKind,
+        },
+    };
+    use ethrex_storage::{EngineType, Store};
+    use secp256k1::SecretKey;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn expired_transactions_only_returns_transactions_older_than_ttl() {
+        let ttl = Duration::from_secs(60);
+        let now = Duration::from_secs(1_000).as_micros();
+        let fresh = H256::from_low_u64_be(1);
+        let expired = H256::from_low_u64_be(2);
+
+        let result = expired_transactions(
+            [
+                (fresh, now - Duration::from_secs(30).as_micros()),
+                (expired, now - Duration::from_secs(61).as_micros()),
+            ],
+            now,
+            ttl,
+        );
+
+        assert_eq!(result, vec![expired]);
+    }
+
+    #[test]
+    fn eviction_interval_follows_short_ttls() {
+        assert_eq!(
+            eviction_interval(Duration::from_secs(5)),
+            Duration::from_secs(5)
+        );
+        assert_eq!(
+            eviction_interval(Duration::from_secs(3600)),
+            MAX_EVICTION_INTERVAL
+        );
+        assert_eq!(eviction_interval(Duration::ZERO), MIN_EVICTION_INTERVAL);
+    }
+
+    #[tokio::test]
+    async fn mempool_is_flushed_by_sender_then_entirely() {
+        let store = Store::new("", EngineType::InMemory).unwrap();
+        let blockchain = Blockchain::default_with_store(store);
+        let (alice, bob) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        for (sender, nonces) in [(alice, 0..2), (bob, 0..1)] {
+            for nonce in nonces {
+                let tx = Transaction::EIP1559Transaction(EIP1559Transaction {
+                    nonce,
+                    gas_limit: 21_000,
+                    max_fee_per_gas: 1,
+                    to: TxKind::Call(sender),
+                    ..Default::default()
+                });
+                blockchain
+                    .mempool
+                    .add_transaction(tx.compute_hash(), MempoolTransaction::new(tx, sender))
+                    .unwrap();
+            }
+        }
+
+        assert_eq!(flush_mempool(&blockchain, Some(alice)).unwrap(), 2);
+        let pending = blockchain
+            .mempool
+            .filter_transactions(&PendingTxFilter::default())
+            .unwrap();
+        assert!(!pending.contains_key(&alice));
+        assert_eq!(pending[&bob].len(), 1);
+
+        assert_eq!(flush_mempool(&blockchain, None).unwrap(), 1);
+        assert!(
+            blockchain
+                .mempool
+                .filter_transactions(&PendingTxFilter::default())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn transaction_is_evicted_after_ttl() {
+        let store = Store::new("", EngineType::InMemory).unwrap();
+        let blockchain = Blockchain::default_with_store(store);
+
+        let tx = Transaction::EIP1559Transaction(EIP1559Transaction {
+            nonce: 0,
+            gas_limit: 21_000,
+            max_fee_per_gas: 1,
+            ..Default::default()
+        });
+        let tx_hash = tx.compute_hash();
+        blockchain
+            .mempool
+            .add_transaction(tx_hash, MempoolTransaction::new(tx, Address::zero()))
+            .unwrap();
+
+        let ttl = Duration::from_secs(60);
+        let evicted = evict_expired_transactions(&blockchain, ttl, SystemTime::now()).unwrap();
+        assert_eq!(evicted, 0);
+
+        let later = SystemTime::now() + ttl + Duration::from_secs(1);
+        let evicted = evict_expired_transactions(&blockchain, ttl, later).unwrap();
+        assert_eq!(evicted, 1);
+        assert!(
+            blockchain
+                .mempool
+                .filter_transactions(&PendingTxFilter::default())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    fn signed_transfer(
+        key: &SecretKey,
+        max_fee_per_gas: u64,
+        max_priority_fee_per_gas: u64,
+    ) -> Transaction {
+        let mut tx = Transaction::EIP1559Transaction(EIP1559Transaction {
+            chain_id: 1,
+            nonce: 0,
+            gas_limit: 21_000,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            to: TxKind::Call(Address::from_low_u64_be(1)),
+            value: U256::one(),
+            ..Default::default()
+        });
+        tx.sign_inplace(key);
+        tx
+    }
+
+    async fn blockchain_funding(sender: Address) -> (Store, Blockchain) {
+        let genesis = Genesis {
+            config: ChainConfig {
+                chain_id: 1,
+                london_block: Some(0),
+                ..Default::default()
+            },
+            alloc: BTreeMap::from([(
+                sender,
+                GenesisAccount {
+                    code: Bytes::new(),
+                    storage: Default::default(),
+                    balance: U256::from(10).pow(U256::from(20)),
+                    nonce: 0,
+                },
+            )]),
+            coinbase: Address::zero(),
+            difficulty: U256::zero(),
+            extra_data: Bytes::new(),
+            gas_limit: 30_000_000,
+            nonce: 0,
+            mix_hash: H256::zero(),
+            timestamp: 0,
+            base_fee_per_gas: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            requests_hash: None,
+        };
+        let store = Store::new("", EngineType::InMemory).unwrap();
+        store.add_initial_state(genesis).await.unwrap();
+        (store.clone(), Blockchain::default_with_store(store))
+    }
+
+    #[tokio::test]
+    async fn replacement_supersedes_the_pending_transaction() {
+        let key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let original = signed_transfer(&key, 2_000_000_000, 1_000_000_000);
+        let sender = original.sender().unwrap();
+        let (_, blockchain) = blockchain_funding(sender).await;
+        let original_hash = blockchain.add_transaction_to_pool(original).await.unwrap();
+
+        // 5% is below the 10% minimum bump.
+        let underpriced = signed_transfer(&key, 2_100_000_000, 1_050_000_000);
+        let result = replace_transaction(&blockchain, underpriced, sender, 10).await;
+        assert!(matches!(
+            result,
+            Err(ReplaceTransactionError::Underpriced { .. })
+        ));
+
+        let replacement = signed_transfer(&key, 2_200_000_000, 1_100_000_000);
+        let replacement_hash = replace_transaction(&blockchain, replacement, sender, 10)
+            .await
+            .unwrap();
+
+        assert_ne!(replacement_hash, original_hash);
+        let pending = blockchain
+            .mempool
+            .filter_transactions(&PendingTxFilter::default())
+            .unwrap();
+        let pending_hashes: Vec<_> = pending[&sender]
+            .iter()
+            .map(|tx| tx.compute_hash())
+            .collect();
+        assert_eq!(pending_hashes, vec![replacement_hash]);
+    }
+
+    #[tokio::test]
+    async fn pending_transaction_count_stops_at_a_nonce_gap() {
+        let sender = Address::from_low_u64_be(1);
+        let (store, blockchain) = blockchain_funding(sender).await;
+        for nonce in [0, 1, 5] {
+            let tx = Transaction::EIP1559Transaction(EIP1559Transaction {
+                nonce,
+                gas_limit: 21_000,
+                max_fee_per_gas: 1,
+                to: TxKind::Call(sender),
+                ..Default::default()
+            });
+            blockchain
+                .mempool
+                .add_transaction(tx.compute_hash(), MempoolTransaction::new(tx, sender))
+                .unwrap();
+        }
+
+        assert_eq!(
+            pending_transaction_count(&blockchain, &store, sender)
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(next_contiguous_nonce(3, &[0, 1, 3, 4, 6]), 5);
+        assert_eq!(next_contiguous_nonce(3, &[5]), 3);
+    }
+
+    #[tokio::test]
+    async fn pending_transaction_count_includes_the_mempool() {
+        let key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let tx = signed_transfer(&key, 2_000_000_000, 1_000_000_000);
+        let sender = tx.sender().unwrap();
+        let (store, blockchain) = blockchain_funding(sender).await;
+        let latest = store
+            .get_nonce_by_account_address(0, sender)
+            .await
+            .unwrap()
+            .unwrap_or_default();
+        assert_eq!(
+            pending_transaction_count(&blockchain, &store, sender)
+                .await
+                .unwrap(),
+            latest
+        );
+
+        blockchain.add_transaction_to_pool(tx).await.unwrap();
+
+        assert_eq!(
+            pending_transaction_count(&blockchain, &store, sender)
+                .await
+                .unwrap(),
+            latest + 1
+        );
+    }
+}
@@ -0,0 +1,18 @@
+use crate::rpc::RpcApiContext;
+use ethrex_rpc::{RpcErr, utils::RpcRequest};
+use serde_json::Value;
+
+/// Returns the number of blocks each full node accepted and refused along with the time of its
+/// last accepted one, for the operators to check the health of the broadcasts. As an admin
+/// method, it is only served when enabled in the RPC config.
+pub struct GetBroadcastStatsRequest;
+
+impl GetBroadcastStatsRequest {
+    pub fn call(request: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
+        if !context.rpc_config.admin_enabled {
+            return Err(RpcErr::MethodNotFound(request.method.clone()));
+        }
+        serde_json::to_value(context.mojave_client.broadcast_stats())
+            .map_err(|error| RpcErr::Internal(error.to_string()))
+    }
+}
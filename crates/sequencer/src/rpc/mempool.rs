@@ -0,0 +1,36 @@
+use crate::{mempool::flush_mempool, rpc::RpcApiContext};
+use ethrex_common::Address;
+use ethrex_rpc::{RpcErr, utils::RpcRequest};
+use serde_json::Value;
+
+/// Removes the pending transactions of the mempool, e.g. during an incident. As an admin method,
+/// it is only served when enabled in the RPC config.
+pub struct FlushMempoolRequest {
+    sender: Option<Address>,
+}
+
+impl FlushMempoolRequest {
+    fn get_sender(rpc_req_params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        let sender = match rpc_req_params.as_deref() {
+            None | Some([]) => None,
+            Some([sender]) => Some(serde_json::from_value::<Address>(sender.clone())?),
+            Some(params) => {
+                return Err(RpcErr::BadParams(format!(
+                    "Expected at most one param and {} were provided",
+                    params.len()
+                )));
+            }
+        };
+        Ok(Self { sender })
+    }
+
+    pub async fn call(request: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
+        if !context.rpc_config.admin_enabled {
+            return Err(RpcErr::MethodNotFound(request.method.clone()));
+        }
+        let Self { sender } = Self::get_sender(&request.params)?;
+        let flushed = flush_mempool(&context.l1_context.blockchain, sender)?;
+        tracing::warn!(flushed, ?sender, "Flushed the mempool");
+        Ok(Value::from(flushed))
+    }
+}
@@ -1,27 +1,49 @@
-use axum::{Json, Router, extract::State, http::StatusCode, routing::post};
+pub mod broadcast;
+pub mod mempool;
+pub mod transaction;
+
+use crate::rpc::{
+    broadcast::GetBroadcastStatsRequest,
+    mempool::FlushMempoolRequest,
+    transaction::{PendingTransactionCountRequest, ResubmitTransactionRequest},
+};
+use axum::{
+    Json, Router,
+    extract::{ConnectInfo, State},
+    http::StatusCode,
+    middleware::from_fn_with_state,
+    routing::post,
+};
 use ethrex_blockchain::Blockchain;
 use ethrex_common::Bytes;
 use ethrex_p2p::{
+    kademlia::KademliaTable,
     peer_handler::PeerHandler,
     sync_manager::SyncManager,
     types::{Node, NodeRecord},
 };
 use ethrex_rpc::{
-    GasTipEstimator, NodeData, RpcApiContext as L1Context, RpcErr, RpcRequestWrapper,
-    map_eth_requests, rpc_response,
+    ActiveFilters, GasTipEstimator, NodeData, RpcApiContext as L1Context, RpcErr,
     utils::{RpcRequest, RpcRequestId},
 };
 use ethrex_storage::Store;
 use ethrex_storage_rollup::StoreRollup;
+use mojave_chain_utils::rpc::{
+    FilterQuotas, LogQueryLimiter, RequestLogger, RpcConfig, RpcError, RpcRequestBody,
+    authrpc_router, batch_response, get_chain_config, get_peers, is_filter_creation, is_log_query,
+    log_requests, map_net_requests, new_filter, process_batch, reject_oversized_requests,
+    request_size_limit, rpc_response, validate_get_proof,
+};
+use mojave_client::MojaveClient;
 use serde_json::Value;
 use std::{
     collections::HashMap,
-    net::SocketAddr,
+    net::{IpAddr, SocketAddr},
     sync::{Arc, Mutex},
     time::Duration,
 };
-use tokio::{net::TcpListener, sync::Mutex as TokioMutex};
-use tower_http::cors::CorsLayer;
+use tokio::{net::TcpListener, sync::Mutex as TokioMutex, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
 use tracing::info;
 
 pub const FILTER_DURATION: Duration = {
@@ -35,7 +57,14 @@ pub const FILTER_DURATION: Duration = {
 #[derive(Clone, Debug)]
 pub struct RpcApiContext {
     pub l1_context: L1Context,
+    pub peer_table: Arc<TokioMutex<KademliaTable>>,
     pub rollup_store: StoreRollup,
+    /// Minimum fee increase in percent of a replacement transaction.
+    pub min_fee_bump: u64,
+    pub rpc_config: RpcConfig,
+    pub filter_quotas: FilterQuotas,
+    pub log_queries: LogQueryLimiter,
+    pub mojave_client: MojaveClient,
 }
 
 #[expect(clippy::too_many_arguments)]
@@ -49,8 +78,13 @@ pub async fn start_api(
     local_node_record: NodeRecord,
     syncer: SyncManager,
     peer_handler: PeerHandler,
+    peer_table: Arc<TokioMutex<KademliaTable>>,
     client_version: String,
     rollup_store: StoreRollup,
+    mojave_client: MojaveClient,
+    min_fee_bump: u64,
+    rpc_config: RpcConfig,
+    shutdown_token: CancellationToken,
 ) -> Result<(), RpcErr> {
     let active_filters = Arc::new(Mutex::new(HashMap::new()));
     let context = RpcApiContext {
@@ -68,92 +102,200 @@ pub async fn start_api(
             },
             gas_tip_estimator: Arc::new(TokioMutex::new(GasTipEstimator::new())),
         },
+        peer_table,
         rollup_store,
-        // mojave_client,
+        min_fee_bump,
+        log_queries: LogQueryLimiter::new(&rpc_config),
+        rpc_config,
+        filter_quotas: FilterQuotas::default(),
+        mojave_client,
     };
 
     // Periodically clean up the active filters for the filters endpoints.
-    tokio::task::spawn(async move {
-        let mut interval = tokio::time::interval(FILTER_DURATION);
-        let filters = active_filters.clone();
-        loop {
-            interval.tick().await;
-            tracing::info!("Running filter clean task");
-            ethrex_rpc::clean_outdated_filters(filters.clone(), FILTER_DURATION);
-            tracing::info!("Filter clean task complete");
-        }
-    });
-
-    // All request headers allowed.
-    // All methods allowed.
-    // All origins allowed.
-    // All headers exposed.
-    let cors = CorsLayer::permissive();
+    let filter_handle = spawn_filter_cleanup_task(active_filters.clone(), shutdown_token.clone());
 
     let http_router = Router::new()
         .route("/", post(handle_http_request))
-        .layer(cors)
+        .layer(from_fn_with_state(
+            RequestLogger::new(context.rpc_config.logging.clone()),
+            log_requests,
+        ))
+        .layer(request_size_limit(&context.rpc_config))
+        .layer(from_fn_with_state(
+            context.rpc_config.max_request_bytes,
+            reject_oversized_requests,
+        ))
+        .layer(context.rpc_config.cors.layer())
         .with_state(context.clone());
     let http_listener = TcpListener::bind(http_addr)
         .await
         .map_err(|error| RpcErr::Internal(error.to_string()))?;
-    let http_server = axum::serve(http_listener, http_router)
-        .with_graceful_shutdown(ethrex_rpc::shutdown_signal())
-        .into_future();
+    let http_server = axum::serve(
+        http_listener,
+        http_router.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_token.clone().cancelled_owned())
+    .into_future();
     info!("Starting HTTP server at {http_addr}");
 
-    info!("Not starting Auth-RPC server. The address passed as argument is {authrpc_addr}");
+    let authrpc_listener = TcpListener::bind(authrpc_addr)
+        .await
+        .map_err(|error| RpcErr::Internal(error.to_string()))?;
+    let authrpc_server = axum::serve(
+        authrpc_listener,
+        authrpc_router(context.l1_context.clone(), &context.rpc_config),
+    )
+    .with_graceful_shutdown(shutdown_token.clone().cancelled_owned())
+    .into_future();
+    info!("Starting Auth-RPC server at {authrpc_addr}");
 
-    let _ =
-        tokio::try_join!(http_server).inspect_err(|e| info!("Error shutting down servers: {e:?}"));
+    let _ = tokio::try_join!(
+        async {
+            http_server
+                .await
+                .map_err(|e| RpcErr::Internal(e.to_string()))
+        },
+        async {
+            authrpc_server
+                .await
+                .map_err(|e| RpcErr::Internal(e.to_string()))
+        },
+        async {
+            filter_handle
+                .await
+                .map_err(|e| RpcErr::Internal(e.to_string()))
+        },
+    )
+    .inspect_err(|e| info!("Error shutting down servers: {e:?}"));
     Ok(())
 }
 
+fn spawn_filter_cleanup_task(
+    active_filters: ActiveFilters,
+    shutdown_token: CancellationToken,
+) -> JoinHandle<()> {
+    tokio::task::spawn(async move {
+        let mut interval = tokio::time::interval(FILTER_DURATION);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    tracing::info!("Running filter clean task");
+                    ethrex_rpc::clean_outdated_filters(active_filters.clone(), FILTER_DURATION);
+                    tracing::info!("Filter clean task complete");
+                }
+                _ = shutdown_token.cancelled() => {
+                    tracing::info!("Shutting down filter clean task");
+                    break;
+                }
+            }
+        }
+    })
+}
+
 async fn handle_http_request(
     State(service_context): State<RpcApiContext>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
     body: String,
 ) -> Result<Json<Value>, StatusCode> {
-    let res = match serde_json::from_str::<RpcRequestWrapper>(&body) {
-        Ok(RpcRequestWrapper::Single(request)) => {
-            let res = map_http_requests(&request, service_context).await;
+    let client = client.ip();
+    let res = match RpcRequestBody::parse(&body, service_context.rpc_config.max_batch_size) {
+        Ok(RpcRequestBody::Single(request)) => {
+            let res = map_http_requests(&request, service_context, client).await;
             rpc_response(request.id, res).map_err(|_| StatusCode::BAD_REQUEST)?
         }
-        Ok(RpcRequestWrapper::Multiple(requests)) => {
-            let mut responses = Vec::new();
-            for req in requests {
-                let res = map_http_requests(&req, service_context.clone()).await;
-                responses.push(rpc_response(req.id, res).map_err(|_| StatusCode::BAD_REQUEST)?);
-            }
-            serde_json::to_value(responses).map_err(|_| StatusCode::BAD_REQUEST)?
+        Ok(RpcRequestBody::Batch(entries)) => {
+            let requests: Vec<&RpcRequest> = entries
+                .iter()
+                .filter_map(|entry| entry.as_ref().ok())
+                .collect();
+            let results = process_batch(
+                &requests,
+                service_context.rpc_config.batch_concurrency,
+                is_state_mutating,
+                |req| map_http_requests(req, service_context.clone(), client),
+            )
+            .await;
+            batch_response(entries, results).map_err(|_| StatusCode::BAD_REQUEST)?
         }
-        Err(_) => rpc_response(
-            RpcRequestId::String("".to_string()),
-            Err(RpcErr::BadParams("Invalid request body".to_string())),
-        )
-        .map_err(|_| StatusCode::BAD_REQUEST)?,
+        Err(error) => rpc_response(RpcRequestId::String("".to_string()), Err(error))
+            .map_err(|_| StatusCode::BAD_REQUEST)?,
     };
     Ok(Json(res))
 }
 
-async fn map_http_requests(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
+/// Returns whether the method changes the state, in which case it must not run concurrently with
+/// other requests of its batch, e.g. to keep the nonces of the sent transactions in order.
+fn is_state_mutating(method: &str) -> bool {
+    matches!(
+        method,
+        "eth_sendRawTransaction" | "mojave_sendForwardTransaction" | "mojave_resubmitTransaction"
+    )
+}
+
+async fn map_http_requests(
+    req: &RpcRequest,
+    context: RpcApiContext,
+    client: IpAddr,
+) -> Result<Value, RpcError> {
     match RpcNamespace::resolve_namespace(req) {
-        Ok(RpcNamespace::Eth) => map_eth_requests(req, context.l1_context).await,
-        Ok(RpcNamespace::Mojave) => map_mojave_requests(req, context).await,
-        Err(err) => Err(err),
+        Ok(RpcNamespace::Eth) => map_eth_requests(req, context, client).await,
+        Ok(RpcNamespace::Mojave) => Ok(map_mojave_requests(req, context).await?),
+        Ok(RpcNamespace::Net) => Ok(map_net_requests(req, &context.l1_context.storage)?),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn map_eth_requests(
+    req: &RpcRequest,
+    context: RpcApiContext,
+    client: IpAddr,
+) -> Result<Value, RpcError> {
+    match req.method.as_str() {
+        "eth_getProof" => {
+            validate_get_proof(req, context.rpc_config.max_proof_storage_keys)?;
+            Ok(ethrex_rpc::map_eth_requests(req, context.l1_context).await?)
+        }
+        "eth_getTransactionCount" if PendingTransactionCountRequest::is_pending(req) => {
+            Ok(PendingTransactionCountRequest::call(req, context).await?)
+        }
+        method if is_filter_creation(method) => {
+            new_filter(
+                req,
+                context.l1_context,
+                client,
+                &context.filter_quotas,
+                &context.rpc_config,
+            )
+            .await
+        }
+        method if is_log_query(method) => {
+            context
+                .log_queries
+                .run(ethrex_rpc::map_eth_requests(req, context.l1_context))
+                .await
+        }
+        _others => Ok(ethrex_rpc::map_eth_requests(req, context.l1_context).await?),
     }
 }
 
-/// Leave this unimplemented for now.
 pub async fn map_mojave_requests(
-    _req: &RpcRequest,
-    _context: RpcApiContext,
+    req: &RpcRequest,
+    context: RpcApiContext,
 ) -> Result<Value, RpcErr> {
-    Err(RpcErr::Internal("Unimplemented".to_owned()))
+    match req.method.as_str() {
+        "mojave_getPeers" => get_peers(&context.peer_table, &context.rpc_config).await,
+        "mojave_getChainConfig" => get_chain_config(&context.l1_context.storage),
+        "mojave_resubmitTransaction" => ResubmitTransactionRequest::call(req, context).await,
+        "mojave_flushMempool" => FlushMempoolRequest::call(req, context).await,
+        "mojave_getBroadcastStats" => GetBroadcastStatsRequest::call(req, context),
+        others => Err(RpcErr::MethodNotFound(others.to_owned())),
+    }
 }
 
 pub enum RpcNamespace {
     Eth,
     Mojave,
+    Net,
 }
 
 impl RpcNamespace {
@@ -165,7 +307,81 @@ impl RpcNamespace {
         match namespace {
             "eth" => Ok(Self::Eth),
             "mojave" => Ok(Self::Mojave),
+            "net" => Ok(Self::Net),
             _others => Err(RpcErr::MethodNotFound(request.method.to_owned())),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    fn request(index: usize, method: &str) -> RpcRequest {
+        RpcRequest {
+            id: RpcRequestId::Number(index as u64),
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params: Some(vec![json!(index)]),
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_runs_reads_concurrently_and_writes_alone_in_order() {
+        let requests = vec![
+            request(0, "eth_call"),
+            request(1, "eth_getBalance"),
+            request(2, "eth_call"),
+            request(3, "eth_sendRawTransaction"),
+            request(4, "eth_sendRawTransaction"),
+            request(5, "eth_call"),
+            request(6, "eth_blockNumber"),
+        ];
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+        let write_overlapped = AtomicBool::new(false);
+
+        let requests: Vec<&RpcRequest> = requests.iter().collect();
+        let results = process_batch(&requests, 2, is_state_mutating, |req| {
+            let (in_flight, max_in_flight, write_overlapped) =
+                (&in_flight, &max_in_flight, &write_overlapped);
+            async move {
+                let running = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(running, Ordering::SeqCst);
+                // Later requests complete first, so the order of the results can only come from
+                // the order of the requests.
+                let index = req.params.as_ref().unwrap()[0].as_u64().unwrap();
+                tokio::time::sleep(Duration::from_millis(50 - index * 5)).await;
+                if is_state_mutating(&req.method) && in_flight.load(Ordering::SeqCst) != 1 {
+                    write_overlapped.store(true, Ordering::SeqCst);
+                }
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(json!(index))
+            }
+        })
+        .await;
+
+        let results: Vec<_> = results.into_iter().map(Result::unwrap).collect();
+        assert_eq!(
+            results,
+            (0..7).map(|index| json!(index)).collect::<Vec<_>>()
+        );
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+        assert!(!write_overlapped.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn filter_cleanup_task_stops_on_shutdown() {
+        let shutdown_token = CancellationToken::new();
+        let handle =
+            spawn_filter_cleanup_task(Arc::new(Mutex::new(HashMap::new())), shutdown_token.clone());
+
+        shutdown_token.cancel();
+        tokio::time::timeout(Duration::from_secs(1), handle)
+            .await
+            .expect("the task stops once cancelled")
+            .unwrap();
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{
+    mempool::{ReplaceTransactionError, pending_transaction_count, replace_transaction},
+    rpc::RpcApiContext,
+};
+use ethrex_common::{Address, types::Transaction};
+use ethrex_rpc::{RpcErr, utils::RpcRequest};
+use serde_json::Value;
+
+/// Replaces a pending transaction by one with the same sender and nonce paying higher fees.
+pub struct ResubmitTransactionRequest(Transaction);
+
+impl ResubmitTransactionRequest {
+    fn get_transaction(rpc_req_params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        let params = rpc_req_params
+            .as_ref()
+            .ok_or(RpcErr::BadParams("No params provided".to_owned()))?;
+        if params.len() != 1 {
+            return Err(RpcErr::BadParams(format!(
+                "Expected one param and {} were provided",
+                params.len()
+            )));
+        };
+
+        let str_data = serde_json::from_value::<String>(params[0].clone())?;
+        let str_data = str_data
+            .strip_prefix("0x")
+            .ok_or(RpcErr::BadParams("Params are not 0x prefixed".to_owned()))?;
+        let transaction_vec =
+            hex::decode(str_data).map_err(|error| RpcErr::BadParams(error.to_string()))?;
+        let transaction = Transaction::decode_canonical(&transaction_vec)
+            .map_err(|error| RpcErr::BadParams(error.to_string()))?;
+        if matches!(transaction, Transaction::EIP4844Transaction(_)) {
+            return Err(RpcErr::BadParams(
+                "Blob transactions cannot be resubmitted".to_owned(),
+            ));
+        }
+        Ok(Self(transaction))
+    }
+
+    pub async fn call(request: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
+        let Self(transaction) = Self::get_transaction(&request.params)?;
+        let sender = transaction
+            .sender()
+            .map_err(|error| RpcErr::BadParams(format!("Invalid signature: {error}")))?;
+        let tx_hash = replace_transaction(
+            &context.l1_context.blockchain,
+            transaction,
+            sender,
+            context.min_fee_bump,
+        )
+        .await
+        .map_err(|error| match error {
+            ReplaceTransactionError::NotFound { .. }
+            | ReplaceTransactionError::Underpriced { .. }
+            | ReplaceTransactionError::Mempool(_) => RpcErr::BadParams(error.to_string()),
+            ReplaceTransactionError::Store(_) => RpcErr::Internal(error.to_string()),
+        })?;
+        serde_json::to_value(tx_hash).map_err(|error| RpcErr::Internal(error.to_string()))
+    }
+}
+
+/// `eth_getTransactionCount` for the `pending` block, which also counts the transactions of the
+/// sender waiting in the mempool so that the next nonce does not collide with them.
+pub struct PendingTransactionCountRequest {
+    address: Address,
+}
+
+impl PendingTransactionCountRequest {
+    /// Returns whether the request targets the `pending` block.
+    pub fn is_pending(request: &RpcRequest) -> bool {
+        request
+            .params
+            .as_ref()
+            .and_then(|params| params.get(1))
+            .and_then(Value::as_str)
+            == Some("pending")
+    }
+
+    fn get_address(rpc_req_params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        let params = rpc_req_params
+            .as_ref()
+            .ok_or(RpcErr::BadParams("No params provided".to_owned()))?;
+        if params.len() != 2 {
+            return Err(RpcErr::BadParams(format!(
+                "Expected two params and {} were provided",
+                params.len()
+            )));
+        };
+
+        let address = serde_json::from_value::<Address>(params[0].clone())?;
+        Ok(Self { address })
+    }
+
+    pub async fn call(request: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
+        let Self { address } = Self::get_address(&request.params)?;
+        let transaction_count = pending_transaction_count(
+            &context.l1_context.blockchain,
+            &context.l1_context.storage,
+            address,
+        )
+        .await?;
+        serde_json::to_value(format!("{transaction_count:#x}"))
+            .map_err(|error| RpcErr::Internal(error.to_string()))
+    }
+}
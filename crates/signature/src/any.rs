@@ -0,0 +1,459 @@
+use crate::{DigestKind, Signature, SignatureError, SignatureScheme, Signer, Verifier};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Message signed by [`AnySigningKey::self_test`].
+const SELF_TEST_MESSAGE: &[u8] = b"mojave signing key self-test";
+
+/// Signing key whose scheme is selected at runtime (e.g. from the node configuration) among the
+/// schemes enabled at compile time.
+#[derive(Clone, Debug)]
+pub enum AnySigningKey {
+    #[cfg(feature = "secp256k1")]
+    Secp256k1(crate::ecdsa::SigningKey),
+    #[cfg(feature = "ed25519")]
+    Ed25519(crate::eddsa::SigningKey),
+    #[cfg(feature = "bls")]
+    Bls(crate::bls::SigningKey),
+}
+
+impl AnySigningKey {
+    /// Parses a hex encoded private key, with or without the `0x` prefix, for the given scheme.
+    pub fn from_str_with_scheme(scheme: SignatureScheme, s: &str) -> Result<Self, SignatureError> {
+        match scheme {
+            #[cfg(feature = "secp256k1")]
+            SignatureScheme::Secp256k1 => {
+                Ok(Self::Secp256k1(crate::ecdsa::SigningKey::from_str(s)?))
+            }
+            #[cfg(feature = "ed25519")]
+            SignatureScheme::Ed25519 => Ok(Self::Ed25519(crate::eddsa::SigningKey::from_str(s)?)),
+            #[cfg(feature = "bls")]
+            SignatureScheme::Bls => Ok(Self::Bls(crate::bls::SigningKey::from_str(s)?)),
+            #[allow(unreachable_patterns)]
+            scheme => Err(SignatureError::UnsupportedScheme(scheme)),
+        }
+    }
+
+    pub fn scheme(&self) -> SignatureScheme {
+        match self {
+            #[cfg(feature = "secp256k1")]
+            Self::Secp256k1(_) => SignatureScheme::Secp256k1,
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(_) => SignatureScheme::Ed25519,
+            #[cfg(feature = "bls")]
+            Self::Bls(_) => SignatureScheme::Bls,
+        }
+    }
+
+    /// Signs the bytes of `message` as they are, see [`crate::MessageEncoding::Raw`].
+    pub fn sign_raw(&self, message: &[u8]) -> Result<Signature, SignatureError> {
+        match self {
+            #[cfg(feature = "secp256k1")]
+            Self::Secp256k1(key) => key.sign_raw(message),
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(key) => key.sign_raw(message),
+            #[cfg(feature = "bls")]
+            Self::Bls(key) => key.sign_raw(message),
+        }
+    }
+
+    /// Signs a 32 bytes digest as it is, see [`crate::MessageEncoding::Prehashed`].
+    pub fn sign_prehashed(&self, digest: &[u8; 32]) -> Result<Signature, SignatureError> {
+        match self {
+            #[cfg(feature = "secp256k1")]
+            Self::Secp256k1(key) => key.sign_prehashed(digest),
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(key) => key.sign_prehashed(digest),
+            #[cfg(feature = "bls")]
+            Self::Bls(key) => key.sign_prehashed(digest),
+        }
+    }
+
+    /// Selects the digest applied to the messages before signing them, which only the secp256k1
+    /// scheme lets choose. The key is returned as is for the other schemes.
+    pub fn with_digest(self, digest: DigestKind) -> Self {
+        match self {
+            #[cfg(feature = "secp256k1")]
+            Self::Secp256k1(key) => Self::Secp256k1(key.with_digest(digest)),
+            #[allow(unreachable_patterns)]
+            key => {
+                let _ = digest;
+                key
+            }
+        }
+    }
+
+    pub fn sign<T: Serialize>(&self, message: &T) -> Result<Signature, SignatureError> {
+        match self {
+            #[cfg(feature = "secp256k1")]
+            Self::Secp256k1(key) => key.sign(message),
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(key) => key.sign(message),
+            #[cfg(feature = "bls")]
+            Self::Bls(key) => key.sign(message),
+        }
+    }
+
+    pub fn verifying_key(&self) -> AnyVerifyingKey {
+        match self {
+            #[cfg(feature = "secp256k1")]
+            Self::Secp256k1(key) => AnyVerifyingKey::Secp256k1(key.verifying_key()),
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(key) => AnyVerifyingKey::Ed25519(key.verifying_key()),
+            #[cfg(feature = "bls")]
+            Self::Bls(key) => AnyVerifyingKey::Bls(key.verifying_key()),
+        }
+    }
+
+    /// Signs a test message and verifies it with `verifying_key`, for a node to find out at
+    /// startup that its key, or the scheme it was parsed with, produces signatures that won't
+    /// verify against the key the other nodes expect.
+    pub fn self_test(&self, verifying_key: &AnyVerifyingKey) -> Result<(), SignatureError> {
+        let signature = self.sign(&SELF_TEST_MESSAGE)?;
+        verifying_key.verify(&SELF_TEST_MESSAGE, &signature)
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+impl From<crate::ecdsa::SigningKey> for AnySigningKey {
+    fn from(key: crate::ecdsa::SigningKey) -> Self {
+        Self::Secp256k1(key)
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl From<crate::eddsa::SigningKey> for AnySigningKey {
+    fn from(key: crate::eddsa::SigningKey) -> Self {
+        Self::Ed25519(key)
+    }
+}
+
+#[cfg(feature = "bls")]
+impl From<crate::bls::SigningKey> for AnySigningKey {
+    fn from(key: crate::bls::SigningKey) -> Self {
+        Self::Bls(key)
+    }
+}
+
+/// Verifying key whose scheme is only known at runtime. It serializes along with the short tag
+/// of its scheme, the same as [`SignatureScheme`], e.g. `{"scheme":"ecdsa","key":"02..."}`, so
+/// the receiving side knows how to parse it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "scheme", content = "key")]
+pub enum AnyVerifyingKey {
+    #[cfg(feature = "secp256k1")]
+    #[serde(rename = "ecdsa", alias = "Secp256k1")]
+    Secp256k1(crate::ecdsa::VerifyingKey),
+    #[cfg(feature = "ed25519")]
+    #[serde(rename = "ed25519", alias = "Ed25519")]
+    Ed25519(crate::eddsa::VerifyingKey),
+    #[cfg(feature = "bls")]
+    #[serde(rename = "bls", alias = "Bls")]
+    Bls(crate::bls::VerifyingKey),
+}
+
+impl AnyVerifyingKey {
+    /// Parses a hex encoded public key for the given scheme.
+    pub fn from_str_with_scheme(scheme: SignatureScheme, s: &str) -> Result<Self, SignatureError> {
+        match scheme {
+            #[cfg(feature = "secp256k1")]
+            SignatureScheme::Secp256k1 => {
+                Ok(Self::Secp256k1(crate::ecdsa::VerifyingKey::from_str(s)?))
+            }
+            #[cfg(feature = "ed25519")]
+            SignatureScheme::Ed25519 => Ok(Self::Ed25519(crate::eddsa::VerifyingKey::from_str(s)?)),
+            #[cfg(feature = "bls")]
+            SignatureScheme::Bls => Ok(Self::Bls(crate::bls::VerifyingKey::from_str(s)?)),
+            #[allow(unreachable_patterns)]
+            scheme => Err(SignatureError::UnsupportedScheme(scheme)),
+        }
+    }
+
+    pub fn scheme(&self) -> SignatureScheme {
+        match self {
+            #[cfg(feature = "secp256k1")]
+            Self::Secp256k1(_) => SignatureScheme::Secp256k1,
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(_) => SignatureScheme::Ed25519,
+            #[cfg(feature = "bls")]
+            Self::Bls(_) => SignatureScheme::Bls,
+        }
+    }
+
+    /// Verifies the signature, failing if it was produced with another scheme than this key's.
+    pub fn verify<T: Serialize>(
+        &self,
+        message: &T,
+        signature: &Signature,
+    ) -> Result<(), SignatureError> {
+        match self {
+            #[cfg(feature = "secp256k1")]
+            Self::Secp256k1(key) => key.verify(message, signature),
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(key) => key.verify(message, signature),
+            #[cfg(feature = "bls")]
+            Self::Bls(key) => key.verify(message, signature),
+        }
+    }
+
+    /// Verifies a signature of [`AnySigningKey::sign_raw`] over the bytes of `message`.
+    pub fn verify_raw(&self, message: &[u8], signature: &Signature) -> Result<(), SignatureError> {
+        match self {
+            #[cfg(feature = "secp256k1")]
+            Self::Secp256k1(key) => key.verify_raw(message, signature),
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(key) => key.verify_raw(message, signature),
+            #[cfg(feature = "bls")]
+            Self::Bls(key) => key.verify_raw(message, signature),
+        }
+    }
+
+    /// Verifies a signature of [`AnySigningKey::sign_prehashed`] over `digest`.
+    pub fn verify_prehashed(
+        &self,
+        digest: &[u8; 32],
+        signature: &Signature,
+    ) -> Result<(), SignatureError> {
+        match self {
+            #[cfg(feature = "secp256k1")]
+            Self::Secp256k1(key) => key.verify_prehashed(digest, signature),
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(key) => key.verify_prehashed(digest, signature),
+            #[cfg(feature = "bls")]
+            Self::Bls(key) => key.verify_prehashed(digest, signature),
+        }
+    }
+
+    /// Verifies a run of signatures at once, see [`Verifier::verify_batch`]. The items of each
+    /// scheme are verified with its own batch implementation.
+    pub fn verify_batch<T: Serialize>(
+        items: &[(&Self, &T, &Signature)],
+    ) -> Result<Vec<bool>, SignatureError> {
+        let mut results = vec![false; items.len()];
+        #[cfg(feature = "secp256k1")]
+        {
+            #[allow(unreachable_patterns)]
+            let (indices, secp256k1_items): (Vec<_>, Vec<_>) = items
+                .iter()
+                .enumerate()
+                .filter_map(|(index, (key, message, signature))| match key {
+                    Self::Secp256k1(key) => Some((index, (key, *message, *signature))),
+                    _ => None,
+                })
+                .unzip();
+            let valid = crate::ecdsa::VerifyingKey::verify_batch(&secp256k1_items)?;
+            for (index, valid) in indices.into_iter().zip(valid) {
+                results[index] = valid;
+            }
+        }
+        for (result, (key, message, signature)) in results.iter_mut().zip(items) {
+            match key {
+                #[cfg(feature = "secp256k1")]
+                Self::Secp256k1(_) => {}
+                #[allow(unreachable_patterns)]
+                key => *result = key.verify(*message, signature).is_ok(),
+            }
+        }
+        Ok(results)
+    }
+
+    /// Recovers the key that produced the signature over `message`, for the schemes allowing it,
+    /// see [`SignatureScheme::is_recoverable`].
+    pub fn recover<T: Serialize>(
+        message: &T,
+        signature: &Signature,
+    ) -> Result<Self, SignatureError> {
+        match signature.scheme {
+            #[cfg(feature = "secp256k1")]
+            SignatureScheme::Secp256k1 => Ok(Self::Secp256k1(crate::ecdsa::VerifyingKey::recover(
+                message, signature,
+            )?)),
+            scheme => Err(SignatureError::UnrecoverableScheme(scheme)),
+        }
+    }
+
+    pub fn to_address(&self) -> String {
+        match self {
+            #[cfg(feature = "secp256k1")]
+            Self::Secp256k1(key) => key.to_address(),
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(key) => key.to_address(),
+            #[cfg(feature = "bls")]
+            Self::Bls(key) => key.to_address(),
+        }
+    }
+}
+
+impl FromStr for AnyVerifyingKey {
+    type Err = SignatureError;
+
+    /// Parses a hex encoded public key prefixed with its scheme, e.g. `ed25519:d75a...`, as the
+    /// nodes take it from their configuration.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (scheme, key) = s.split_once(':').ok_or_else(|| {
+            SignatureError::InvalidFormat(format!(
+                "expected a public key in the form 'scheme:hex', got {s}"
+            ))
+        })?;
+        Self::from_str_with_scheme(SignatureScheme::from_str(scheme)?, key)
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+impl From<crate::ecdsa::VerifyingKey> for AnyVerifyingKey {
+    fn from(key: crate::ecdsa::VerifyingKey) -> Self {
+        Self::Secp256k1(key)
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl From<crate::eddsa::VerifyingKey> for AnyVerifyingKey {
+    fn from(key: crate::eddsa::VerifyingKey) -> Self {
+        Self::Ed25519(key)
+    }
+}
+
+#[cfg(feature = "bls")]
+impl From<crate::bls::VerifyingKey> for AnyVerifyingKey {
+    fn from(key: crate::bls::VerifyingKey) -> Self {
+        Self::Bls(key)
+    }
+}
+
+#[cfg(all(test, feature = "secp256k1", feature = "ed25519"))]
+mod tests {
+    use super::*;
+
+    const SECP256K1_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const ED25519_KEY: &str = "902ddc4259c907ef56ad9be31f6640978eb8d392e18ffde0a569ded804df23e1";
+
+    #[test]
+    fn test_sign_and_verify_with_each_scheme() {
+        for (scheme, key) in [
+            (SignatureScheme::Secp256k1, SECP256K1_KEY),
+            (SignatureScheme::Ed25519, ED25519_KEY),
+        ] {
+            let signing_key = AnySigningKey::from_str_with_scheme(scheme, key).unwrap();
+            assert_eq!(signing_key.scheme(), scheme);
+
+            let signature = signing_key.sign(b"Hello World").unwrap();
+            assert_eq!(signature.scheme, scheme);
+
+            let verifying_key = signing_key.verifying_key();
+            assert_eq!(verifying_key.scheme(), scheme);
+            verifying_key.verify(b"Hello World", &signature).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_verify_rejects_signature_from_other_scheme() {
+        let secp256k1 =
+            AnySigningKey::from_str_with_scheme(SignatureScheme::Secp256k1, SECP256K1_KEY).unwrap();
+        let ed25519 =
+            AnySigningKey::from_str_with_scheme(SignatureScheme::Ed25519, ED25519_KEY).unwrap();
+
+        let signature = secp256k1.sign(b"Hello World").unwrap();
+        let result = ed25519.verifying_key().verify(b"Hello World", &signature);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_verifying_key_rejects_key_of_other_scheme() {
+        let ed25519 =
+            AnySigningKey::from_str_with_scheme(SignatureScheme::Ed25519, ED25519_KEY).unwrap();
+        let ed25519_public_key = String::from(match ed25519.verifying_key() {
+            AnyVerifyingKey::Ed25519(key) => key,
+            _ => unreachable!(),
+        });
+
+        let result =
+            AnyVerifyingKey::from_str_with_scheme(SignatureScheme::Secp256k1, &ed25519_public_key);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_verifying_key_from_str_with_scheme_prefix() {
+        let ed25519 =
+            AnySigningKey::from_str_with_scheme(SignatureScheme::Ed25519, ED25519_KEY).unwrap();
+        let ed25519_public_key = String::from(match ed25519.verifying_key() {
+            AnyVerifyingKey::Ed25519(key) => key,
+            _ => unreachable!(),
+        });
+
+        let parsed = AnyVerifyingKey::from_str(&format!("ed25519:{ed25519_public_key}")).unwrap();
+        assert_eq!(parsed.scheme(), SignatureScheme::Ed25519);
+        assert_eq!(parsed.to_address(), ed25519.verifying_key().to_address());
+
+        assert!(AnyVerifyingKey::from_str(&ed25519_public_key).is_err());
+        assert!(AnyVerifyingKey::from_str(&format!("ecdsa:{ed25519_public_key}")).is_err());
+        assert!(AnyVerifyingKey::from_str(&format!("rsa:{ed25519_public_key}")).is_err());
+    }
+
+    #[test]
+    fn test_self_test_fails_for_mismatched_key_or_scheme() {
+        let secp256k1 =
+            AnySigningKey::from_str_with_scheme(SignatureScheme::Secp256k1, SECP256K1_KEY).unwrap();
+        let ed25519 =
+            AnySigningKey::from_str_with_scheme(SignatureScheme::Ed25519, ED25519_KEY).unwrap();
+        secp256k1.self_test(&secp256k1.verifying_key()).unwrap();
+        ed25519.self_test(&ed25519.verifying_key()).unwrap();
+
+        // The secp256k1 private key configured with the ed25519 scheme.
+        let wrong_scheme =
+            AnySigningKey::from_str_with_scheme(SignatureScheme::Ed25519, SECP256K1_KEY).unwrap();
+        assert!(wrong_scheme.self_test(&secp256k1.verifying_key()).is_err());
+        assert!(ed25519.self_test(&secp256k1.verifying_key()).is_err());
+
+        let other_key = AnySigningKey::from_str_with_scheme(
+            SignatureScheme::Secp256k1,
+            "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d",
+        )
+        .unwrap();
+        assert!(other_key.self_test(&secp256k1.verifying_key()).is_err());
+    }
+
+    #[test]
+    fn test_verify_batch_mixes_schemes() {
+        let secp256k1 =
+            AnySigningKey::from_str_with_scheme(SignatureScheme::Secp256k1, SECP256K1_KEY).unwrap();
+        let ed25519 =
+            AnySigningKey::from_str_with_scheme(SignatureScheme::Ed25519, ED25519_KEY).unwrap();
+        let (secp256k1_key, ed25519_key) = (secp256k1.verifying_key(), ed25519.verifying_key());
+        let secp256k1_signature = secp256k1.sign(b"Hello World").unwrap();
+        let ed25519_signature = ed25519.sign(b"Hello World").unwrap();
+
+        let results = AnyVerifyingKey::verify_batch(&[
+            (&secp256k1_key, b"Hello World", &secp256k1_signature),
+            (&ed25519_key, b"Hello World", &ed25519_signature),
+            (&ed25519_key, b"Hello World", &secp256k1_signature),
+            (&secp256k1_key, b"Hello World", &ed25519_signature),
+        ])
+        .unwrap();
+        assert_eq!(results, vec![true, true, false, false]);
+    }
+
+    #[test]
+    fn test_verifying_key_serde_round_trip() {
+        for (scheme, key) in [
+            (SignatureScheme::Secp256k1, SECP256K1_KEY),
+            (SignatureScheme::Ed25519, ED25519_KEY),
+        ] {
+            let verifying_key = AnySigningKey::from_str_with_scheme(scheme, key)
+                .unwrap()
+                .verifying_key();
+
+            let json = serde_json::to_value(&verifying_key).unwrap();
+            assert_eq!(json["scheme"], serde_json::to_value(scheme).unwrap());
+            assert_eq!(json["scheme"], scheme.as_str());
+
+            let decoded: AnyVerifyingKey = serde_json::from_value(json).unwrap();
+            assert_eq!(decoded.scheme(), scheme);
+            assert_eq!(decoded.to_address(), verifying_key.to_address());
+
+            let mut legacy = serde_json::to_value(&verifying_key).unwrap();
+            legacy["scheme"] = format!("{scheme:?}").into();
+            let decoded: AnyVerifyingKey = serde_json::from_value(legacy).unwrap();
+            assert_eq!(decoded.scheme(), scheme);
+        }
+    }
+}
@@ -0,0 +1,439 @@
+use crate::{DigestKind, MessageEncoding, Signature, SignatureError, SignatureScheme};
+use blst::{
+    min_pk::{AggregateSignature, PublicKey, SecretKey as PrivateKey, Signature as BlsSignature},
+    BLST_ERROR,
+};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use tiny_keccak::{Hasher, Keccak};
+
+/// Domain separation tag of the proof of possession ciphersuite, which makes the aggregation of
+/// signatures over the same message safe as long as every public key was registered along with a
+/// proof of possession of its private key.
+const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Domain separation tag of the proofs of possession, distinct from [`DST`] so that a proof
+/// cannot be passed off as a signature of the key bytes, or the other way around.
+const POP_DST: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// The blst private key erases itself from memory once dropped.
+#[derive(Clone)]
+pub struct SigningKey(PrivateKey);
+
+/// Only shows the address of the key, never the private key itself.
+impl std::fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("address", &self.verifying_key().to_address())
+            .finish_non_exhaustive()
+    }
+}
+
+impl FromStr for SigningKey {
+    type Err = SignatureError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix("0x").unwrap_or(s);
+        let bytes = hex::decode(s).map_err(|error| Error::CreateSigningKey(error.into()))?;
+        <Self as super::Signer>::from_slice(&bytes)
+    }
+}
+
+impl super::Signer for SigningKey {
+    fn from_slice(slice: &[u8]) -> Result<Self, SignatureError> {
+        let secret_key = PrivateKey::from_bytes(slice)
+            .map_err(|error| Error::CreateSigningKey(ErrorKind::Blst(error)))?;
+        Ok(Self(secret_key))
+    }
+
+    fn sign<T: Serialize>(&self, message: &T) -> Result<Signature, SignatureError> {
+        let message_bytes =
+            bincode::serialize(message).map_err(|error| Error::Sign(error.into()))?;
+        Ok(self.sign_bytes(&message_bytes, MessageEncoding::Bincode))
+    }
+
+    fn sign_raw(&self, message: &[u8]) -> Result<Signature, SignatureError> {
+        Ok(self.sign_bytes(message, MessageEncoding::Raw))
+    }
+
+    fn sign_prehashed(&self, digest: &[u8; 32]) -> Result<Signature, SignatureError> {
+        Ok(self.sign_bytes(digest, MessageEncoding::Prehashed))
+    }
+}
+
+impl SigningKey {
+    fn sign_bytes(&self, message_bytes: &[u8], encoding: MessageEncoding) -> Signature {
+        let signature = self.0.sign(message_bytes, DST, &[]);
+        Signature {
+            bytes: signature.to_bytes().to_vec(),
+            scheme: SignatureScheme::Bls,
+            digest: DigestKind::default(),
+            encoding,
+        }
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        VerifyingKey(self.0.sk_to_pk())
+    }
+
+    /// Signs the compressed verifying key, for it to be registered along with the proof, see
+    /// [`VerifyingKey::verify_possession`].
+    pub fn prove_possession(&self) -> Signature {
+        let proof = self.0.sign(&self.0.sk_to_pk().compress(), POP_DST, &[]);
+        Signature {
+            bytes: proof.to_bytes().to_vec(),
+            scheme: SignatureScheme::Bls,
+            digest: DigestKind::default(),
+            encoding: MessageEncoding::Raw,
+        }
+    }
+}
+
+/// Public key in G1, hex encoded in its 48 bytes compressed form.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(try_from = "String")]
+#[serde(into = "String")]
+pub struct VerifyingKey(PublicKey);
+
+impl TryFrom<String> for VerifyingKey {
+    type Error = SignatureError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::from_str(&value)
+    }
+}
+
+impl From<VerifyingKey> for String {
+    fn from(value: VerifyingKey) -> Self {
+        hex::encode(value.0.compress())
+    }
+}
+
+impl FromStr for VerifyingKey {
+    type Err = SignatureError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s).map_err(|error| Error::CreateVerifyingKey(error.into()))?;
+        <Self as crate::Verifier>::from_slice(&bytes)
+    }
+}
+
+impl crate::Verifier for VerifyingKey {
+    fn from_slice(slice: &[u8]) -> Result<Self, SignatureError> {
+        // Rejects the point at infinity and points outside of the group.
+        let public_key = PublicKey::key_validate(slice)
+            .map_err(|error| Error::CreateVerifyingKey(ErrorKind::Blst(error)))?;
+        Ok(Self(public_key))
+    }
+
+    fn verify<T: Serialize>(
+        &self,
+        message: &T,
+        signature: &Signature,
+    ) -> Result<(), SignatureError> {
+        let message_bytes =
+            bincode::serialize(message).map_err(|error| Error::Verify(error.into()))?;
+        self.verify_bytes(&message_bytes, MessageEncoding::Bincode, signature)
+    }
+
+    fn verify_raw(&self, message: &[u8], signature: &Signature) -> Result<(), SignatureError> {
+        self.verify_bytes(message, MessageEncoding::Raw, signature)
+    }
+
+    fn verify_prehashed(
+        &self,
+        digest: &[u8; 32],
+        signature: &Signature,
+    ) -> Result<(), SignatureError> {
+        self.verify_bytes(digest, MessageEncoding::Prehashed, signature)
+    }
+}
+
+impl VerifyingKey {
+    fn verify_bytes(
+        &self,
+        message_bytes: &[u8],
+        encoding: MessageEncoding,
+        signature: &Signature,
+    ) -> Result<(), SignatureError> {
+        if signature.encoding != encoding {
+            return Err(Error::Verify(ErrorKind::InvalidMessageEncoding))?;
+        }
+        signature.check_len()?;
+        let signature = parse_signature(signature).map_err(Error::Verify)?;
+
+        match signature.verify(false, message_bytes, DST, &[], &self.0, false) {
+            BLST_ERROR::BLST_SUCCESS => Ok(()),
+            error => Err(Error::Verify(ErrorKind::Blst(error)).into()),
+        }
+    }
+
+    /// Checks a proof of [`SigningKey::prove_possession`], which a key must pass before being
+    /// trusted with [`aggregate_verify`].
+    pub fn verify_possession(&self, proof: &Signature) -> Result<(), SignatureError> {
+        proof.check_len()?;
+        let proof = parse_signature(proof).map_err(Error::Verify)?;
+        match proof.verify(false, &self.0.compress(), POP_DST, &[], &self.0, false) {
+            BLST_ERROR::BLST_SUCCESS => Ok(()),
+            error => Err(Error::Verify(ErrorKind::Blst(error)).into()),
+        }
+    }
+
+    /// Hex encoded last 20 bytes of the keccak256 hash of the compressed key, like the other
+    /// schemes' addresses.
+    pub fn to_address(&self) -> String {
+        let mut hasher = Keccak::v256();
+        hasher.update(&self.0.compress());
+        let mut hash = [0u8; 32];
+        hasher.finalize(&mut hash);
+
+        hex::encode(&hash[12..32])
+    }
+}
+
+/// Aggregates the signatures into one whose [`Signature::bytes`] have the same 96 bytes layout
+/// as a single BLS signature.
+pub fn aggregate(signatures: &[Signature]) -> Result<Signature, SignatureError> {
+    if signatures.is_empty() {
+        return Err(Error::Aggregate(ErrorKind::Empty))?;
+    }
+    let signatures = signatures
+        .iter()
+        .map(parse_signature)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Error::Aggregate)?;
+    let signatures: Vec<&BlsSignature> = signatures.iter().collect();
+
+    let aggregate = AggregateSignature::aggregate(&signatures, false)
+        .map_err(|error| Error::Aggregate(ErrorKind::Blst(error)))?;
+    Ok(Signature {
+        bytes: aggregate.to_signature().to_bytes().to_vec(),
+        scheme: SignatureScheme::Bls,
+        digest: DigestKind::default(),
+        encoding: MessageEncoding::default(),
+    })
+}
+
+/// Verifies an aggregated signature, `keys[i]` being the key that signed `messages[i]`, in a
+/// single pairing check instead of one per signature.
+///
+/// The keys must have passed [`VerifyingKey::verify_possession`] when they were registered.
+/// Otherwise a rogue key, derived from the keys of others, forges an aggregate over a message
+/// the others never signed.
+pub fn aggregate_verify<T: Serialize>(
+    keys: &[VerifyingKey],
+    messages: &[&T],
+    aggregate: &Signature,
+) -> Result<(), SignatureError> {
+    if keys.is_empty() {
+        return Err(Error::Verify(ErrorKind::Empty))?;
+    }
+    if keys.len() != messages.len() {
+        return Err(Error::Verify(ErrorKind::LengthMismatch {
+            keys: keys.len(),
+            messages: messages.len(),
+        }))?;
+    }
+
+    let aggregate = parse_signature(aggregate).map_err(Error::Verify)?;
+    let messages = messages
+        .iter()
+        .map(|message| bincode::serialize(message))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| Error::Verify(error.into()))?;
+    let messages: Vec<&[u8]> = messages.iter().map(Vec::as_slice).collect();
+    let keys: Vec<&PublicKey> = keys.iter().map(|key| &key.0).collect();
+
+    match aggregate.aggregate_verify(false, &messages, DST, &keys, false) {
+        BLST_ERROR::BLST_SUCCESS => Ok(()),
+        error => Err(Error::Verify(ErrorKind::Blst(error)).into()),
+    }
+}
+
+/// Parses a compressed G2 signature, checking that it belongs to the group.
+fn parse_signature(signature: &Signature) -> Result<BlsSignature, ErrorKind> {
+    if signature.scheme != SignatureScheme::Bls {
+        return Err(ErrorKind::InvalidSignatureScheme);
+    }
+    BlsSignature::sig_validate(&signature.bytes, true).map_err(ErrorKind::Blst)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Failed to create a signing key: {0}")]
+    CreateSigningKey(ErrorKind),
+    #[error("Failed to sign the message: {0}")]
+    Sign(ErrorKind),
+    #[error("Failed to create a verifying key: {0}")]
+    CreateVerifyingKey(ErrorKind),
+    #[error("Failed to verify the message: {0}")]
+    Verify(ErrorKind),
+    #[error("Failed to aggregate the signatures: {0}")]
+    Aggregate(ErrorKind),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ErrorKind {
+    #[error("{0:?}")]
+    Blst(BLST_ERROR),
+    #[error("{0}")]
+    Hex(#[from] hex::FromHexError),
+    #[error("{0}")]
+    Bincode(#[from] bincode::Error),
+    #[error("Invalid signature scheme")]
+    InvalidSignatureScheme,
+    #[error("The signature is over a message of another encoding")]
+    InvalidMessageEncoding,
+    #[error("Nothing to aggregate")]
+    Empty,
+    #[error("Got {keys} keys for {messages} messages")]
+    LengthMismatch { keys: usize, messages: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{Signer, Verifier};
+
+    const PRIVATE_KEY: &str = "263dbd792f5b1be47ed85f8938c0f29586af0d3ac7b977f21c278fe1462040e3";
+
+    fn signing_keys() -> Vec<SigningKey> {
+        (1..=3)
+            .map(|byte| SigningKey::from_slice(&[byte; 32]).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_bls_sign_and_verify() {
+        let signing_key = SigningKey::from_str(PRIVATE_KEY).unwrap();
+        let verifying_key = signing_key.verifying_key();
+        let msg = b"Hello World";
+
+        let signature = signing_key.sign(msg).unwrap();
+        assert_eq!(signature.bytes.len(), 96);
+        assert!(verifying_key.verify(msg, &signature).is_ok());
+        assert!(verifying_key.verify(b"Hello World!", &signature).is_err());
+    }
+
+    #[test]
+    fn test_bls_verify_rejects_signature_from_other_scheme() {
+        let signing_key = SigningKey::from_str(PRIVATE_KEY).unwrap();
+        let mut signature = signing_key.sign(b"Hello World").unwrap();
+        signature.scheme = SignatureScheme::Ed25519;
+
+        let result = signing_key
+            .verifying_key()
+            .verify(b"Hello World", &signature);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_bls_aggregate_verify() {
+        let signing_keys = signing_keys();
+        let verifying_keys: Vec<_> = signing_keys.iter().map(SigningKey::verifying_key).collect();
+        let messages = [b"block 1", b"block 2", b"block 3"];
+        let signatures: Vec<_> = signing_keys
+            .iter()
+            .zip(&messages)
+            .map(|(key, msg)| key.sign(msg).unwrap())
+            .collect();
+
+        let aggregate = aggregate(&signatures).unwrap();
+        assert_eq!(aggregate.bytes.len(), 96);
+        let msgs: Vec<_> = messages.iter().collect();
+        assert!(aggregate_verify(&verifying_keys, &msgs, &aggregate).is_ok());
+
+        let tampered = [&messages[0], &messages[1], &b"block 4"];
+        assert!(aggregate_verify(&verifying_keys, &tampered, &aggregate).is_err());
+        assert!(aggregate_verify(&verifying_keys[..2], &msgs[..2], &aggregate).is_err());
+    }
+
+    #[test]
+    fn test_bls_aggregate_verify_same_message() {
+        let signing_keys = signing_keys();
+        let verifying_keys: Vec<_> = signing_keys.iter().map(SigningKey::verifying_key).collect();
+        let msg = b"block 1";
+        let signatures: Vec<_> = signing_keys
+            .iter()
+            .map(|key| key.sign(msg).unwrap())
+            .collect();
+
+        let aggregate = aggregate(&signatures).unwrap();
+        assert!(aggregate_verify(&verifying_keys, &[msg; 3], &aggregate).is_ok());
+    }
+
+    #[test]
+    fn test_bls_aggregate_rejects_invalid_input() {
+        assert!(aggregate(&[]).is_err());
+
+        let invalid_signature = Signature {
+            bytes: vec![0u8; 96],
+            scheme: SignatureScheme::Bls,
+            digest: DigestKind::default(),
+            encoding: MessageEncoding::Bincode,
+        };
+        assert!(aggregate(&[invalid_signature]).is_err());
+
+        let signing_key = SigningKey::from_str(PRIVATE_KEY).unwrap();
+        let signature = signing_key.sign(&"Hello World").unwrap();
+        let result = aggregate_verify(
+            &[signing_key.verifying_key()],
+            &[&"Hello World", &"Hello"],
+            &signature,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_bls_proof_of_possession() {
+        let signing_keys = signing_keys();
+        let proof = signing_keys[0].prove_possession();
+
+        let verifying_key = signing_keys[0].verifying_key();
+        assert!(verifying_key.verify_possession(&proof).is_ok());
+        assert!(signing_keys[1]
+            .verifying_key()
+            .verify_possession(&proof)
+            .is_err());
+
+        // A signature of the key bytes is not a proof, the tags of the two differ.
+        let signature = signing_keys[0]
+            .sign_raw(&verifying_key.0.compress())
+            .unwrap();
+        assert!(verifying_key.verify_possession(&signature).is_err());
+    }
+
+    #[test]
+    fn test_bls_address_is_20_bytes() {
+        let address = SigningKey::from_str(PRIVATE_KEY)
+            .unwrap()
+            .verifying_key()
+            .to_address();
+        assert_eq!(hex::decode(address).unwrap().len(), 20);
+    }
+
+    #[test]
+    fn test_bls_invalid_verifying_key() {
+        assert!(VerifyingKey::from_str("invalid_hex_string_not_valid").is_err());
+        assert!(VerifyingKey::from_slice(&[0u8; 48]).is_err());
+        assert!(VerifyingKey::from_slice(&[0u8; 16]).is_err());
+    }
+
+    #[test]
+    fn test_bls_serde_round_trip() {
+        let signing_key = SigningKey::from_str(PRIVATE_KEY).unwrap();
+        let verifying_key = signing_key.verifying_key();
+        let signature = signing_key.sign(b"Hello World").unwrap();
+
+        let json = serde_json::to_string(&verifying_key).unwrap();
+        let decoded: VerifyingKey = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.to_address(), verifying_key.to_address());
+
+        let json = serde_json::to_string(&signature).unwrap();
+        let decoded: Signature = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.bytes, signature.bytes);
+        assert_eq!(decoded.scheme, SignatureScheme::Bls);
+        assert!(verifying_key.verify(b"Hello World", &decoded).is_ok());
+    }
+}
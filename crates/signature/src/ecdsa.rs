@@ -1,19 +1,52 @@
-use crate::{Signature, SignatureError, SignatureScheme};
+use crate::{DigestKind, MessageEncoding, Signature, SignatureError, SignatureScheme};
 use secp256k1::{
-    ecdsa::Signature as EcdsaSignature, Message, PublicKey, Secp256k1, SecretKey as PrivateKey,
+    ecdsa::{RecoverableSignature, RecoveryId, Signature as EcdsaSignature},
+    Message, PublicKey, Secp256k1, SecretKey as PrivateKey,
 };
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
-use std::{str::FromStr, sync::LazyLock};
+use std::{num::NonZeroUsize, str::FromStr, sync::LazyLock};
 use tiny_keccak::{Hasher, Keccak};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 static SECP256K1_SIGNING: LazyLock<Secp256k1<secp256k1::SignOnly>> =
     LazyLock::new(Secp256k1::signing_only);
 static SECP256K1_VERIFY: LazyLock<Secp256k1<secp256k1::VerifyOnly>> =
     LazyLock::new(Secp256k1::verification_only);
 
-#[derive(Clone, Debug)]
-pub struct SigningKey(PrivateKey);
+/// Private key along with the digest applied to the messages it signs, SHA-256 unless selected
+/// otherwise with [`SigningKey::with_digest`].
+#[derive(Clone)]
+pub struct SigningKey(SecretKey, DigestKind);
+
+/// Private key erased from memory once dropped. secp256k1 only exposes an erasure that the
+/// compiler may elide, hence the fence after it.
+#[derive(Clone)]
+struct SecretKey(PrivateKey);
+
+impl Zeroize for SecretKey {
+    fn zeroize(&mut self) {
+        self.0.non_secure_erase();
+        std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SecretKey {}
+
+/// Only shows the address of the key, never the private key itself.
+impl std::fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("address", &self.verifying_key().to_address())
+            .field("digest", &self.1)
+            .finish_non_exhaustive()
+    }
+}
 
 impl FromStr for SigningKey {
     type Err = SignatureError;
@@ -28,7 +61,7 @@ impl FromStr for SigningKey {
             PrivateKey::from_slice(&bytes)
                 .map_err(|error| Error::CreateSigningKey(ErrorKind::Secp256k1(error)))
         })?;
-        Ok(Self(private_key))
+        Ok(Self(SecretKey(private_key), DigestKind::default()))
     }
 }
 
@@ -36,28 +69,61 @@ impl super::Signer for SigningKey {
     fn from_slice(slice: &[u8]) -> Result<Self, SignatureError> {
         let private_key =
             PrivateKey::from_slice(slice).map_err(|error| Error::CreateSigningKey(error.into()))?;
-        Ok(Self(private_key))
+        Ok(Self(SecretKey(private_key), DigestKind::default()))
     }
 
     fn sign<T: Serialize>(&self, message: &T) -> Result<Signature, SignatureError> {
         let message_bytes =
             bincode::serialize(message).map_err(|error| Error::Sign(error.into()))?;
-        let msg_hash = Sha256::digest(message_bytes);
-        let message = Message::from_digest_slice(msg_hash.as_slice())
-            .map_err(|error| Error::Sign(error.into()))?;
+        self.sign_bytes(&message_bytes, MessageEncoding::Bincode)
+    }
+
+    fn sign_raw(&self, message: &[u8]) -> Result<Signature, SignatureError> {
+        self.sign_bytes(message, MessageEncoding::Raw)
+    }
+
+    fn sign_prehashed(&self, digest: &[u8; 32]) -> Result<Signature, SignatureError> {
+        self.sign_digest(digest, MessageEncoding::Prehashed)
+    }
+}
+
+impl SigningKey {
+    fn sign_bytes(
+        &self,
+        message_bytes: &[u8],
+        encoding: MessageEncoding,
+    ) -> Result<Signature, SignatureError> {
+        self.sign_digest(&self.1.digest(message_bytes), encoding)
+    }
+
+    fn sign_digest(
+        &self,
+        msg_hash: &[u8; 32],
+        encoding: MessageEncoding,
+    ) -> Result<Signature, SignatureError> {
+        let message =
+            Message::from_digest_slice(msg_hash).map_err(|error| Error::Sign(error.into()))?;
         let secp256k1 = &SECP256K1_SIGNING;
-        let signature = secp256k1.sign_ecdsa(&message, &self.0).serialize_compact();
+        let (recovery_id, signature) = secp256k1
+            .sign_ecdsa_recoverable(&message, &self.0 .0)
+            .serialize_compact();
+        let mut bytes = signature.to_vec();
+        bytes.push(recovery_id.to_i32() as u8);
         Ok(Signature {
-            bytes: signature.to_vec(),
+            bytes,
             scheme: SignatureScheme::Secp256k1,
+            digest: self.1,
+            encoding,
         })
     }
-}
 
-impl SigningKey {
+    pub fn with_digest(self, digest: DigestKind) -> Self {
+        Self(self.0, digest)
+    }
+
     pub fn verifying_key(&self) -> VerifyingKey {
         let secp = Secp256k1::new();
-        VerifyingKey(PublicKey::from_secret_key(&secp, &self.0))
+        VerifyingKey(PublicKey::from_secret_key(&secp, &self.0 .0))
     }
 }
 
@@ -102,24 +168,175 @@ impl crate::Verifier for VerifyingKey {
         message: &T,
         signature: &Signature,
     ) -> Result<(), SignatureError> {
-        if signature.scheme != SignatureScheme::Secp256k1 {
-            return Err(Error::InvalidSignatureScheme)?;
-        }
-
-        let secp = &SECP256K1_VERIFY;
         let message_bytes =
             bincode::serialize(message).map_err(|error| Error::Verify(error.into()))?;
-        let digest = Sha256::digest(message_bytes);
-        let msg =
-            Message::from_digest_slice(&digest).map_err(|error| Error::Verify(error.into()))?;
-        let sig = EcdsaSignature::from_compact(&signature.bytes)
+        self.verify_bytes(&message_bytes, MessageEncoding::Bincode, signature)
+    }
+
+    fn verify_raw(&self, message: &[u8], signature: &Signature) -> Result<(), SignatureError> {
+        self.verify_bytes(message, MessageEncoding::Raw, signature)
+    }
+
+    fn verify_prehashed(
+        &self,
+        digest: &[u8; 32],
+        signature: &Signature,
+    ) -> Result<(), SignatureError> {
+        self.verify_digest(digest, MessageEncoding::Prehashed, signature)
+    }
+
+    /// Hashes the messages in parallel, then verifies the signatures with the shared verification
+    /// context.
+    fn verify_batch<T: Serialize>(
+        items: &[(&Self, &T, &Signature)],
+    ) -> Result<Vec<bool>, SignatureError> {
+        let messages = items
+            .iter()
+            .map(|(_, message, signature)| {
+                bincode::serialize(message).map(|bytes| (signature.digest, bytes))
+            })
+            .collect::<Result<Vec<_>, _>>()
             .map_err(|error| Error::Verify(error.into()))?;
+        let digests = digest_in_parallel(&messages);
 
-        secp.verify_ecdsa(&msg, &sig, &self.0).map_err(|e| e.into())
+        let secp = &SECP256K1_VERIFY;
+        Ok(items
+            .iter()
+            .zip(digests)
+            .map(|((key, _, signature), digest)| {
+                signature.scheme == SignatureScheme::Secp256k1
+                    && signature.encoding == MessageEncoding::Bincode
+                    && Message::from_digest_slice(&digest)
+                        .and_then(|msg| {
+                            let sig = parse_signature(&signature.bytes)?;
+                            secp.verify_ecdsa(&msg, &sig, &key.0)
+                        })
+                        .is_ok()
+            })
+            .collect())
+    }
+}
+
+/// Checks that the signature is a secp256k1 one over a message of the given encoding.
+fn check_signature(signature: &Signature, encoding: MessageEncoding) -> Result<(), Error> {
+    if signature.scheme != SignatureScheme::Secp256k1 {
+        return Err(Error::InvalidSignatureScheme);
     }
+    if signature.encoding != encoding {
+        return Err(Error::InvalidMessageEncoding);
+    }
+    Ok(())
+}
+
+/// Parses a signature with or without its trailing recovery id, 64 bytes signatures being the
+/// ones produced before signatures became recoverable.
+fn parse_signature(bytes: &[u8]) -> Result<EcdsaSignature, secp256k1::Error> {
+    match bytes.len() {
+        65 => EcdsaSignature::from_compact(&bytes[..64]),
+        _ => EcdsaSignature::from_compact(bytes),
+    }
+}
+
+/// Below this number of messages, hashing them is cheaper than spawning threads.
+const MIN_PARALLEL_MESSAGES: usize = 64;
+
+fn digest_in_parallel(messages: &[(DigestKind, Vec<u8>)]) -> Vec<[u8; 32]> {
+    let digest = |(digest, message): &(DigestKind, Vec<u8>)| digest.digest(message);
+    if messages.len() < MIN_PARALLEL_MESSAGES {
+        return messages.iter().map(digest).collect();
+    }
+
+    let threads = std::thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    let chunk_size = messages.len().div_ceil(threads);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = messages
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(digest).collect::<Vec<_>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+            })
+            .collect()
+    })
 }
 
 impl VerifyingKey {
+    fn verify_bytes(
+        &self,
+        message_bytes: &[u8],
+        encoding: MessageEncoding,
+        signature: &Signature,
+    ) -> Result<(), SignatureError> {
+        self.verify_digest(&signature.digest.digest(message_bytes), encoding, signature)
+    }
+
+    fn verify_digest(
+        &self,
+        digest: &[u8; 32],
+        encoding: MessageEncoding,
+        signature: &Signature,
+    ) -> Result<(), SignatureError> {
+        check_signature(signature, encoding)?;
+        signature.check_len()?;
+
+        let secp = &SECP256K1_VERIFY;
+        let msg =
+            Message::from_digest_slice(digest).map_err(|error| Error::Verify(error.into()))?;
+        let sig = parse_signature(&signature.bytes).map_err(|error| Error::Verify(error.into()))?;
+
+        secp.verify_ecdsa(&msg, &sig, &self.0).map_err(|e| e.into())
+    }
+
+    /// Recovers the key that produced the signature over `message`, which only succeeds for
+    /// signatures carrying their recovery id.
+    pub fn recover<T: Serialize>(
+        message: &T,
+        signature: &Signature,
+    ) -> Result<VerifyingKey, SignatureError> {
+        let message_bytes =
+            bincode::serialize(message).map_err(|error| Error::Recover(error.into()))?;
+        Self::recover_bytes(&message_bytes, MessageEncoding::Bincode, signature)
+    }
+
+    /// Recovers the key that produced a signature of [`crate::Signer::sign_raw`].
+    pub fn recover_raw(
+        message: &[u8],
+        signature: &Signature,
+    ) -> Result<VerifyingKey, SignatureError> {
+        Self::recover_bytes(message, MessageEncoding::Raw, signature)
+    }
+
+    fn recover_bytes(
+        message_bytes: &[u8],
+        encoding: MessageEncoding,
+        signature: &Signature,
+    ) -> Result<VerifyingKey, SignatureError> {
+        check_signature(signature, encoding)?;
+        signature.check_len()?;
+        let [compact @ .., recovery_id] = signature.bytes.as_slice() else {
+            return Err(Error::Recover(ErrorKind::MissingRecoveryId))?;
+        };
+        if compact.len() != 64 {
+            return Err(Error::Recover(ErrorKind::MissingRecoveryId))?;
+        }
+
+        let digest = signature.digest.digest(message_bytes);
+        let msg =
+            Message::from_digest_slice(&digest).map_err(|error| Error::Recover(error.into()))?;
+        let sig = RecoveryId::from_i32(i32::from(*recovery_id))
+            .and_then(|recovery_id| RecoverableSignature::from_compact(compact, recovery_id))
+            .map_err(|error| Error::Recover(error.into()))?;
+
+        let public_key = SECP256K1_VERIFY
+            .recover_ecdsa(&msg, &sig)
+            .map_err(|error| Error::Recover(error.into()))?;
+        Ok(Self(public_key))
+    }
+
     pub fn to_address(&self) -> String {
         let publick_key_byte = PublicKey::serialize_uncompressed(&self.0);
 
@@ -143,8 +360,12 @@ pub enum Error {
     CreateVerifyingKey(ErrorKind),
     #[error("Failed to verify the message: {0}")]
     Verify(ErrorKind),
+    #[error("Failed to recover the verifying key: {0}")]
+    Recover(ErrorKind),
     #[error("Invalid signature scheme")]
     InvalidSignatureScheme,
+    #[error("The signature is over a message of another encoding")]
+    InvalidMessageEncoding,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -155,6 +376,8 @@ pub enum ErrorKind {
     Bincode(#[from] bincode::Error),
     #[error("{0}")]
     InvalidHex(hex::FromHexError),
+    #[error("The signature does not carry a recovery id")]
+    MissingRecoveryId,
 }
 
 #[cfg(test)]
@@ -298,22 +521,56 @@ mod test {
         let invalid_signature_short = Signature {
             bytes: vec![0u8; 32], // Too short (32 bytes instead of 64)
             scheme: SignatureScheme::Secp256k1,
+            digest: DigestKind::Sha256,
+            encoding: MessageEncoding::Bincode,
         };
         let result = verifying_key.verify(msg, &invalid_signature_short);
-        assert!(result.is_err());
+        assert!(matches!(
+            result,
+            Err(SignatureError::InvalidLength {
+                expected: 65,
+                got: 32
+            })
+        ));
 
         // Test with invalid signature length (too long)
         let invalid_signature_long = Signature {
             bytes: vec![0u8; 128], // Too long
             scheme: SignatureScheme::Secp256k1,
+            digest: DigestKind::Sha256,
+            encoding: MessageEncoding::Bincode,
         };
         let result = verifying_key.verify(msg, &invalid_signature_long);
-        assert!(result.is_err());
+        assert!(matches!(
+            result,
+            Err(SignatureError::InvalidLength {
+                expected: 65,
+                got: 128
+            })
+        ));
+
+        // The raw path checks the length the same way
+        let result = verifying_key.verify_raw(
+            msg,
+            &Signature {
+                encoding: MessageEncoding::Raw,
+                ..invalid_signature_long
+            },
+        );
+        assert!(matches!(
+            result,
+            Err(SignatureError::InvalidLength {
+                expected: 65,
+                got: 128
+            })
+        ));
 
         // Test with invalid signature content (all zeros with correct length)
         let invalid_signature_zeros = Signature {
             bytes: vec![0u8; 64], // All zeros - invalid signature
             scheme: SignatureScheme::Secp256k1,
+            digest: DigestKind::Sha256,
+            encoding: MessageEncoding::Bincode,
         };
         let result = verifying_key.verify(msg, &invalid_signature_zeros);
         assert!(result.is_err());
@@ -322,6 +579,8 @@ mod test {
         let invalid_signature_max = Signature {
             bytes: vec![255u8; 64], // All 255s - invalid signature
             scheme: SignatureScheme::Secp256k1,
+            digest: DigestKind::Sha256,
+            encoding: MessageEncoding::Bincode,
         };
         let result = verifying_key.verify(msg, &invalid_signature_max);
         assert!(result.is_err());
@@ -387,6 +646,27 @@ mod test {
         let _decoded = hex::decode(&address1).expect("Address should be valid hex");
     }
 
+    #[test]
+    fn test_secp256k1_address_helper() {
+        let verifying_key = SigningKey::from_str(ANVIL_ACC0_KEY)
+            .unwrap()
+            .verifying_key();
+        assert_eq!(
+            crate::address(&verifying_key),
+            "f39fd6e51aad88f6f4ce6ab8827279cfffb92266"
+        );
+    }
+
+    #[test]
+    fn test_secp256k1_debug_hides_private_key() {
+        let signing_key = SigningKey::from_str(ANVIL_ACC0_KEY).unwrap();
+        let debug = format!("{signing_key:?} {signing_key:#?}");
+
+        assert!(!debug.to_lowercase().contains(ANVIL_ACC0_KEY));
+        assert!(!debug.contains("172, 9, 116"));
+        assert!(debug.contains("f39fd6e51aad88f6f4ce6ab8827279cfffb92266"));
+    }
+
     #[test]
     fn test_secp256k1_wrong_private_key_range() {
         // Test with private key that's too large for secp256k1 (> curve order)
@@ -395,4 +675,173 @@ mod test {
         let result = SigningKey::from_str(invalid_key_str);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_secp256k1_recover_verifying_key() {
+        let signing_key = SigningKey::from_str(ANVIL_ACC0_KEY).unwrap();
+        let msg = b"Hello World";
+
+        let signature = signing_key.sign(msg).unwrap();
+        assert_eq!(signature.bytes.len(), 65);
+        let recovered = VerifyingKey::recover(msg, &signature).unwrap();
+        assert_eq!(
+            recovered.to_address(),
+            signing_key.verifying_key().to_address()
+        );
+
+        let recovered = VerifyingKey::recover(b"Hello World!", &signature).unwrap();
+        assert_ne!(
+            recovered.to_address(),
+            signing_key.verifying_key().to_address()
+        );
+    }
+
+    #[test]
+    fn test_secp256k1_compact_signature_is_still_verified() {
+        let signing_key = SigningKey::from_str(ANVIL_ACC0_KEY).unwrap();
+        let verifying_key = signing_key.verifying_key();
+        let msg = b"Hello World";
+
+        let mut signature = signing_key.sign(msg).unwrap();
+        signature.bytes.truncate(64);
+        assert!(verifying_key.verify(msg, &signature).is_ok());
+        assert!(VerifyingKey::verify_batch(&[(&verifying_key, msg, &signature)]).unwrap()[0]);
+        // Without the recovery id, the key has to be transmitted along.
+        assert!(VerifyingKey::recover(msg, &signature).is_err());
+    }
+
+    /// The anvil 0 account signing `Hello World` with each digest. The signatures are the
+    /// deterministic RFC 6979 ones, `r || s || v` with `v` the recovery id.
+    const HELLO_WORLD_VECTORS: [(DigestKind, &str, &str); 2] = [
+        (
+            DigestKind::Sha256,
+            "a591a6d40bf420404a011733cfb7b190d62c65bf0bcda32b57b277d9ad9f146e",
+            "3104cc32c3b355a9af9e5fef67c55b53a1ab8fa6b757f15e19cc3d34dc4a38cb5ac648b986926c58b17ef3c7214f8493568a41eebf0b47b76789a7bea3b2770d01",
+        ),
+        (
+            DigestKind::Keccak256,
+            "592fa743889fc7f92ac2a37bb1f5ba1daf2a5c84741ca0e0061d243a2e6707ba",
+            "b7cd0753e55396897c7efed41c0687c471d6ad1ae2b222629f88cb76e39313aa348c1fdf3a40342a2bc0f6c596d7a6bfcabb4ce4b522ca88c89eca61def48a9401",
+        ),
+    ];
+
+    #[test]
+    fn test_secp256k1_digest_vectors() {
+        // A byte array is serialized by bincode as is, so the digest is the one of `Hello World`.
+        let msg = b"Hello World";
+        for (digest, expected_digest, expected_signature) in HELLO_WORLD_VECTORS {
+            assert_eq!(hex::encode(digest.digest(msg)), expected_digest);
+
+            let signing_key = SigningKey::from_str(ANVIL_ACC0_KEY)
+                .unwrap()
+                .with_digest(digest);
+            let signature = signing_key.sign(msg).unwrap();
+            assert_eq!(signature.digest, digest);
+            assert_eq!(hex::encode(&signature.bytes), expected_signature);
+
+            let recovered = VerifyingKey::recover(msg, &signature).unwrap();
+            assert_eq!(
+                recovered.to_address(),
+                "f39fd6e51aad88f6f4ce6ab8827279cfffb92266"
+            );
+            assert!(signing_key.verifying_key().verify(msg, &signature).is_ok());
+        }
+    }
+
+    /// The raw encoding signs the bytes as they are: `"Hello World"` hashes to the digests of
+    /// [`HELLO_WORLD_VECTORS`], whereas bincode prefixes a string with its 8 bytes length.
+    #[test]
+    fn test_secp256k1_raw_message_vectors() {
+        let msg = "Hello World";
+        for (digest, _, expected_signature) in HELLO_WORLD_VECTORS {
+            let signing_key = SigningKey::from_str(ANVIL_ACC0_KEY)
+                .unwrap()
+                .with_digest(digest);
+            let verifying_key = signing_key.verifying_key();
+
+            let signature = signing_key.sign_raw(msg.as_bytes()).unwrap();
+            assert_eq!(signature.encoding, MessageEncoding::Raw);
+            assert_eq!(hex::encode(&signature.bytes), expected_signature);
+            assert!(verifying_key.verify_raw(msg.as_bytes(), &signature).is_ok());
+            assert_eq!(
+                VerifyingKey::recover_raw(msg.as_bytes(), &signature)
+                    .unwrap()
+                    .to_address(),
+                verifying_key.to_address()
+            );
+            // The encodings are not interchangeable.
+            assert!(verifying_key.verify(&msg, &signature).is_err());
+            assert!(verifying_key.verify(&msg.as_bytes(), &signature).is_err());
+
+            let bincode_signature = signing_key.sign(&msg).unwrap();
+            assert_ne!(bincode_signature.bytes, signature.bytes);
+            assert!(verifying_key
+                .verify_raw(msg.as_bytes(), &bincode_signature)
+                .is_err());
+        }
+    }
+
+    /// Signing the SHA-256 digest of `Hello World` as is gives the signature of
+    /// [`HELLO_WORLD_VECTORS`], whereas signing it as a serialized message hashes it a second
+    /// time.
+    #[test]
+    fn test_secp256k1_prehashed_vectors() {
+        let (_, expected_digest, expected_signature) = HELLO_WORLD_VECTORS[0];
+        let digest: [u8; 32] = hex::decode(expected_digest).unwrap().try_into().unwrap();
+        let signing_key = SigningKey::from_str(ANVIL_ACC0_KEY).unwrap();
+        let verifying_key = signing_key.verifying_key();
+
+        let signature = signing_key.sign_prehashed(&digest).unwrap();
+        assert_eq!(signature.encoding, MessageEncoding::Prehashed);
+        assert_eq!(hex::encode(&signature.bytes), expected_signature);
+        assert!(verifying_key.verify_prehashed(&digest, &signature).is_ok());
+        assert!(verifying_key
+            .verify_prehashed(&[0u8; 32], &signature)
+            .is_err());
+
+        // The double hashed path signs another message, and neither verifies as the other.
+        let double_hashed = signing_key.sign(&digest).unwrap();
+        assert_ne!(double_hashed.bytes, signature.bytes);
+        assert!(verifying_key.verify(&digest, &signature).is_err());
+        assert!(verifying_key
+            .verify_prehashed(&digest, &double_hashed)
+            .is_err());
+        assert!(verifying_key.verify_raw(&digest, &signature).is_err());
+    }
+
+    #[test]
+    fn test_secp256k1_verify_with_other_digest_fails() {
+        let signing_key = SigningKey::from_str(ANVIL_ACC0_KEY)
+            .unwrap()
+            .with_digest(DigestKind::Keccak256);
+        let msg = b"Hello World";
+
+        let mut signature = signing_key.sign(msg).unwrap();
+        signature.digest = DigestKind::Sha256;
+        assert!(signing_key.verifying_key().verify(msg, &signature).is_err());
+    }
+
+    #[test]
+    fn test_secp256k1_verify_batch() {
+        let signing_key = SigningKey::from_str(ANVIL_ACC0_KEY).unwrap();
+        let verifying_key = signing_key.verifying_key();
+        // Enough messages to hash them in parallel.
+        let messages: Vec<String> = (0..100).map(|index| format!("block {index}")).collect();
+        let mut signatures: Vec<_> = messages
+            .iter()
+            .map(|message| signing_key.sign(message).unwrap())
+            .collect();
+        signatures[3].bytes[0] ^= 1;
+        signatures[7].scheme = SignatureScheme::Ed25519;
+
+        let items: Vec<_> = messages
+            .iter()
+            .zip(&signatures)
+            .map(|(message, signature)| (&verifying_key, message, signature))
+            .collect();
+        let results = VerifyingKey::verify_batch(&items).unwrap();
+
+        let expected: Vec<bool> = (0..100).map(|index| index != 3 && index != 7).collect();
+        assert_eq!(results, expected);
+    }
 }
@@ -1,13 +1,28 @@
-use crate::{Signature, SignatureError, SignatureScheme};
+use crate::{DigestKind, MessageEncoding, Signature, SignatureError, SignatureScheme};
 use ed25519_dalek::{
     Signature as EddsaSignature, Signer, SigningKey as PrivateKey, Verifier,
     VerifyingKey as PublicKey,
 };
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
-
-#[derive(Clone, Debug)]
-pub struct SigningKey(PrivateKey);
+use tiny_keccak::{Hasher, Keccak};
+use zeroize::ZeroizeOnDrop;
+
+#[derive(Clone)]
+pub struct SigningKey(SecretKey);
+
+/// Private key erased from memory once dropped.
+#[derive(Clone, ZeroizeOnDrop)]
+struct SecretKey(PrivateKey);
+
+/// Only shows the address of the key, never the private key itself.
+impl std::fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("address", &self.verifying_key().to_address())
+            .finish_non_exhaustive()
+    }
+}
 
 impl FromStr for SigningKey {
     type Err = SignatureError;
@@ -17,7 +32,7 @@ impl FromStr for SigningKey {
         let bytes = hex::decode(s).map_err(|error| Error::CreateSigningKey(error.into()))?;
         let secret_key = PrivateKey::try_from(bytes.as_slice())
             .map_err(|error| Error::CreateSigningKey(error.into()))?;
-        Ok(Self(secret_key))
+        Ok(Self(SecretKey(secret_key)))
     }
 }
 
@@ -25,23 +40,37 @@ impl super::Signer for SigningKey {
     fn from_slice(slice: &[u8]) -> Result<Self, SignatureError> {
         let secret_key =
             PrivateKey::try_from(slice).map_err(|error| Error::CreateSigningKey(error.into()))?;
-        Ok(Self(secret_key))
+        Ok(Self(SecretKey(secret_key)))
     }
 
     fn sign<T: Serialize>(&self, message: &T) -> Result<Signature, SignatureError> {
         let message_bytes =
             bincode::serialize(message).map_err(|error| Error::Sign(error.into()))?;
-        let signature = self.0.sign(&message_bytes);
-        Ok(Signature {
-            bytes: signature.to_vec(),
-            scheme: SignatureScheme::Ed25519,
-        })
+        Ok(self.sign_bytes(&message_bytes, MessageEncoding::Bincode))
+    }
+
+    fn sign_raw(&self, message: &[u8]) -> Result<Signature, SignatureError> {
+        Ok(self.sign_bytes(message, MessageEncoding::Raw))
+    }
+
+    fn sign_prehashed(&self, digest: &[u8; 32]) -> Result<Signature, SignatureError> {
+        Ok(self.sign_bytes(digest, MessageEncoding::Prehashed))
     }
 }
 
 impl SigningKey {
+    fn sign_bytes(&self, message_bytes: &[u8], encoding: MessageEncoding) -> Signature {
+        let signature = self.0 .0.sign(message_bytes);
+        Signature {
+            bytes: signature.to_vec(),
+            scheme: SignatureScheme::Ed25519,
+            digest: DigestKind::default(),
+            encoding,
+        }
+    }
+
     pub fn verifying_key(&self) -> VerifyingKey {
-        VerifyingKey(PublicKey::from(&self.0))
+        VerifyingKey(PublicKey::from(&self.0 .0))
     }
 }
 
@@ -86,26 +115,58 @@ impl crate::Verifier for VerifyingKey {
         &self,
         message: &T,
         signature: &Signature,
+    ) -> Result<(), SignatureError> {
+        let message_bytes =
+            bincode::serialize(message).map_err(|error| Error::Verify(error.into()))?;
+        self.verify_bytes(&message_bytes, MessageEncoding::Bincode, signature)
+    }
+
+    fn verify_raw(&self, message: &[u8], signature: &Signature) -> Result<(), SignatureError> {
+        self.verify_bytes(message, MessageEncoding::Raw, signature)
+    }
+
+    fn verify_prehashed(
+        &self,
+        digest: &[u8; 32],
+        signature: &Signature,
+    ) -> Result<(), SignatureError> {
+        self.verify_bytes(digest, MessageEncoding::Prehashed, signature)
+    }
+}
+
+impl VerifyingKey {
+    fn verify_bytes(
+        &self,
+        message_bytes: &[u8],
+        encoding: MessageEncoding,
+        signature: &Signature,
     ) -> Result<(), SignatureError> {
         if signature.scheme != SignatureScheme::Ed25519 {
             return Err(Error::InvalidSignatureScheme)?;
         }
+        if signature.encoding != encoding {
+            return Err(Error::InvalidMessageEncoding)?;
+        }
+        signature.check_len()?;
 
-        let message_bytes =
-            bincode::serialize(message).map_err(|error| Error::Verify(error.into()))?;
         let signature = EddsaSignature::from_slice(&signature.bytes)
             .map_err(|error| Error::Verify(error.into()))?;
 
-        match self.0.verify(&message_bytes, &signature) {
+        match self.0.verify(message_bytes, &signature) {
             Ok(()) => Ok(()),
             Err(error) => Err(Error::Verify(ErrorKind::Ed25519(error)).into()),
         }
     }
-}
 
-impl VerifyingKey {
+    /// Derives a 20 bytes address the way secp256k1 keys do: the last 20 bytes of the Keccak256
+    /// of the 32 bytes public key, hex encoded.
     pub fn to_address(&self) -> String {
-        String::from(self.clone())
+        let mut hasher = Keccak::v256();
+        hasher.update(self.0.as_bytes());
+        let mut hash = [0u8; 32];
+        hasher.finalize(&mut hash);
+
+        hex::encode(&hash[12..32])
     }
 }
 
@@ -121,6 +182,8 @@ pub enum Error {
     Verify(ErrorKind),
     #[error("Invalid signature scheme")]
     InvalidSignatureScheme,
+    #[error("The signature is over a message of another encoding")]
+    InvalidMessageEncoding,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -320,22 +383,40 @@ mod tests {
         let invalid_signature_short = Signature {
             bytes: vec![0u8; 32], // Too short (32 bytes instead of 64)
             scheme: SignatureScheme::Ed25519,
+            digest: DigestKind::default(),
+            encoding: MessageEncoding::Bincode,
         };
         let result = verifying_key.verify(msg, &invalid_signature_short);
-        assert!(result.is_err());
+        assert!(matches!(
+            result,
+            Err(SignatureError::InvalidLength {
+                expected: 64,
+                got: 32
+            })
+        ));
 
         // Test with invalid signature length (too long)
         let invalid_signature_long = Signature {
             bytes: vec![0u8; 128], // Too long
             scheme: SignatureScheme::Ed25519,
+            digest: DigestKind::default(),
+            encoding: MessageEncoding::Bincode,
         };
         let result = verifying_key.verify(msg, &invalid_signature_long);
-        assert!(result.is_err());
+        assert!(matches!(
+            result,
+            Err(SignatureError::InvalidLength {
+                expected: 64,
+                got: 128
+            })
+        ));
 
         // Test with invalid signature content (all zeros)
         let invalid_signature_zeros = Signature {
             bytes: vec![0u8; 64], // All zeros - invalid signature
             scheme: SignatureScheme::Ed25519,
+            digest: DigestKind::default(),
+            encoding: MessageEncoding::Bincode,
         };
         let result = verifying_key.verify(msg, &invalid_signature_zeros);
         assert!(result.is_err()); // Signature parses, but is invalid -> verify returns Err
@@ -384,4 +465,78 @@ mod tests {
         let result: Result<VerifyingKey, _> = serde_json::from_str(wrong_length_json);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_ed25519_sign_and_verify_raw() {
+        let signing_key = SigningKey::from_slice(&PRIVATE_KEY).unwrap();
+        let verifying_key = signing_key.verifying_key();
+        let msg = "Hello World";
+
+        let signature = signing_key.sign_raw(msg.as_bytes()).unwrap();
+        assert_eq!(signature.encoding, MessageEncoding::Raw);
+        assert!(verifying_key.verify_raw(msg.as_bytes(), &signature).is_ok());
+        assert!(verifying_key.verify(&msg, &signature).is_err());
+        assert!(verifying_key
+            .verify_raw(msg.as_bytes(), &signing_key.sign(&msg).unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_ed25519_sign_and_verify_prehashed() {
+        let signing_key = SigningKey::from_slice(&PRIVATE_KEY).unwrap();
+        let verifying_key = signing_key.verifying_key();
+        let digest = [7u8; 32];
+
+        let signature = signing_key.sign_prehashed(&digest).unwrap();
+        assert_eq!(signature.encoding, MessageEncoding::Prehashed);
+        assert!(verifying_key.verify_prehashed(&digest, &signature).is_ok());
+        assert!(verifying_key.verify_raw(&digest, &signature).is_err());
+        assert!(verifying_key.verify(&digest, &signature).is_err());
+
+        let double_hashed = signing_key.sign(&digest).unwrap();
+        assert!(verifying_key
+            .verify_prehashed(&digest, &double_hashed)
+            .is_err());
+    }
+
+    #[test]
+    fn test_ed25519_debug_hides_private_key() {
+        let signing_key = SigningKey::from_slice(&PRIVATE_KEY).unwrap();
+        let debug = format!("{signing_key:?} {signing_key:#?}");
+
+        assert!(!debug.contains(&hex::encode(PRIVATE_KEY)));
+        assert!(!debug.contains("144, 45, 220"));
+        assert!(debug.contains(&signing_key.verifying_key().to_address()));
+    }
+
+    #[test]
+    fn test_ed25519_address() {
+        let verifying_key = SigningKey::from_slice(&PRIVATE_KEY)
+            .unwrap()
+            .verifying_key();
+
+        let address = verifying_key.to_address();
+        assert_eq!(address, verifying_key.to_address());
+        assert_eq!(address.len(), 40);
+        assert!(hex::decode(&address).is_ok());
+
+        let other_key = SigningKey::from_slice(&[1u8; 32]).unwrap().verifying_key();
+        assert_ne!(other_key.to_address(), address);
+    }
+
+    #[test]
+    fn test_ed25519_verify_batch() {
+        let signing_key = SigningKey::from_slice(&PRIVATE_KEY).unwrap();
+        let verifying_key = signing_key.verifying_key();
+        let valid = signing_key.sign(b"Hello World").unwrap();
+        let mut corrupted = valid.clone();
+        corrupted.bytes[0] ^= 1;
+
+        let results = VerifyingKey::verify_batch(&[
+            (&verifying_key, b"Hello World", &valid),
+            (&verifying_key, b"Hello World", &corrupted),
+        ])
+        .unwrap();
+        assert_eq!(results, vec![true, false]);
+    }
 }
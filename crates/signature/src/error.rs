@@ -1,5 +1,21 @@
+use crate::SignatureScheme;
+
 #[derive(Debug, thiserror::Error)]
 pub enum SignatureError {
+    #[error("Signature scheme {0:?} is not enabled in this build")]
+    UnsupportedScheme(SignatureScheme),
+    #[error("Unknown signature scheme {0}, expected ed25519, secp256k1 or bls")]
+    UnknownScheme(String),
+    #[error("Invalid signature threshold {threshold} for {keys} distinct keys")]
+    InvalidThreshold { threshold: usize, keys: usize },
+    #[error("Not signed by {threshold} of the {keys} expected keys")]
+    ThresholdNotReached { threshold: usize, keys: usize },
+    #[error("Invalid signature length: expected {expected} bytes, got {got}")]
+    InvalidLength { expected: usize, got: usize },
+    #[error("Invalid signature format: {0}")]
+    InvalidFormat(String),
+    #[error("The verifying key cannot be recovered from a {0:?} signature")]
+    UnrecoverableScheme(SignatureScheme),
     #[cfg(feature = "secp256k1")]
     #[error("{0}")]
     Ecdsa(#[from] crate::ecdsa::Error),
@@ -12,4 +28,7 @@ pub enum SignatureError {
     #[cfg(feature = "ed25519")]
     #[error("ed25519 signature verification failed")]
     Ed25519(#[from] ed25519_dalek::SignatureError),
+    #[cfg(feature = "bls")]
+    #[error("{0}")]
+    Bls(#[from] crate::bls::Error),
 }
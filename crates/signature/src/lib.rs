@@ -1,10 +1,16 @@
+mod any;
+#[cfg(feature = "bls")]
+pub mod bls;
 #[cfg(feature = "secp256k1")]
 pub mod ecdsa;
 #[cfg(feature = "ed25519")]
 pub mod eddsa;
 mod error;
+mod threshold;
 
+pub use any::{AnySigningKey, AnyVerifyingKey};
 pub use error::SignatureError;
+pub use threshold::ThresholdVerifier;
 
 cfg_if::cfg_if! {
     if #[cfg(feature = "secp256k1")] {
@@ -14,13 +20,29 @@ cfg_if::cfg_if! {
     }
 }
 
+/// Address of a verifying key of the default scheme, the last 20 bytes of the Keccak256 of its
+/// public key, hex encoded. Secp256k1 and ed25519 keys derive their addresses the same way.
+#[cfg(any(feature = "secp256k1", feature = "ed25519"))]
+pub fn address(key: &VerifyingKey) -> String {
+    key.to_address()
+}
+
 use serde::{Deserialize, Serialize};
-use std::str::FromStr;
+use sha2::{Digest, Sha256};
+use std::{fmt, str::FromStr};
+use tiny_keccak::{Hasher, Keccak};
 
 pub trait Signer: FromStr<Err = SignatureError> + Sized {
     fn from_slice(slice: &[u8]) -> Result<Self, SignatureError>;
 
     fn sign<T: Serialize>(&self, message: &T) -> Result<Signature, SignatureError>;
+
+    /// Signs the bytes of `message` as they are, see [`MessageEncoding::Raw`].
+    fn sign_raw(&self, message: &[u8]) -> Result<Signature, SignatureError>;
+
+    /// Signs a message that is already a 32 bytes digest, e.g. a block hash, without serializing
+    /// and hashing it again, see [`MessageEncoding::Prehashed`].
+    fn sign_prehashed(&self, digest: &[u8; 32]) -> Result<Signature, SignatureError>;
 }
 
 pub trait Verifier:
@@ -33,18 +55,363 @@ pub trait Verifier:
         message: &T,
         signature: &Signature,
     ) -> Result<(), SignatureError>;
+
+    /// Verifies a signature produced by [`Signer::sign_raw`] over the bytes of `message`.
+    fn verify_raw(&self, message: &[u8], signature: &Signature) -> Result<(), SignatureError>;
+
+    /// Verifies a signature produced by [`Signer::sign_prehashed`] over `digest`.
+    fn verify_prehashed(
+        &self,
+        digest: &[u8; 32],
+        signature: &Signature,
+    ) -> Result<(), SignatureError>;
+
+    /// Verifies every `(key, message, signature)` item and returns whether each one is valid, in
+    /// the order of the items. Schemes may override it to verify a run of signatures faster than
+    /// one by one.
+    fn verify_batch<T: Serialize>(
+        items: &[(&Self, &T, &Signature)],
+    ) -> Result<Vec<bool>, SignatureError> {
+        Ok(items
+            .iter()
+            .map(|(key, message, signature)| key.verify(*message, signature).is_ok())
+            .collect())
+    }
 }
 
+/// Serialized as a short tag, the variant names being accepted as well for the signatures
+/// serialized before.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum SignatureScheme {
+    #[serde(rename = "ed25519", alias = "Ed25519")]
     Ed25519,
+    #[serde(rename = "ecdsa", alias = "Secp256k1")]
     Secp256k1,
+    #[serde(rename = "bls", alias = "Bls")]
+    Bls,
+}
+
+impl SignatureScheme {
+    /// The short tag of the scheme, as serialized.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ed25519 => "ed25519",
+            Self::Secp256k1 => "ecdsa",
+            Self::Bls => "bls",
+        }
+    }
+
+    /// Length in bytes of the signatures of the scheme. Secp256k1 signatures carry their
+    /// recovery id after the 64 bytes `r || s`, see [`Signature::bytes`].
+    pub fn expected_signature_len(self) -> usize {
+        match self {
+            Self::Ed25519 => 64,
+            Self::Secp256k1 => 65,
+            Self::Bls => 96,
+        }
+    }
+
+    /// Whether the verifying key can be recovered from a signature and the signed message, in
+    /// which case it does not need to be transmitted along with the signature.
+    pub fn is_recoverable(self) -> bool {
+        matches!(self, Self::Secp256k1)
+    }
 }
 
+/// Formats the scheme the way it is given in the configuration, e.g. `secp256k1`.
+impl fmt::Display for SignatureScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Ed25519 => "ed25519",
+            Self::Secp256k1 => "secp256k1",
+            Self::Bls => "bls",
+        })
+    }
+}
+
+/// Parses a scheme regardless of the case, either as formatted by [`fmt::Display`] or as its
+/// short tag, see [`SignatureScheme::as_str`].
+impl FromStr for SignatureScheme {
+    type Err = SignatureError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ed25519" => Ok(Self::Ed25519),
+            "secp256k1" | "ecdsa" => Ok(Self::Secp256k1),
+            "bls" => Ok(Self::Bls),
+            _ => Err(SignatureError::UnknownScheme(s.to_owned())),
+        }
+    }
+}
+
+/// Hash function the secp256k1 scheme applies to the bincode serialized message before signing
+/// it. Ed25519 and BLS hash the message as part of their own algorithm and ignore it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum DigestKind {
+    #[default]
+    Sha256,
+    /// The hash used by Ethereum, so that external clients can recover the signer with
+    /// `ecrecover` from `keccak256(message)` and the `r`, `s` and `v` of the signature.
+    Keccak256,
+}
+
+impl DigestKind {
+    pub fn digest(self, bytes: &[u8]) -> [u8; 32] {
+        match self {
+            Self::Sha256 => Sha256::digest(bytes).into(),
+            Self::Keccak256 => {
+                let mut hasher = Keccak::v256();
+                hasher.update(bytes);
+                let mut hash = [0u8; 32];
+                hasher.finalize(&mut hash);
+                hash
+            }
+        }
+    }
+}
+
+/// How the signed message was turned into the bytes that were hashed and signed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum MessageEncoding {
+    /// The bincode serialization of the message, which depends on bincode's encoding of its Rust
+    /// type. Kept as the default for the signatures produced before encodings were selectable.
+    #[default]
+    Bincode,
+    /// The bytes supplied by the caller, used as they are. This is the encoding to use for
+    /// signatures that must stay valid across versions or be verified outside of Rust: e.g. a
+    /// secp256k1 signature with [`DigestKind::Keccak256`] signs `keccak256(message)`.
+    Raw,
+    /// A 32 bytes digest supplied by the caller, which secp256k1 signs without hashing it
+    /// again, ignoring [`Signature::digest`]. Ed25519 and BLS sign it as a raw message.
+    Prehashed,
+}
+
+/// Serialized with its bytes `0x` prefixed hex encoded, e.g.
+/// `{"bytes":"0x1b2c...","scheme":"ecdsa","digest":"Sha256","encoding":"Bincode"}`, the bytes of
+/// the signatures serialized before as an array being accepted as well.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Signature {
+    /// Raw signature, whose layout depends on the scheme:
+    /// - `Secp256k1`: 65 bytes recoverable ECDSA signature, `r || s || v` with `r` and `s` in big
+    ///   endian and `v` the recovery id. 64 bytes signatures without `v` are still verified.
+    /// - `Ed25519`: 64 bytes signature, `R || s` as defined by RFC 8032.
+    /// - `Bls`: 96 bytes compressed BLS12-381 G2 point, for a single or an aggregated signature.
+    #[serde(with = "hex_bytes")]
     pub bytes: Vec<u8>,
     pub scheme: SignatureScheme,
+    /// Signatures produced before the digest was selectable carry none and used SHA-256.
+    #[serde(default)]
+    pub digest: DigestKind,
+    #[serde(default)]
+    pub encoding: MessageEncoding,
+}
+
+impl Signature {
+    /// Checks the length of the bytes against the scheme before handing them to the crypto
+    /// library, accepting the 64 bytes secp256k1 signatures produced before they became
+    /// recoverable.
+    pub(crate) fn check_len(&self) -> Result<(), SignatureError> {
+        let expected = self.scheme.expected_signature_len();
+        let got = self.bytes.len();
+        let legacy = self.scheme == SignatureScheme::Secp256k1 && got == 64;
+        if got != expected && !legacy {
+            return Err(SignatureError::InvalidLength { expected, got });
+        }
+        Ok(())
+    }
+}
+
+/// Formats the signature as `<scheme>:<digest>:<encoding>:0x<bytes>`, e.g.
+/// `ecdsa:sha256:bincode:0x1b2c...`.
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digest = match self.digest {
+            DigestKind::Sha256 => "sha256",
+            DigestKind::Keccak256 => "keccak256",
+        };
+        let encoding = match self.encoding {
+            MessageEncoding::Bincode => "bincode",
+            MessageEncoding::Raw => "raw",
+            MessageEncoding::Prehashed => "prehashed",
+        };
+        write!(
+            f,
+            "{}:{digest}:{encoding}:0x{}",
+            self.scheme.as_str(),
+            hex::encode(&self.bytes)
+        )
+    }
+}
+
+/// Parses the [`Display`](fmt::Display) format, or `<scheme>:0x<bytes>` for the default digest
+/// and encoding.
+impl FromStr for Signature {
+    type Err = SignatureError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || SignatureError::InvalidFormat(s.to_owned());
+        let parts: Vec<&str> = s.split(':').collect();
+        let (scheme, digest, encoding, bytes) = match parts.as_slice() {
+            [scheme, bytes] => (
+                *scheme,
+                DigestKind::default(),
+                MessageEncoding::default(),
+                *bytes,
+            ),
+            [scheme, digest, encoding, bytes] => {
+                let digest = match *digest {
+                    "sha256" => DigestKind::Sha256,
+                    "keccak256" => DigestKind::Keccak256,
+                    _ => return Err(invalid()),
+                };
+                let encoding = match *encoding {
+                    "bincode" => MessageEncoding::Bincode,
+                    "raw" => MessageEncoding::Raw,
+                    "prehashed" => MessageEncoding::Prehashed,
+                    _ => return Err(invalid()),
+                };
+                (*scheme, digest, encoding, *bytes)
+            }
+            _ => return Err(invalid()),
+        };
+        let scheme = SignatureScheme::from_str(scheme).map_err(|_| invalid())?;
+        let bytes =
+            hex::decode(bytes.strip_prefix("0x").ok_or_else(invalid)?).map_err(|_| invalid())?;
+        Ok(Self {
+            bytes,
+            scheme,
+            digest,
+            encoding,
+        })
+    }
+}
+
+/// `0x` prefixed hex encoding of bytes, also deserializing them from an array of numbers.
+mod hex_bytes {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Bytes {
+            Hex(String),
+            Array(Vec<u8>),
+        }
+
+        match Bytes::deserialize(deserializer)? {
+            Bytes::Hex(hex) => {
+                let hex = hex
+                    .strip_prefix("0x")
+                    .ok_or_else(|| D::Error::custom("expected 0x prefixed hex bytes"))?;
+                hex::decode(hex).map_err(D::Error::custom)
+            }
+            Bytes::Array(bytes) => Ok(bytes),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signature() -> Signature {
+        Signature {
+            bytes: vec![0x1b, 0x2c, 0xff],
+            scheme: SignatureScheme::Secp256k1,
+            digest: DigestKind::Keccak256,
+            encoding: MessageEncoding::Raw,
+        }
+    }
+
+    #[test]
+    fn signature_serializes_bytes_as_hex() {
+        let json = serde_json::to_value(signature()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "bytes": "0x1b2cff",
+                "scheme": "ecdsa",
+                "digest": "Keccak256",
+                "encoding": "Raw",
+            })
+        );
+
+        let decoded: Signature = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.to_string(), signature().to_string());
+    }
+
+    #[test]
+    fn signature_deserializes_the_legacy_array_form() {
+        let json = r#"{"bytes":[27,44,255],"scheme":"Secp256k1"}"#;
+        let decoded: Signature = serde_json::from_str(json).unwrap();
+
+        assert_eq!(decoded.bytes, vec![0x1b, 0x2c, 0xff]);
+        assert_eq!(decoded.scheme, SignatureScheme::Secp256k1);
+        assert_eq!(decoded.digest, DigestKind::Sha256);
+        assert_eq!(decoded.encoding, MessageEncoding::Bincode);
+    }
+
+    #[test]
+    fn signature_scheme_from_str() {
+        for (s, scheme) in [
+            ("ed25519", SignatureScheme::Ed25519),
+            ("Ed25519", SignatureScheme::Ed25519),
+            ("secp256k1", SignatureScheme::Secp256k1),
+            ("SECP256K1", SignatureScheme::Secp256k1),
+            ("ecdsa", SignatureScheme::Secp256k1),
+            ("bls", SignatureScheme::Bls),
+        ] {
+            assert_eq!(SignatureScheme::from_str(s).unwrap(), scheme, "{s}");
+        }
+
+        for scheme in [
+            SignatureScheme::Ed25519,
+            SignatureScheme::Secp256k1,
+            SignatureScheme::Bls,
+        ] {
+            assert_eq!(
+                SignatureScheme::from_str(&scheme.to_string()).unwrap(),
+                scheme
+            );
+        }
+
+        for invalid in ["", "rsa", "secp256r1", "ed 25519"] {
+            assert!(matches!(
+                SignatureScheme::from_str(invalid),
+                Err(SignatureError::UnknownScheme(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn signature_display_round_trip() {
+        let signature = signature();
+        assert_eq!(signature.to_string(), "ecdsa:keccak256:raw:0x1b2cff");
+        let parsed = Signature::from_str(&signature.to_string()).unwrap();
+        assert_eq!(parsed.bytes, signature.bytes);
+        assert_eq!(parsed.scheme, signature.scheme);
+        assert_eq!(parsed.digest, signature.digest);
+        assert_eq!(parsed.encoding, signature.encoding);
+
+        let parsed = Signature::from_str("ed25519:0x1b2cff").unwrap();
+        assert_eq!(parsed.scheme, SignatureScheme::Ed25519);
+        assert_eq!(parsed.digest, DigestKind::Sha256);
+        assert_eq!(parsed.encoding, MessageEncoding::Bincode);
+
+        for invalid in [
+            "rsa:0x1b",
+            "ecdsa:1b2cff",
+            "ecdsa:0xzz",
+            "ecdsa:md5:raw:0x1b",
+            "0x1b",
+        ] {
+            assert!(Signature::from_str(invalid).is_err(), "{invalid}");
+        }
+    }
 }
 
 // #[cfg(test)]
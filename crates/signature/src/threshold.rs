@@ -0,0 +1,166 @@
+use crate::{AnyVerifyingKey, Signature, SignatureError};
+use serde::Serialize;
+
+/// Verifies that a message is signed by at least `threshold` of a set of known keys, e.g. by M
+/// of the N sequencers of the network. The keys may be of different schemes.
+#[derive(Clone, Debug)]
+pub struct ThresholdVerifier {
+    keys: Vec<AnyVerifyingKey>,
+    threshold: usize,
+}
+
+impl ThresholdVerifier {
+    /// Fails if `threshold` is 0 or larger than the number of distinct keys, a key listed twice
+    /// being only kept once.
+    pub fn new(keys: Vec<AnyVerifyingKey>, threshold: usize) -> Result<Self, SignatureError> {
+        let mut distinct_keys: Vec<AnyVerifyingKey> = Vec::with_capacity(keys.len());
+        for key in keys {
+            if !distinct_keys.iter().any(|known| same_key(known, &key)) {
+                distinct_keys.push(key);
+            }
+        }
+        if threshold == 0 || threshold > distinct_keys.len() {
+            return Err(SignatureError::InvalidThreshold {
+                threshold,
+                keys: distinct_keys.len(),
+            });
+        }
+        Ok(Self {
+            keys: distinct_keys,
+            threshold,
+        })
+    }
+
+    pub fn keys(&self) -> &[AnyVerifyingKey] {
+        &self.keys
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Returns whether `key` is one of the keys, whatever its encoding.
+    pub fn contains(&self, key: &AnyVerifyingKey) -> bool {
+        self.keys.iter().any(|known| same_key(known, key))
+    }
+
+    /// Returns whether at least `threshold` of the keys produced one of the signatures over
+    /// `message`. Each key counts once however many of the signatures it produced, and the
+    /// signatures of unknown keys are ignored.
+    pub fn verify_threshold<T: Serialize>(
+        &self,
+        message: &T,
+        signatures: &[Signature],
+    ) -> Result<bool, SignatureError> {
+        let signers = self
+            .keys
+            .iter()
+            .filter(|key| {
+                signatures
+                    .iter()
+                    .any(|signature| key.verify(message, signature).is_ok())
+            })
+            .count();
+        Ok(signers >= self.threshold)
+    }
+}
+
+fn same_key(a: &AnyVerifyingKey, b: &AnyVerifyingKey) -> bool {
+    a.scheme() == b.scheme() && a.to_address() == b.to_address()
+}
+
+#[cfg(all(test, feature = "secp256k1", feature = "ed25519"))]
+mod tests {
+    use super::*;
+    use crate::{AnySigningKey, DigestKind, SignatureScheme};
+
+    const SECP256K1_KEYS: [&str; 2] = [
+        "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d",
+    ];
+    const ED25519_KEY: &str = "902ddc4259c907ef56ad9be31f6640978eb8d392e18ffde0a569ded804df23e1";
+
+    fn signing_keys() -> Vec<AnySigningKey> {
+        let mut keys: Vec<_> = SECP256K1_KEYS
+            .iter()
+            .map(|key| AnySigningKey::from_str_with_scheme(SignatureScheme::Secp256k1, key))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        keys.push(
+            AnySigningKey::from_str_with_scheme(SignatureScheme::Ed25519, ED25519_KEY).unwrap(),
+        );
+        keys
+    }
+
+    fn verifier(threshold: usize) -> ThresholdVerifier {
+        let keys = signing_keys()
+            .iter()
+            .map(AnySigningKey::verifying_key)
+            .collect();
+        ThresholdVerifier::new(keys, threshold).unwrap()
+    }
+
+    #[test]
+    fn test_threshold_is_reached_by_distinct_signers() {
+        let keys = signing_keys();
+        let signatures: Vec<_> = keys.iter().map(|key| key.sign(b"block").unwrap()).collect();
+        let verifier = verifier(2);
+
+        assert!(!verifier
+            .verify_threshold(b"block", &signatures[..1])
+            .unwrap());
+        assert!(verifier
+            .verify_threshold(b"block", &signatures[..2])
+            .unwrap());
+        assert!(verifier.verify_threshold(b"block", &signatures).unwrap());
+        assert!(!verifier.verify_threshold(b"other", &signatures).unwrap());
+    }
+
+    #[test]
+    fn test_duplicate_signatures_count_once() {
+        let keys = signing_keys();
+        let signature = keys[0].sign(b"block").unwrap();
+        let verifier = verifier(2);
+
+        let duplicates = vec![signature.clone(), signature.clone(), signature];
+        assert!(!verifier.verify_threshold(b"block", &duplicates).unwrap());
+
+        // Distinct signatures of the same key over the same message count once as well.
+        let keccak = keys[0]
+            .clone()
+            .with_digest(DigestKind::Keccak256)
+            .sign(b"block")
+            .unwrap();
+        assert_ne!(keccak.bytes, duplicates[0].bytes);
+        let distinct = vec![duplicates[0].clone(), keccak];
+        assert!(!verifier.verify_threshold(b"block", &distinct).unwrap());
+    }
+
+    #[test]
+    fn test_unknown_signers_are_ignored() {
+        let keys = signing_keys();
+        let verifier = ThresholdVerifier::new(vec![keys[0].verifying_key()], 1).unwrap();
+
+        let signature = keys[1].sign(b"block").unwrap();
+        assert!(!verifier.verify_threshold(b"block", &[signature]).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_threshold_is_rejected() {
+        let keys = signing_keys();
+        let key = keys[0].verifying_key();
+
+        assert!(matches!(
+            ThresholdVerifier::new(vec![key.clone()], 0),
+            Err(SignatureError::InvalidThreshold { .. })
+        ));
+        // The same key listed twice is a single signer.
+        assert!(matches!(
+            ThresholdVerifier::new(vec![key.clone(), key], 2),
+            Err(SignatureError::InvalidThreshold {
+                threshold: 2,
+                keys: 1
+            })
+        ));
+    }
+}
@@ -0,0 +1,243 @@
+//! Property tests of the signing and verification of random messages with random keys, for
+//! every enabled scheme.
+#![cfg(any(feature = "secp256k1", feature = "ed25519"))]
+
+use mojave_signature::{AnySigningKey, DigestKind, Signature, SignatureScheme};
+use proptest::{prelude::*, test_runner::TestCaseError};
+use std::str::FromStr;
+
+/// Signing is slow enough for the default 256 cases per property to add up.
+const CASES: u32 = 64;
+
+/// Returns the key of `scheme` with the given secret, rejecting the test case for the rare
+/// secrets that are not a valid key of the scheme, e.g. above the secp256k1 curve order.
+fn signing_key(scheme: SignatureScheme, secret: [u8; 32]) -> Result<AnySigningKey, TestCaseError> {
+    AnySigningKey::from_str_with_scheme(scheme, &hex::encode(secret))
+        .map_err(|error| TestCaseError::reject(error.to_string()))
+}
+
+fn sign_then_verify(
+    scheme: SignatureScheme,
+    secret: [u8; 32],
+    message: Vec<u8>,
+) -> Result<(), TestCaseError> {
+    let key = signing_key(scheme, secret)?;
+    let signature = key.sign(&message).unwrap();
+    prop_assert_eq!(signature.scheme, scheme);
+    prop_assert!(key.verifying_key().verify(&message, &signature).is_ok());
+    Ok(())
+}
+
+fn verify_with_wrong_key(
+    scheme: SignatureScheme,
+    secret: [u8; 32],
+    other_secret: [u8; 32],
+    message: Vec<u8>,
+) -> Result<(), TestCaseError> {
+    prop_assume!(secret != other_secret);
+    let key = signing_key(scheme, secret)?;
+    let other_key = signing_key(scheme, other_secret)?;
+    let signature = key.sign(&message).unwrap();
+    prop_assert!(other_key
+        .verifying_key()
+        .verify(&message, &signature)
+        .is_err());
+    Ok(())
+}
+
+fn verify_other_message(
+    scheme: SignatureScheme,
+    secret: [u8; 32],
+    message: Vec<u8>,
+    other_message: Vec<u8>,
+) -> Result<(), TestCaseError> {
+    prop_assume!(message != other_message);
+    let key = signing_key(scheme, secret)?;
+    let signature = key.sign(&message).unwrap();
+    prop_assert!(key
+        .verifying_key()
+        .verify(&other_message, &signature)
+        .is_err());
+    Ok(())
+}
+
+/// Flips bits of one of the first 64 bytes of the signature, `r || s` for both schemes, the
+/// recovery id of a secp256k1 signature not being covered by its verification.
+fn verify_tampered_signature(
+    scheme: SignatureScheme,
+    secret: [u8; 32],
+    message: Vec<u8>,
+    index: usize,
+    flip: u8,
+) -> Result<(), TestCaseError> {
+    let key = signing_key(scheme, secret)?;
+    let mut signature = key.sign(&message).unwrap();
+    signature.bytes[index % 64] ^= flip;
+    prop_assert!(key.verifying_key().verify(&message, &signature).is_err());
+    Ok(())
+}
+
+fn raw_and_prehashed_round_trip(
+    scheme: SignatureScheme,
+    secret: [u8; 32],
+    message: Vec<u8>,
+    digest: [u8; 32],
+) -> Result<(), TestCaseError> {
+    let key = signing_key(scheme, secret)?;
+    let verifying_key = key.verifying_key();
+
+    let raw = key.sign_raw(&message).unwrap();
+    prop_assert!(verifying_key.verify_raw(&message, &raw).is_ok());
+    let prehashed = key.sign_prehashed(&digest).unwrap();
+    prop_assert!(verifying_key.verify_prehashed(&digest, &prehashed).is_ok());
+
+    let mut other_digest = digest;
+    other_digest[0] ^= 1;
+    prop_assert!(verifying_key
+        .verify_prehashed(&other_digest, &prehashed)
+        .is_err());
+    Ok(())
+}
+
+fn keccak_digest_round_trip(
+    scheme: SignatureScheme,
+    secret: [u8; 32],
+    message: Vec<u8>,
+) -> Result<(), TestCaseError> {
+    let key = signing_key(scheme, secret)?.with_digest(DigestKind::Keccak256);
+    let signature = key.sign(&message).unwrap();
+    prop_assert!(key.verifying_key().verify(&message, &signature).is_ok());
+    Ok(())
+}
+
+/// A signature still verifies once written to and parsed back from its text and JSON forms.
+fn encoded_signature_round_trip(
+    scheme: SignatureScheme,
+    secret: [u8; 32],
+    message: Vec<u8>,
+) -> Result<(), TestCaseError> {
+    let key = signing_key(scheme, secret)?;
+    let verifying_key = key.verifying_key();
+    let signature = key.sign(&message).unwrap();
+
+    let parsed = Signature::from_str(&signature.to_string()).unwrap();
+    prop_assert_eq!(&parsed.bytes, &signature.bytes);
+    prop_assert!(verifying_key.verify(&message, &parsed).is_ok());
+
+    let json = serde_json::to_string(&signature).unwrap();
+    let deserialized: Signature = serde_json::from_str(&json).unwrap();
+    prop_assert!(verifying_key.verify(&message, &deserialized).is_ok());
+    Ok(())
+}
+
+macro_rules! scheme_properties {
+    ($scheme:expr) => {
+        use super::*;
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(CASES))]
+
+            #[test]
+            fn signature_verifies(secret in any::<[u8; 32]>(), message in any::<Vec<u8>>()) {
+                sign_then_verify($scheme, secret, message)?;
+            }
+
+            #[test]
+            fn wrong_key_is_rejected(
+                secret in any::<[u8; 32]>(),
+                other_secret in any::<[u8; 32]>(),
+                message in any::<Vec<u8>>(),
+            ) {
+                verify_with_wrong_key($scheme, secret, other_secret, message)?;
+            }
+
+            #[test]
+            fn other_message_is_rejected(
+                secret in any::<[u8; 32]>(),
+                message in any::<Vec<u8>>(),
+                other_message in any::<Vec<u8>>(),
+            ) {
+                verify_other_message($scheme, secret, message, other_message)?;
+            }
+
+            #[test]
+            fn tampered_signature_is_rejected(
+                secret in any::<[u8; 32]>(),
+                message in any::<Vec<u8>>(),
+                index in any::<usize>(),
+                flip in 1..=u8::MAX,
+            ) {
+                verify_tampered_signature($scheme, secret, message, index, flip)?;
+            }
+
+            #[test]
+            fn raw_and_prehashed_signatures_verify(
+                secret in any::<[u8; 32]>(),
+                message in any::<Vec<u8>>(),
+                digest in any::<[u8; 32]>(),
+            ) {
+                raw_and_prehashed_round_trip($scheme, secret, message, digest)?;
+            }
+
+            #[test]
+            fn keccak_signature_verifies(secret in any::<[u8; 32]>(), message in any::<Vec<u8>>()) {
+                keccak_digest_round_trip($scheme, secret, message)?;
+            }
+
+            #[test]
+            fn encoded_signature_verifies(
+                secret in any::<[u8; 32]>(),
+                message in any::<Vec<u8>>(),
+            ) {
+                encoded_signature_round_trip($scheme, secret, message)?;
+            }
+        }
+    };
+}
+
+#[cfg(feature = "secp256k1")]
+mod secp256k1 {
+    use mojave_signature::AnyVerifyingKey;
+
+    scheme_properties!(SignatureScheme::Secp256k1);
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(CASES))]
+
+        #[test]
+        fn signer_is_recovered(secret in any::<[u8; 32]>(), message in any::<Vec<u8>>()) {
+            let key = signing_key(SignatureScheme::Secp256k1, secret)?;
+            let signature = key.sign(&message).unwrap();
+            let recovered = AnyVerifyingKey::recover(&message, &signature).unwrap();
+            prop_assert_eq!(recovered.to_address(), key.verifying_key().to_address());
+        }
+    }
+}
+
+#[cfg(feature = "ed25519")]
+mod ed25519 {
+    scheme_properties!(SignatureScheme::Ed25519);
+}
+
+#[cfg(all(feature = "secp256k1", feature = "ed25519"))]
+mod cross_scheme {
+    use super::*;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(CASES))]
+
+        #[test]
+        fn signature_of_another_scheme_is_rejected(
+            secret in any::<[u8; 32]>(),
+            message in any::<Vec<u8>>(),
+        ) {
+            let secp256k1 = signing_key(SignatureScheme::Secp256k1, secret)?;
+            let ed25519 = signing_key(SignatureScheme::Ed25519, secret)?;
+
+            let signature = secp256k1.sign(&message).unwrap();
+            prop_assert!(ed25519.verifying_key().verify(&message, &signature).is_err());
+            let signature = ed25519.sign(&message).unwrap();
+            prop_assert!(secp256k1.verifying_key().verify(&message, &signature).is_err());
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     network::{MAINNET_BOOTNODES, Network, TESTNET_BOOTNODES},
     options::Options,
+    rpc::{CorsConfig, RpcConfig, RpcLoggingConfig},
 };
 use ethrex::utils::read_node_config_file;
 use ethrex_common::Address;
@@ -83,6 +84,41 @@ pub fn get_authrpc_socket_addr(opts: &Options) -> SocketAddr {
         .expect("Failed to parse authrpc address and port")
 }
 
+/// Returns the address to serve the metrics at, if they are enabled.
+pub fn get_metrics_socket_addr(opts: &Options) -> Option<SocketAddr> {
+    opts.metrics_enabled.then(|| {
+        parse_socket_addr(&opts.metrics_addr, &opts.metrics_port)
+            .expect("Failed to parse metrics address and port")
+    })
+}
+
+pub fn get_rpc_config(opts: &Options) -> RpcConfig {
+    RpcConfig {
+        logging: RpcLoggingConfig {
+            default_sample_rate: opts.rpc_log_sample_rate,
+            method_sample_rates: opts.rpc_log_method_sample_rates.iter().cloned().collect(),
+        },
+        cors: CorsConfig {
+            allowed_origins: opts
+                .rpc_cors_origins
+                .iter()
+                .filter(|origin| *origin != "*")
+                .cloned()
+                .collect(),
+            allow_any: opts.rpc_cors_origins.iter().any(|origin| origin == "*"),
+        },
+        max_proof_storage_keys: opts.rpc_max_proof_storage_keys,
+        admin_enabled: opts.rpc_admin_enabled,
+        max_active_filters: opts.rpc_max_active_filters,
+        max_active_filters_per_client: opts.rpc_max_active_filters_per_client,
+        max_concurrent_log_queries: opts.rpc_max_concurrent_log_queries as usize,
+        max_queued_log_queries: opts.rpc_max_queued_log_queries,
+        max_request_bytes: opts.rpc_max_request_bytes as usize,
+        max_batch_size: opts.rpc_max_batch_size as usize,
+        batch_concurrency: opts.rpc_batch_concurrency as usize,
+    }
+}
+
 pub fn get_local_p2p_node(opts: &Options, signer: &SecretKey) -> Node {
     let udp_socket_addr = parse_socket_addr(&opts.discovery_addr, &opts.discovery_port)
         .expect("Failed to parse discovery address and port");
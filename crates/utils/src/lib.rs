@@ -3,4 +3,5 @@ pub mod logging;
 pub mod network;
 pub mod options;
 pub mod prover_options;
+pub mod rpc;
 pub mod unique_heap;
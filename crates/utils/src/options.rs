@@ -1,4 +1,12 @@
-use crate::network::Network;
+use crate::{
+    network::Network,
+    rpc::{
+        DEFAULT_BATCH_CONCURRENCY, DEFAULT_MAX_ACTIVE_FILTERS,
+        DEFAULT_MAX_ACTIVE_FILTERS_PER_CLIENT, DEFAULT_MAX_BATCH_SIZE,
+        DEFAULT_MAX_CONCURRENT_LOG_QUERIES, DEFAULT_MAX_PROOF_STORAGE_KEYS,
+        DEFAULT_MAX_QUEUED_LOG_QUERIES, DEFAULT_MAX_REQUEST_BYTES,
+    },
+};
 use clap::{ArgAction, Parser};
 use ethrex::utils;
 use ethrex_p2p::{sync::SyncMode, types::Node};
@@ -125,6 +133,121 @@ pub struct Options {
     )]
     pub authrpc_jwtsecret: String,
 
+    #[arg(
+        long = "rpc.log_sample_rate",
+        default_value = "0",
+        value_name = "RATE",
+        value_parser = parse_sample_rate,
+        help = "Share, between 0 and 1, of the rpc requests logged along with their latency.",
+        long_help = "Applies to the methods without a rate set with --rpc.log_method_sample_rates.",
+        help_heading = "RPC options"
+    )]
+    pub rpc_log_sample_rate: f64,
+
+    #[arg(
+        long = "rpc.log_method_sample_rates",
+        value_name = "METHOD=RATE",
+        value_parser = parse_method_sample_rate,
+        value_delimiter = ',',
+        help = "Comma separated log sampling rates of specific rpc methods, e.g. eth_getLogs=1,eth_blockNumber=0.01",
+        help_heading = "RPC options"
+    )]
+    pub rpc_log_method_sample_rates: Vec<(String, f64)>,
+
+    #[arg(
+        long = "rpc.cors_origins",
+        default_value = "*",
+        value_delimiter = ',',
+        value_name = "ORIGINS",
+        help = "Comma separated origins allowed to call the rpc server from a browser, e.g. https://app.example.com, or * for any origin.",
+        help_heading = "RPC options"
+    )]
+    pub rpc_cors_origins: Vec<String>,
+
+    #[arg(
+        long = "rpc.max_proof_storage_keys",
+        default_value = "1024",
+        value_name = "MAX_STORAGE_KEYS",
+        help = "Maximum number of storage keys of an eth_getProof request.",
+        help_heading = "RPC options"
+    )]
+    pub rpc_max_proof_storage_keys: usize,
+
+    #[arg(
+        long = "rpc.admin",
+        action = ArgAction::SetTrue,
+        help = "Serve the admin rpc methods, such as mojave_getPeers.",
+        help_heading = "RPC options"
+    )]
+    pub rpc_admin_enabled: bool,
+
+    #[arg(
+        long = "rpc.max_active_filters",
+        default_value = "10000",
+        value_name = "MAX_FILTERS",
+        help = "Maximum number of filters alive at once, across every client.",
+        help_heading = "RPC options"
+    )]
+    pub rpc_max_active_filters: usize,
+
+    #[arg(
+        long = "rpc.max_active_filters_per_client",
+        default_value = "100",
+        value_name = "MAX_FILTERS",
+        help = "Maximum number of filters alive at once created by a single client IP.",
+        help_heading = "RPC options"
+    )]
+    pub rpc_max_active_filters_per_client: usize,
+
+    #[arg(
+        long = "rpc.max_concurrent_log_queries",
+        default_value = "16",
+        value_name = "MAX_QUERIES",
+        help = "Maximum number of eth_getLogs and eth_getFilterLogs queries executed at once.",
+        help_heading = "RPC options",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub rpc_max_concurrent_log_queries: u64,
+
+    #[arg(
+        long = "rpc.max_queued_log_queries",
+        default_value = "64",
+        value_name = "MAX_QUERIES",
+        help = "Maximum number of log queries waiting for an executing one to finish, the following ones being rejected.",
+        help_heading = "RPC options"
+    )]
+    pub rpc_max_queued_log_queries: usize,
+
+    #[arg(
+        long = "rpc.max_request_bytes",
+        default_value = "2097152",
+        value_name = "MAX_BYTES",
+        help = "Maximum size in bytes of the body of a request, the requests declaring a larger Content-Length being rejected before their body is read.",
+        help_heading = "RPC options",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub rpc_max_request_bytes: u64,
+
+    #[arg(
+        long = "rpc.max_batch_size",
+        default_value = "100",
+        value_name = "MAX_REQUESTS",
+        help = "Maximum number of requests of a batch, larger batches being rejected before any of their requests is processed.",
+        help_heading = "RPC options",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub rpc_max_batch_size: u64,
+
+    #[arg(
+        long = "rpc.batch_concurrency",
+        default_value = "8",
+        value_name = "MAX_REQUESTS",
+        help = "Maximum number of read-only requests of a batch processed concurrently.",
+        help_heading = "RPC options",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub rpc_batch_concurrency: u64,
+
     #[arg(long = "p2p.enabled", default_value =  "true" , value_name = "P2P_ENABLED", action = ArgAction::SetTrue, help_heading = "P2P options")]
     pub p2p_enabled: bool,
 
@@ -171,6 +294,18 @@ impl Default for Options {
             authrpc_addr: Default::default(),
             authrpc_port: Default::default(),
             authrpc_jwtsecret: Default::default(),
+            rpc_log_sample_rate: Default::default(),
+            rpc_log_method_sample_rates: Default::default(),
+            rpc_cors_origins: vec!["*".to_owned()],
+            rpc_max_proof_storage_keys: DEFAULT_MAX_PROOF_STORAGE_KEYS,
+            rpc_admin_enabled: false,
+            rpc_max_active_filters: DEFAULT_MAX_ACTIVE_FILTERS,
+            rpc_max_active_filters_per_client: DEFAULT_MAX_ACTIVE_FILTERS_PER_CLIENT,
+            rpc_max_concurrent_log_queries: DEFAULT_MAX_CONCURRENT_LOG_QUERIES as u64,
+            rpc_max_queued_log_queries: DEFAULT_MAX_QUEUED_LOG_QUERIES,
+            rpc_max_request_bytes: DEFAULT_MAX_REQUEST_BYTES as u64,
+            rpc_max_batch_size: DEFAULT_MAX_BATCH_SIZE as u64,
+            rpc_batch_concurrency: DEFAULT_BATCH_CONCURRENCY as u64,
             p2p_enabled: Default::default(),
             p2p_addr: Default::default(),
             p2p_port: Default::default(),
@@ -205,6 +340,33 @@ impl fmt::Debug for Options {
             .field("authrpc_addr", &self.authrpc_addr)
             .field("authrpc_port", &self.authrpc_port)
             .field("authrpc_jwtsecret", &self.authrpc_jwtsecret)
+            .field("rpc_log_sample_rate", &self.rpc_log_sample_rate)
+            .field(
+                "rpc_log_method_sample_rates",
+                &self.rpc_log_method_sample_rates,
+            )
+            .field("rpc_cors_origins", &self.rpc_cors_origins)
+            .field(
+                "rpc_max_proof_storage_keys",
+                &self.rpc_max_proof_storage_keys,
+            )
+            .field("rpc_admin_enabled", &self.rpc_admin_enabled)
+            .field("rpc_max_active_filters", &self.rpc_max_active_filters)
+            .field(
+                "rpc_max_active_filters_per_client",
+                &self.rpc_max_active_filters_per_client,
+            )
+            .field(
+                "rpc_max_concurrent_log_queries",
+                &self.rpc_max_concurrent_log_queries,
+            )
+            .field(
+                "rpc_max_queued_log_queries",
+                &self.rpc_max_queued_log_queries,
+            )
+            .field("rpc_max_request_bytes", &self.rpc_max_request_bytes)
+            .field("rpc_max_batch_size", &self.rpc_max_batch_size)
+            .field("rpc_batch_concurrency", &self.rpc_batch_concurrency)
             .field("p2p_enabled", &self.p2p_enabled)
             .field("p2p_addr", &self.p2p_addr)
             .field("p2p_port", &self.p2p_port)
@@ -213,3 +375,20 @@ impl fmt::Debug for Options {
             .finish()
     }
 }
+
+fn parse_sample_rate(value: &str) -> Result<f64, String> {
+    let rate = value
+        .parse::<f64>()
+        .map_err(|error| format!("invalid sample rate {value}: {error}"))?;
+    if !(0.0..=1.0).contains(&rate) {
+        return Err(format!("sample rate {value} is not between 0 and 1"));
+    }
+    Ok(rate)
+}
+
+fn parse_method_sample_rate(value: &str) -> Result<(String, f64), String> {
+    let (method, rate) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected METHOD=RATE, got {value}"))?;
+    Ok((method.to_owned(), parse_sample_rate(rate)?))
+}
@@ -0,0 +1,1351 @@
+use axum::{
+    Json, Router,
+    body::{Body, to_bytes},
+    extract::{DefaultBodyLimit, Request, State},
+    http::{
+        HeaderValue, StatusCode,
+        header::{AUTHORIZATION, CONTENT_LENGTH},
+    },
+    middleware::{Next, from_fn_with_state},
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use ethrex_common::Bytes;
+use ethrex_p2p::kademlia::{KademliaTable, PeerData};
+use ethrex_rpc::{
+    ActiveFilters, RpcApiContext as L1Context, RpcErr,
+    utils::{RpcRequest, RpcRequestId},
+};
+use ethrex_storage::{Store, error::StoreError};
+use futures::{StreamExt, stream};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    net::IpAddr,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::Semaphore;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+pub const DEFAULT_MAX_PROOF_STORAGE_KEYS: usize = 1024;
+pub const DEFAULT_MAX_ACTIVE_FILTERS: usize = 10_000;
+pub const DEFAULT_MAX_ACTIVE_FILTERS_PER_CLIENT: usize = 100;
+pub const DEFAULT_MAX_CONCURRENT_LOG_QUERIES: usize = 16;
+pub const DEFAULT_MAX_QUEUED_LOG_QUERIES: usize = 64;
+pub const DEFAULT_MAX_REQUEST_BYTES: usize = 2 * 1024 * 1024;
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 100;
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 8;
+
+/// JSON-RPC error code of the requests rejected because a limit of the node is reached.
+pub const LIMIT_EXCEEDED_CODE: i32 = -32005;
+/// JSON-RPC error code of the bodies that are not a valid request, such as an empty batch.
+pub const INVALID_REQUEST_CODE: i32 = -32600;
+/// Maximum difference in seconds between the `iat` claim of an Auth-RPC token and the local
+/// time, as set by the engine API.
+pub const JWT_MAX_CLOCK_SKEW: u64 = 60;
+
+/// Error of an RPC request, either one of ethrex or one of the errors ethrex has no variant for.
+/// A limit of the node being reached is answered with the [`LIMIT_EXCEEDED_CODE`] error, for the
+/// client to know it may retry later.
+#[derive(Debug)]
+pub enum RpcError {
+    Eth(RpcErr),
+    LimitExceeded(String),
+    /// Answered with the [`INVALID_REQUEST_CODE`] error.
+    InvalidRequest(String),
+}
+
+impl From<RpcErr> for RpcError {
+    fn from(error: RpcErr) -> Self {
+        Self::Eth(error)
+    }
+}
+
+impl From<StoreError> for RpcError {
+    fn from(error: StoreError) -> Self {
+        Self::Eth(error.into())
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Eth(error) => write!(f, "{error}"),
+            Self::LimitExceeded(message) | Self::InvalidRequest(message) => f.write_str(message),
+        }
+    }
+}
+
+/// Settings of the RPC server common to every node type.
+#[derive(Clone, Debug)]
+pub struct RpcConfig {
+    pub logging: RpcLoggingConfig,
+    pub cors: CorsConfig,
+    /// Maximum number of storage keys of an `eth_getProof` request.
+    pub max_proof_storage_keys: usize,
+    /// Whether the methods exposing node internals, such as `mojave_getPeers`, are served.
+    pub admin_enabled: bool,
+    /// Maximum number of filters alive at once, across every client.
+    pub max_active_filters: usize,
+    /// Maximum number of filters alive at once created by a single client IP.
+    pub max_active_filters_per_client: usize,
+    /// Maximum number of `eth_getLogs`/`eth_getFilterLogs` queries executed at once.
+    pub max_concurrent_log_queries: usize,
+    /// Maximum number of log queries waiting for one of the executing ones to finish, the
+    /// following ones being rejected.
+    pub max_queued_log_queries: usize,
+    /// Maximum size in bytes of the body of a request.
+    pub max_request_bytes: usize,
+    /// Maximum number of requests of a batch.
+    pub max_batch_size: usize,
+    /// Maximum number of read-only requests of a batch processed concurrently.
+    pub batch_concurrency: usize,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            logging: RpcLoggingConfig::default(),
+            cors: CorsConfig::default(),
+            max_proof_storage_keys: DEFAULT_MAX_PROOF_STORAGE_KEYS,
+            admin_enabled: false,
+            max_active_filters: DEFAULT_MAX_ACTIVE_FILTERS,
+            max_active_filters_per_client: DEFAULT_MAX_ACTIVE_FILTERS_PER_CLIENT,
+            max_concurrent_log_queries: DEFAULT_MAX_CONCURRENT_LOG_QUERIES,
+            max_queued_log_queries: DEFAULT_MAX_QUEUED_LOG_QUERIES,
+            max_request_bytes: DEFAULT_MAX_REQUEST_BYTES,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            batch_concurrency: DEFAULT_BATCH_CONCURRENCY,
+        }
+    }
+}
+
+/// Body of an RPC request. The entries of a batch are parsed one by one, so that a malformed entry
+/// gets its own error instead of failing the whole batch, once the batch is known not to exceed
+/// the maximum size.
+#[derive(Debug)]
+pub enum RpcRequestBody {
+    Single(RpcRequest),
+    Batch(Vec<Result<RpcRequest, (RpcRequestId, RpcErr)>>),
+}
+
+impl RpcRequestBody {
+    /// Fails for an empty batch, which JSON-RPC answers with a single error rather than with an
+    /// empty array.
+    pub fn parse(body: &str, max_batch_size: usize) -> Result<Self, RpcError> {
+        let invalid_body = || RpcErr::BadParams("Invalid request body".to_owned());
+        match serde_json::from_str::<Value>(body).map_err(|_| invalid_body())? {
+            Value::Array(entries) if entries.is_empty() => Err(RpcError::InvalidRequest(
+                "Invalid request: empty batch".to_owned(),
+            )),
+            Value::Array(entries) if entries.len() > max_batch_size => {
+                Err(RpcError::LimitExceeded(format!(
+                    "Batch of {} requests exceeds the maximum of {max_batch_size}",
+                    entries.len()
+                )))
+            }
+            Value::Array(entries) => Ok(Self::Batch(
+                entries.into_iter().map(parse_batch_entry).collect(),
+            )),
+            request => serde_json::from_value(request)
+                .map(Self::Single)
+                .map_err(|_| invalid_body().into()),
+        }
+    }
+}
+
+/// Processes the requests of a batch and returns their results in the order of the requests.
+///
+/// Consecutive read-only requests are processed concurrently, at most `concurrency` at a time,
+/// whereas a request for which `is_state_mutating` holds only starts once every request before it
+/// completed and blocks the requests after it until it completes.
+pub async fn process_batch<'a, F, Fut>(
+    requests: &[&'a RpcRequest],
+    concurrency: usize,
+    is_state_mutating: fn(&str) -> bool,
+    handler: F,
+) -> Vec<Result<Value, RpcError>>
+where
+    F: Fn(&'a RpcRequest) -> Fut,
+    Fut: Future<Output = Result<Value, RpcError>>,
+{
+    let mut results = Vec::with_capacity(requests.len());
+    let mut pending = requests;
+    while let Some(request) = pending.first() {
+        if is_state_mutating(&request.method) {
+            results.push(handler(*request).await);
+            pending = &pending[1..];
+            continue;
+        }
+
+        let reads_len = pending
+            .iter()
+            .position(|request| is_state_mutating(&request.method))
+            .unwrap_or(pending.len());
+        let (reads, rest) = pending.split_at(reads_len);
+        let read_results: Vec<_> = stream::iter(reads)
+            .map(|request| handler(*request))
+            .buffered(concurrency.max(1))
+            .collect()
+            .await;
+        results.extend(read_results);
+        pending = rest;
+    }
+    results
+}
+
+/// Answers the entries of a batch in their order, the requests with their result, in the order
+/// of the requests, and the malformed entries with their error.
+pub fn batch_response(
+    entries: Vec<Result<RpcRequest, (RpcRequestId, RpcErr)>>,
+    results: Vec<Result<Value, RpcError>>,
+) -> Result<Value, RpcErr> {
+    let mut results = results.into_iter();
+    let responses = entries
+        .into_iter()
+        .map(|entry| match entry {
+            Ok(request) => rpc_response(
+                request.id,
+                results.next().unwrap_or_else(|| {
+                    Err(RpcErr::Internal("Missing batch result".to_owned()).into())
+                }),
+            ),
+            Err((id, error)) => rpc_response(id, Err(error)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Value::Array(responses))
+}
+
+/// Parses an entry of a batch, returning the id to answer it with, when it has one, along with
+/// the error if it is malformed.
+fn parse_batch_entry(entry: Value) -> Result<RpcRequest, (RpcRequestId, RpcErr)> {
+    let id = entry
+        .get("id")
+        .and_then(|id| serde_json::from_value(id.clone()).ok())
+        .unwrap_or_else(|| RpcRequestId::String(String::new()));
+    serde_json::from_value(entry)
+        .map_err(|error| (id, RpcErr::BadParams(format!("Invalid request: {error}"))))
+}
+
+/// Handles the `net` namespace, shared by every node type so that they answer it the same way.
+pub fn map_net_requests(req: &RpcRequest, storage: &Store) -> Result<Value, RpcErr> {
+    match req.method.as_str() {
+        "net_version" => net_version(storage),
+        others => Err(RpcErr::MethodNotFound(others.to_owned())),
+    }
+}
+
+/// Returns the configured chain id as a decimal string, for the tooling still relying on
+/// `net_version` rather than `eth_chainId`.
+fn net_version(storage: &Store) -> Result<Value, RpcErr> {
+    let chain_id = storage.get_chain_config()?.chain_id;
+    Ok(Value::String(chain_id.to_string()))
+}
+
+/// Rejects the `eth_getProof` requests with more than `max_storage_keys` storage keys, or with keys
+/// that are not 32 bytes hex encoded values, before any proof gets computed.
+pub fn validate_get_proof(req: &RpcRequest, max_storage_keys: usize) -> Result<(), RpcErr> {
+    let storage_keys = req
+        .params
+        .as_ref()
+        .and_then(|params| params.get(1))
+        .ok_or_else(|| RpcErr::BadParams("Missing storage keys parameter".to_owned()))?
+        .as_array()
+        .ok_or_else(|| RpcErr::BadParams("Storage keys must be an array".to_owned()))?;
+
+    if storage_keys.len() > max_storage_keys {
+        return Err(RpcErr::BadParams(format!(
+            "Too many storage keys: {} were requested but at most {max_storage_keys} are allowed",
+            storage_keys.len()
+        )));
+    }
+    for key in storage_keys {
+        let is_valid = key
+            .as_str()
+            .and_then(|key| key.strip_prefix("0x"))
+            .is_some_and(|hex| hex.len() == 64 && hex.bytes().all(|byte| byte.is_ascii_hexdigit()));
+        if !is_valid {
+            return Err(RpcErr::BadParams(format!(
+                "Invalid storage key {key}, expected a 0x prefixed 32 bytes hex value"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether the method installs a filter, which counts towards the filter caps.
+pub fn is_filter_creation(method: &str) -> bool {
+    matches!(
+        method,
+        "eth_newFilter" | "eth_newBlockFilter" | "eth_newPendingTransactionFilter"
+    )
+}
+
+/// Filters installed by each client IP, so that a single client cannot exhaust the memory by
+/// creating filters faster than they expire.
+#[derive(Clone, Debug, Default)]
+pub struct FilterQuotas(Arc<Mutex<HashMap<IpAddr, ClientFilters>>>);
+
+#[derive(Debug, Default)]
+struct ClientFilters {
+    ids: HashSet<u64>,
+    /// Filters being created, counted towards the caps until they are installed or fail.
+    reserved: usize,
+}
+
+impl FilterQuotas {
+    /// Reserves a filter for `client`, failing if one more filter would exceed a cap of the
+    /// config. The caps are checked and the filter reserved under the same lock, so that
+    /// concurrent creations cannot overshoot them.
+    pub fn reserve(
+        &self,
+        active_filters: &ActiveFilters,
+        client: IpAddr,
+        config: &RpcConfig,
+    ) -> Result<FilterReservation<'_>, RpcError> {
+        let mut clients = self.lock();
+        let active_filters = active_filters
+            .lock()
+            .map_err(|error| RpcErr::Internal(error.to_string()))?;
+        // Forget the filters uninstalled or expired since the last reservation.
+        clients.retain(|_, filters| {
+            filters
+                .ids
+                .retain(|filter_id| active_filters.contains_key(filter_id));
+            !filters.ids.is_empty() || filters.reserved > 0
+        });
+        let reserved: usize = clients.values().map(|filters| filters.reserved).sum();
+        if active_filters.len() + reserved >= config.max_active_filters {
+            return Err(RpcError::LimitExceeded(format!(
+                "Too many active filters: at most {} are allowed",
+                config.max_active_filters
+            )));
+        }
+        drop(active_filters);
+
+        let filters = clients.entry(client).or_default();
+        if filters.ids.len() + filters.reserved >= config.max_active_filters_per_client {
+            return Err(RpcError::LimitExceeded(format!(
+                "Too many active filters for {client}: at most {} are allowed per client",
+                config.max_active_filters_per_client
+            )));
+        }
+        filters.reserved += 1;
+        Ok(FilterReservation {
+            quotas: self,
+            client,
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<IpAddr, ClientFilters>> {
+        self.0.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+/// A filter reserved by [`FilterQuotas::reserve`], released when dropped unless it was
+/// installed.
+#[derive(Debug)]
+pub struct FilterReservation<'a> {
+    quotas: &'a FilterQuotas,
+    client: IpAddr,
+}
+
+impl FilterReservation<'_> {
+    /// Counts the installed filter towards the caps in place of the reservation.
+    pub fn install(self, filter_id: u64) {
+        self.quotas
+            .lock()
+            .entry(self.client)
+            .or_default()
+            .ids
+            .insert(filter_id);
+    }
+}
+
+impl Drop for FilterReservation<'_> {
+    fn drop(&mut self) {
+        if let Some(filters) = self.quotas.lock().get_mut(&self.client) {
+            filters.reserved = filters.reserved.saturating_sub(1);
+        }
+    }
+}
+
+/// Installs a filter for `client` unless the filter caps of the config are reached.
+pub async fn new_filter(
+    req: &RpcRequest,
+    context: L1Context,
+    client: IpAddr,
+    quotas: &FilterQuotas,
+    config: &RpcConfig,
+) -> Result<Value, RpcError> {
+    let reservation = quotas.reserve(&context.active_filters, client, config)?;
+    let filter_id = ethrex_rpc::map_eth_requests(req, context).await?;
+    if let Some(id) = filter_id
+        .as_str()
+        .and_then(|id| id.strip_prefix("0x"))
+        .and_then(|id| u64::from_str_radix(id, 16).ok())
+    {
+        reservation.install(id);
+    }
+    Ok(filter_id)
+}
+
+/// Returns whether the method queries logs, which [`LogQueryLimiter`] bounds.
+pub fn is_log_query(method: &str) -> bool {
+    matches!(method, "eth_getLogs" | "eth_getFilterLogs")
+}
+
+/// Bounds the number of log queries executed at once, an expensive log query being able to keep
+/// the node busy for long. The queries over the cap wait for a running one to finish, up to a
+/// bound past which they are rejected with [`RpcError::LimitExceeded`].
+#[derive(Clone, Debug)]
+pub struct LogQueryLimiter {
+    permits: Arc<Semaphore>,
+    /// Number of queries executing or waiting for a permit.
+    admitted: Arc<AtomicUsize>,
+    max_admitted: usize,
+}
+
+impl LogQueryLimiter {
+    pub fn new(config: &RpcConfig) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(config.max_concurrent_log_queries)),
+            admitted: Arc::new(AtomicUsize::new(0)),
+            max_admitted: config.max_concurrent_log_queries + config.max_queued_log_queries,
+        }
+    }
+
+    pub async fn run<F>(&self, query: F) -> Result<Value, RpcError>
+    where
+        F: Future<Output = Result<Value, RpcErr>>,
+    {
+        if self.admitted.fetch_add(1, Ordering::SeqCst) >= self.max_admitted {
+            self.admitted.fetch_sub(1, Ordering::SeqCst);
+            return Err(RpcError::LimitExceeded(
+                "Too many concurrent log queries, retry later".to_owned(),
+            ));
+        }
+        let _admitted = AdmittedQuery(&self.admitted);
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|error| RpcErr::Internal(error.to_string()))?;
+        Ok(query.await?)
+    }
+}
+
+/// Releases the admission of a query, even if it is cancelled while waiting for a permit.
+struct AdmittedQuery<'a>(&'a AtomicUsize);
+
+impl Drop for AdmittedQuery<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Same as [`ethrex_rpc::rpc_response`], answering the errors ethrex has no variant for with
+/// their own code.
+pub fn rpc_response<E: Into<RpcError>>(
+    id: RpcRequestId,
+    res: Result<Value, E>,
+) -> Result<Value, RpcErr> {
+    let res: Result<Value, RpcError> = res.map_err(Into::into);
+    let (code, message) = match res {
+        Ok(value) => return ethrex_rpc::rpc_response(id, Ok::<_, RpcErr>(value)),
+        Err(RpcError::Eth(error)) => return ethrex_rpc::rpc_response(id, Err(error)),
+        Err(RpcError::LimitExceeded(message)) => (LIMIT_EXCEEDED_CODE, message),
+        Err(RpcError::InvalidRequest(message)) => (INVALID_REQUEST_CODE, message),
+    };
+    Ok(json!({
+        "id": id,
+        "jsonrpc": "2.0",
+        "error": {
+            "code": code,
+            "message": message,
+        },
+    }))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerInfo {
+    pub enode: String,
+    pub ip: IpAddr,
+    pub direction: PeerDirection,
+    /// UNIX timestamp, in seconds, of the last ping answered by the peer.
+    pub last_seen: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PeerDirection {
+    Inbound,
+    Outbound,
+}
+
+impl From<&PeerData> for PeerInfo {
+    fn from(peer: &PeerData) -> Self {
+        Self {
+            enode: peer.node.enode_url(),
+            ip: peer.node.ip,
+            direction: if peer.is_connection_inbound {
+                PeerDirection::Inbound
+            } else {
+                PeerDirection::Outbound
+            },
+            last_seen: peer.last_ping,
+        }
+    }
+}
+
+/// Returns the details of every peer of the table. As an admin method, it is only served when
+/// enabled in the RPC config.
+pub async fn get_peers(
+    peer_table: &tokio::sync::Mutex<KademliaTable>,
+    config: &RpcConfig,
+) -> Result<Value, RpcErr> {
+    if !config.admin_enabled {
+        return Err(RpcErr::MethodNotFound("mojave_getPeers".to_owned()));
+    }
+    let peers: Vec<PeerInfo> = peer_table
+        .lock()
+        .await
+        .iter_peers()
+        .map(PeerInfo::from)
+        .collect();
+    serde_json::to_value(peers).map_err(|error| RpcErr::Internal(error.to_string()))
+}
+
+/// Returns the chain config of the store, with the chain id and the fork activations, so that
+/// tooling does not need the genesis file to learn them.
+pub fn get_chain_config(store: &Store) -> Result<Value, RpcErr> {
+    let chain_config = store.get_chain_config()?;
+    serde_json::to_value(chain_config).map_err(|error| RpcErr::Internal(error.to_string()))
+}
+
+/// Origins whose browser pages may call the RPC server.
+#[derive(Clone, Debug)]
+pub struct CorsConfig {
+    /// Origins allowed when `allow_any` is not set, e.g. `https://app.mojave.network`.
+    pub allowed_origins: Vec<String>,
+    pub allow_any: bool,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allow_any: true,
+        }
+    }
+}
+
+impl CorsConfig {
+    /// Returns the layer answering the CORS requests, allowing every method and header to the
+    /// allowed origins.
+    pub fn layer(&self) -> CorsLayer {
+        if self.allow_any {
+            return CorsLayer::permissive();
+        }
+        let origins: Vec<HeaderValue> = self
+            .allowed_origins
+            .iter()
+            .filter_map(|origin| {
+                HeaderValue::from_str(origin)
+                    .inspect_err(|_| tracing::warn!("Ignoring the invalid CORS origin {origin}"))
+                    .ok()
+            })
+            .collect();
+        CorsLayer::new()
+            .allow_origin(AllowOrigin::list(origins))
+            .allow_methods(Any)
+            .allow_headers(Any)
+    }
+}
+
+/// Sampling rates, between 0 and 1, of the logged RPC requests.
+#[derive(Clone, Debug, Default)]
+pub struct RpcLoggingConfig {
+    /// Rate of the methods without a rate of their own.
+    pub default_sample_rate: f64,
+    pub method_sample_rates: HashMap<String, f64>,
+}
+
+/// State of the [`log_requests`] middleware, deciding which requests get logged.
+#[derive(Clone, Debug, Default)]
+pub struct RequestLogger {
+    config: Arc<RpcLoggingConfig>,
+    counters: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+impl RequestLogger {
+    pub fn new(config: RpcLoggingConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            counters: Arc::default(),
+        }
+    }
+
+    /// Counts a request of the method and returns whether it must be logged. Sampling is
+    /// deterministic: at a rate of 0.01, one request out of every hundred is logged.
+    pub fn should_log(&self, method: &str) -> bool {
+        let rate = self
+            .config
+            .method_sample_rates
+            .get(method)
+            .copied()
+            .unwrap_or(self.config.default_sample_rate);
+        if rate <= 0.0 {
+            return false;
+        }
+        if rate >= 1.0 {
+            return true;
+        }
+
+        let mut counters = self
+            .counters
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        let count = counters.entry(method.to_owned()).or_default();
+        *count += 1;
+        (*count as f64 * rate).floor() > ((*count - 1) as f64 * rate).floor()
+    }
+}
+
+/// Middleware logging the sampled requests along with the time taken to answer them. The methods
+/// of a batch are sampled one by one and logged with the latency of the whole batch.
+pub async fn log_requests(
+    State(logger): State<RequestLogger>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let (parts, body) = request.into_parts();
+    let Ok(body) = to_bytes(body, usize::MAX).await else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let sampled: Vec<String> = request_methods(&body)
+        .into_iter()
+        .filter(|method| logger.should_log(method))
+        .collect();
+
+    let started_at = Instant::now();
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    let latency_ms = started_at.elapsed().as_millis();
+    for method in sampled {
+        tracing::info!(method = method.as_str(), latency_ms, "Served RPC request");
+    }
+    response
+}
+
+/// Middleware answering `413 Payload Too Large` to the requests whose body, a single request or
+/// a whole batch, exceeds `max_request_bytes`. The requests declaring a larger `Content-Length`
+/// are rejected before their body is read, the other ones once its first `max_request_bytes`
+/// are read, so that the following middlewares and handlers never buffer a larger body.
+pub async fn reject_oversized_requests(
+    State(max_request_bytes): State<usize>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let content_length = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    match content_length {
+        Some(length) if length > max_request_bytes as u64 => {
+            return StatusCode::PAYLOAD_TOO_LARGE.into_response();
+        }
+        _ => {}
+    }
+    let (parts, body) = request.into_parts();
+    let Ok(body) = to_bytes(body, max_request_bytes).await else {
+        return StatusCode::PAYLOAD_TOO_LARGE.into_response();
+    };
+    next.run(Request::from_parts(parts, Body::from(body))).await
+}
+
+/// Limit of the size of the bodies read by the request handlers, see
+/// [`reject_oversized_requests`].
+pub fn request_size_limit(config: &RpcConfig) -> DefaultBodyLimit {
+    DefaultBodyLimit::max(config.max_request_bytes)
+}
+
+/// Returns the methods of a single or batch request, ignoring the malformed entries.
+fn request_methods(body: &[u8]) -> Vec<String> {
+    let method = |request: &Value| request.get("method")?.as_str().map(str::to_owned);
+    match serde_json::from_slice::<Value>(body) {
+        Ok(Value::Array(requests)) => requests.iter().filter_map(method).collect(),
+        Ok(request) => method(&request).into_iter().collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+#[derive(Clone)]
+struct AuthRpcState {
+    context: L1Context,
+    max_batch_size: usize,
+}
+
+/// Router of the Auth-RPC server, answering the `engine_*` requests authenticated with the JWT
+/// secret of the node, either single or batched. The size of the requests is bounded before
+/// their authentication.
+pub fn authrpc_router(context: L1Context, config: &RpcConfig) -> Router {
+    let jwt_secret = context.node_data.jwt_secret.clone();
+    Router::new()
+        .route("/", post(handle_authrpc_request))
+        .layer(from_fn_with_state(jwt_secret, authenticate_jwt))
+        .layer(request_size_limit(config))
+        .layer(from_fn_with_state(
+            config.max_request_bytes,
+            reject_oversized_requests,
+        ))
+        .with_state(AuthRpcState {
+            context,
+            max_batch_size: config.max_batch_size,
+        })
+}
+
+async fn handle_authrpc_request(
+    State(state): State<AuthRpcState>,
+    body: String,
+) -> Result<Json<Value>, StatusCode> {
+    let res = match RpcRequestBody::parse(&body, state.max_batch_size) {
+        Ok(RpcRequestBody::Single(request)) => {
+            let res = map_authrpc_requests(&request, state.context).await;
+            rpc_response(request.id, res)
+        }
+        Ok(RpcRequestBody::Batch(entries)) => {
+            let requests: Vec<&RpcRequest> = entries
+                .iter()
+                .filter_map(|entry| entry.as_ref().ok())
+                .collect();
+            // Most engine requests update the chain, so they are all processed one at a time, in
+            // order.
+            let results = process_batch(
+                &requests,
+                1,
+                |_| true,
+                |req| map_authrpc_requests(req, state.context.clone()),
+            )
+            .await;
+            batch_response(entries, results)
+        }
+        Err(error) => rpc_response(RpcRequestId::String("".to_string()), Err(error)),
+    };
+    res.map(Json).map_err(|_| StatusCode::BAD_REQUEST)
+}
+
+async fn map_authrpc_requests(req: &RpcRequest, context: L1Context) -> Result<Value, RpcError> {
+    if req.method.starts_with("engine_") {
+        Ok(ethrex_rpc::map_engine_requests(req, context).await?)
+    } else {
+        Err(RpcErr::MethodNotFound(req.method.clone()).into())
+    }
+}
+
+#[derive(Deserialize)]
+struct JwtClaims {
+    iat: u64,
+}
+
+/// Middleware rejecting with `401 Unauthorized` the requests without an `Authorization: Bearer`
+/// token signed with `jwt_secret` using HS256 and issued within [`JWT_MAX_CLOCK_SKEW`] seconds.
+pub async fn authenticate_jwt(
+    State(jwt_secret): State<Bytes>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let token = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match token.map(|token| validate_jwt(&jwt_secret, token)) {
+        Some(Ok(())) => next.run(request).await,
+        Some(Err(error)) => {
+            tracing::debug!("Rejected an Auth-RPC request: {}", error);
+            StatusCode::UNAUTHORIZED.into_response()
+        }
+        None => StatusCode::UNAUTHORIZED.into_response(),
+    }
+}
+
+fn validate_jwt(jwt_secret: &[u8], token: &str) -> Result<(), String> {
+    let mut validation = Validation::new(Algorithm::HS256);
+    // The engine API tokens only carry an `iat` claim.
+    validation.validate_exp = false;
+    validation.required_spec_claims.clear();
+    let claims = jsonwebtoken::decode::<JwtClaims>(
+        token,
+        &DecodingKey::from_secret(jwt_secret),
+        &validation,
+    )
+    .map_err(|error| error.to_string())?
+    .claims;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|error| error.to_string())?
+        .as_secs();
+    if now.abs_diff(claims.iat) > JWT_MAX_CLOCK_SKEW {
+        return Err(format!(
+            "token issued at {} is too far from now",
+            claims.iat
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::header::{ACCESS_CONTROL_ALLOW_ORIGIN, ORIGIN};
+    use ethrex_blockchain::Blockchain;
+    use ethrex_common::{
+        Address, Bytes, H256, H512, U256,
+        types::{ChainConfig, Genesis},
+    };
+    use ethrex_p2p::{
+        peer_handler::PeerHandler,
+        sync_manager::SyncManager,
+        types::{Node, NodeRecord},
+    };
+    use ethrex_rpc::{GasTipEstimator, NodeData};
+    use ethrex_storage::EngineType;
+    use std::{collections::BTreeMap, io, net::Ipv4Addr};
+    use tower::ServiceExt;
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn logging_config() -> RpcLoggingConfig {
+        RpcLoggingConfig {
+            default_sample_rate: 0.0,
+            method_sample_rates: HashMap::from([
+                ("eth_getLogs".to_owned(), 1.0),
+                ("eth_blockNumber".to_owned(), 0.01),
+            ]),
+        }
+    }
+
+    #[tokio::test]
+    async fn chain_config_is_the_one_of_the_store() {
+        let genesis = Genesis {
+            config: ChainConfig {
+                chain_id: 1729,
+                london_block: Some(0),
+                shanghai_time: Some(0),
+                ..Default::default()
+            },
+            alloc: BTreeMap::new(),
+            coinbase: Address::zero(),
+            difficulty: U256::zero(),
+            extra_data: Bytes::new(),
+            gas_limit: 30_000_000,
+            nonce: 0,
+            mix_hash: H256::zero(),
+            timestamp: 0,
+            base_fee_per_gas: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            requests_hash: None,
+        };
+        let store = Store::new("", EngineType::InMemory).unwrap();
+        store.add_initial_state(genesis).await.unwrap();
+
+        let chain_config = get_chain_config(&store).unwrap();
+
+        assert_eq!(chain_config["chainId"], 1729);
+        assert_eq!(chain_config["shanghaiTime"], 0);
+        assert_eq!(
+            chain_config,
+            serde_json::to_value(store.get_chain_config().unwrap()).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn log_queries_over_the_caps_wait_or_are_rejected() {
+        let limiter = LogQueryLimiter::new(&RpcConfig {
+            max_concurrent_log_queries: 2,
+            max_queued_log_queries: 2,
+            ..RpcConfig::default()
+        });
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let queries: Vec<_> = (0..6)
+            .map(|_| {
+                let limiter = limiter.clone();
+                let running = running.clone();
+                let peak = peak.clone();
+                tokio::spawn(async move {
+                    limiter
+                        .run(async {
+                            let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                            peak.fetch_max(now_running, Ordering::SeqCst);
+                            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                            running.fetch_sub(1, Ordering::SeqCst);
+                            Ok(Value::Array(Vec::new()))
+                        })
+                        .await
+                })
+            })
+            .collect();
+        let mut results = Vec::new();
+        for query in queries {
+            results.push(query.await.unwrap());
+        }
+
+        // 2 queries ran at once, 2 waited for them and the last 2 were rejected.
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 4);
+        let rejected = results.into_iter().find_map(Result::err).unwrap();
+        assert!(matches!(rejected, RpcError::LimitExceeded(_)));
+        let response = rpc_response(RpcRequestId::Number(1), Err(rejected)).unwrap();
+        assert_eq!(response["error"]["code"], LIMIT_EXCEEDED_CODE);
+
+        // The admissions were all released.
+        assert!(limiter.run(async { Ok(Value::Null) }).await.is_ok());
+    }
+
+    #[test]
+    fn filter_reservations_count_towards_the_caps() {
+        let active_filters: ActiveFilters = Arc::new(Mutex::new(HashMap::new()));
+        let config = RpcConfig {
+            max_active_filters: 3,
+            max_active_filters_per_client: 2,
+            ..RpcConfig::default()
+        };
+        let quotas = FilterQuotas::default();
+        let client = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let other_client = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+
+        // Filters being created count as much as the installed ones.
+        let first = quotas.reserve(&active_filters, client, &config).unwrap();
+        let second = quotas.reserve(&active_filters, client, &config).unwrap();
+        assert!(matches!(
+            quotas.reserve(&active_filters, client, &config),
+            Err(RpcError::LimitExceeded(_))
+        ));
+        let third = quotas
+            .reserve(&active_filters, other_client, &config)
+            .unwrap();
+        assert!(matches!(
+            quotas.reserve(&active_filters, other_client, &config),
+            Err(RpcError::LimitExceeded(_))
+        ));
+
+        // A filter whose creation failed releases its reservation.
+        drop(second);
+        quotas.reserve(&active_filters, client, &config).unwrap();
+        drop((first, third));
+    }
+
+    #[test]
+    fn malformed_batch_entries_are_parsed_on_their_own() {
+        let body = r#"[
+            {"jsonrpc":"2.0","id":1,"method":"eth_chainId"},
+            {"jsonrpc":"2.0","id":2,"method":5},
+            "not a request"
+        ]"#;
+        let Ok(RpcRequestBody::Batch(entries)) = RpcRequestBody::parse(body, 3) else {
+            panic!("expected a batch");
+        };
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].as_ref().unwrap().method, "eth_chainId");
+        assert!(matches!(
+            &entries[1],
+            Err((RpcRequestId::Number(2), RpcErr::BadParams(_)))
+        ));
+        assert!(matches!(&entries[2], Err((RpcRequestId::String(id), _)) if id.is_empty()));
+        assert!(RpcRequestBody::parse("[{", 3).is_err());
+    }
+
+    #[test]
+    fn batch_over_the_maximum_size_is_rejected() {
+        let request = r#"{"jsonrpc":"2.0","id":1,"method":"eth_chainId"}"#;
+        let batch = |len: usize| format!("[{}]", vec![request; len].join(","));
+
+        let Ok(RpcRequestBody::Batch(entries)) = RpcRequestBody::parse(&batch(3), 3) else {
+            panic!("expected a batch");
+        };
+        assert_eq!(entries.len(), 3);
+        let Err(RpcError::LimitExceeded(message)) = RpcRequestBody::parse(&batch(4), 3) else {
+            panic!("expected the batch to be rejected");
+        };
+        assert!(message.contains("exceeds the maximum of 3"));
+        assert!(matches!(
+            RpcRequestBody::parse(request, 0),
+            Ok(RpcRequestBody::Single(_))
+        ));
+    }
+
+    #[test]
+    fn empty_batch_is_an_invalid_request() {
+        let error = RpcRequestBody::parse("[]", 3).unwrap_err();
+        assert!(matches!(error, RpcError::InvalidRequest(_)));
+
+        let response = rpc_response(RpcRequestId::String(String::new()), Err(error)).unwrap();
+        assert!(!response.is_array());
+        assert_eq!(response["error"]["code"], INVALID_REQUEST_CODE);
+    }
+
+    #[test]
+    fn get_proof_storage_keys_are_validated() {
+        let request = |storage_keys: Value| RpcRequest {
+            id: RpcRequestId::Number(1),
+            jsonrpc: "2.0".to_owned(),
+            method: "eth_getProof".to_owned(),
+            params: Some(vec![
+                Value::String(format!("0x{}", "00".repeat(20))),
+                storage_keys,
+                Value::String("latest".to_owned()),
+            ]),
+        };
+        let key = Value::String(format!("0x{}", "ab".repeat(32)));
+
+        assert!(validate_get_proof(&request(Value::Array(vec![key.clone()])), 1).is_ok());
+        assert!(validate_get_proof(&request(Value::Array(vec![key.clone(); 2])), 1).is_err());
+        for invalid in [
+            "0x1234".to_owned(),
+            "ab".repeat(32),
+            format!("0x{}", "zz".repeat(32)),
+        ] {
+            let keys = Value::Array(vec![Value::String(invalid)]);
+            assert!(validate_get_proof(&request(keys), 1).is_err());
+        }
+        assert!(validate_get_proof(&request(key), 1).is_err());
+    }
+
+    #[tokio::test]
+    async fn peers_are_only_returned_to_admins() {
+        let peer_table = ethrex_p2p::network::peer_table(H512::zero());
+        let peer = Node::new(
+            IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+            30303,
+            30303,
+            H512::from_low_u64_be(1),
+        );
+        peer_table.lock().await.insert_node(peer.clone());
+
+        let mut config = RpcConfig::default();
+        let result = get_peers(&peer_table, &config).await;
+        assert!(matches!(result, Err(RpcErr::MethodNotFound(_))));
+
+        config.admin_enabled = true;
+        let peers = get_peers(&peer_table, &config).await.unwrap();
+        let peers = peers.as_array().unwrap();
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0]["enode"], peer.enode_url());
+        assert_eq!(peers[0]["ip"], "10.0.0.1");
+        assert_eq!(peers[0]["direction"], "outbound");
+        assert!(peers[0]["lastSeen"].is_u64());
+    }
+
+    #[test]
+    fn requests_are_sampled_per_method() {
+        let logger = RequestLogger::new(logging_config());
+        let logged = |method: &str| (0..200).filter(|_| logger.should_log(method)).count();
+
+        assert_eq!(logged("eth_getLogs"), 200);
+        assert_eq!(logged("eth_blockNumber"), 2);
+        assert_eq!(logged("eth_chainId"), 0);
+    }
+
+    #[tokio::test]
+    async fn middleware_logs_sampled_requests() {
+        let logs = CapturedLogs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_ansi(false)
+            .with_writer({
+                let logs = logs.clone();
+                move || logs.clone()
+            })
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let router = Router::new()
+            .route("/", post(|| async { "ok" }))
+            .layer(from_fn_with_state(
+                RequestLogger::new(logging_config()),
+                log_requests,
+            ));
+        for method in ["eth_getLogs", "eth_blockNumber", "eth_chainId"] {
+            for _ in 0..100 {
+                let body = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"{method}"}}"#);
+                let request = Request::post("/").body(Body::from(body)).unwrap();
+                let response = router.clone().oneshot(request).await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+            }
+        }
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let logged = |method: &str| logs.matches(&format!("method=\"{method}\"")).count();
+        assert_eq!(logged("eth_getLogs"), 100);
+        assert_eq!(logged("eth_blockNumber"), 1);
+        assert_eq!(logged("eth_chainId"), 0);
+        assert!(logs.contains("latency_ms="));
+    }
+
+    #[tokio::test]
+    async fn authrpc_requests_need_a_valid_jwt() {
+        let jwt_secret = Bytes::from_static(&[7; 32]);
+        let router = Router::new()
+            .route("/", post(|| async { "ok" }))
+            .layer(from_fn_with_state(jwt_secret.clone(), authenticate_jwt));
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let token = |secret: &[u8], iat: u64| {
+            jsonwebtoken::encode(
+                &jsonwebtoken::Header::new(Algorithm::HS256),
+                &json!({ "iat": iat }),
+                &jsonwebtoken::EncodingKey::from_secret(secret),
+            )
+            .unwrap()
+        };
+        let status = |authorization: Option<String>| {
+            let router = router.clone();
+            async move {
+                let mut request = Request::post("/");
+                if let Some(authorization) = authorization {
+                    request = request.header(AUTHORIZATION, authorization);
+                }
+                let request = request.body(Body::from("{}")).unwrap();
+                router.oneshot(request).await.unwrap().status()
+            }
+        };
+
+        assert_eq!(status(None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            status(Some("Bearer not-a-jwt".to_owned())).await,
+            StatusCode::UNAUTHORIZED
+        );
+        let other_secret = token(&[8; 32], now);
+        assert_eq!(
+            status(Some(format!("Bearer {other_secret}"))).await,
+            StatusCode::UNAUTHORIZED
+        );
+        let stale = token(&jwt_secret, now - 2 * JWT_MAX_CLOCK_SKEW);
+        assert_eq!(
+            status(Some(format!("Bearer {stale}"))).await,
+            StatusCode::UNAUTHORIZED
+        );
+
+        let valid = token(&jwt_secret, now);
+        assert_eq!(
+            status(Some(format!("Bearer {valid}"))).await,
+            StatusCode::OK
+        );
+    }
+
+    fn l1_context() -> L1Context {
+        let store = Store::new("", EngineType::InMemory).unwrap();
+        L1Context {
+            blockchain: Arc::new(Blockchain::default_with_store(store.clone())),
+            storage: store,
+            active_filters: Arc::new(Mutex::new(HashMap::new())),
+            syncer: Arc::new(SyncManager::dummy()),
+            peer_handler: PeerHandler::dummy(),
+            node_data: NodeData {
+                jwt_secret: Bytes::new(),
+                local_p2p_node: Node::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0, 0, H512::zero()),
+                local_node_record: NodeRecord {
+                    signature: H512::zero(),
+                    seq: 0,
+                    pairs: vec![],
+                },
+                client_version: "test".to_owned(),
+            },
+            gas_tip_estimator: Arc::new(tokio::sync::Mutex::new(GasTipEstimator::new())),
+        }
+    }
+
+    #[tokio::test]
+    async fn authrpc_requests_are_bounded_before_their_authentication() {
+        let config = RpcConfig {
+            max_request_bytes: 1024,
+            ..RpcConfig::default()
+        };
+        let router = authrpc_router(l1_context(), &config);
+
+        let request = Request::post("/")
+            .header(CONTENT_LENGTH, 100 * 1024 * 1024)
+            .body(Body::from("{}"))
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let request = Request::post("/").body(Body::from("{}")).unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn authrpc_batch_entries_are_answered_individually() {
+        let state = AuthRpcState {
+            context: l1_context(),
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+        };
+        let body = r#"[
+            {"jsonrpc":"2.0","id":1,"method":"eth_chainId"},
+            {"jsonrpc":"2.0","id":2,"method":5}
+        ]"#;
+
+        let Json(responses) = handle_authrpc_request(State(state.clone()), body.to_owned())
+            .await
+            .unwrap();
+        let responses = responses.as_array().unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["id"], 1);
+        assert!(responses[0].get("error").is_some());
+        assert_eq!(responses[1]["id"], 2);
+        assert!(responses[1].get("error").is_some());
+
+        let Json(response) = handle_authrpc_request(State(state), "[]".to_owned())
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], INVALID_REQUEST_CODE);
+    }
+
+    #[tokio::test]
+    async fn oversized_request_is_rejected_before_its_body_is_read() {
+        let handled = Arc::new(AtomicUsize::new(0));
+        let router = Router::new()
+            .route(
+                "/",
+                post({
+                    let handled = handled.clone();
+                    move |_body: String| async move {
+                        handled.fetch_add(1, Ordering::SeqCst);
+                        "ok"
+                    }
+                }),
+            )
+            .layer(from_fn_with_state(1024, reject_oversized_requests));
+
+        // A body that never arrives, so that reading it would hang.
+        let body = Body::from_stream(futures::stream::pending::<Result<Bytes, io::Error>>());
+        let request = Request::post("/")
+            .header(CONTENT_LENGTH, 100 * 1024 * 1024)
+            .body(body)
+            .unwrap();
+        let response = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            router.clone().oneshot(request),
+        )
+        .await
+        .expect("the request is rejected without reading its body")
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(handled.load(Ordering::SeqCst), 0);
+
+        let request = Request::post("/")
+            .header(CONTENT_LENGTH, 2)
+            .body(Body::from("{}"))
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(handled.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn request_body_over_the_limit_is_rejected() {
+        let router = Router::new()
+            .route(
+                "/",
+                post(|body: String| async move { body.len().to_string() }),
+            )
+            .layer(from_fn_with_state(
+                RequestLogger::new(logging_config()),
+                log_requests,
+            ))
+            .layer(from_fn_with_state(1024, reject_oversized_requests));
+        let request = r#"{"jsonrpc":"2.0","id":1,"method":"eth_blockNumber"}"#;
+        let batch = |len: usize| format!("[{}]", vec![request; len].join(","));
+        // Sent in chunks without a Content-Length, so that only reading the body reveals its size.
+        let chunked = |body: String| {
+            let chunks: Vec<Result<Bytes, io::Error>> = body
+                .into_bytes()
+                .chunks(100)
+                .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+                .collect();
+            Request::post("/")
+                .body(Body::from_stream(futures::stream::iter(chunks)))
+                .unwrap()
+        };
+
+        let response = router.clone().oneshot(chunked(batch(100))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let response = router
+            .clone()
+            .oneshot(Request::post("/").body(Body::from(batch(100))).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let response = router.oneshot(chunked(batch(10))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn cors_only_allows_the_configured_origins() {
+        let cors = CorsConfig {
+            allowed_origins: vec!["https://allowed.example".to_owned()],
+            allow_any: false,
+        };
+        let router = Router::new()
+            .route("/", post(|| async { "ok" }))
+            .layer(cors.layer());
+        let allowed_origin = |origin: &str| {
+            let router = router.clone();
+            let request = Request::post("/")
+                .header(ORIGIN, origin)
+                .body(Body::from("{}"))
+                .unwrap();
+            async move {
+                let response = router.oneshot(request).await.unwrap();
+                response
+                    .headers()
+                    .get(ACCESS_CONTROL_ALLOW_ORIGIN)
+                    .map(|value| value.to_str().unwrap().to_owned())
+            }
+        };
+
+        assert_eq!(
+            allowed_origin("https://allowed.example").await.as_deref(),
+            Some("https://allowed.example")
+        );
+        assert_eq!(allowed_origin("https://other.example").await, None);
+
+        let router = Router::new()
+            .route("/", post(|| async { "ok" }))
+            .layer(CorsConfig::default().layer());
+        let request = Request::post("/")
+            .header(ORIGIN, "https://other.example")
+            .body(Body::from("{}"))
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    }
+}
@@ -2,9 +2,14 @@ use std::{
     cmp::Ord,
     collections::{BinaryHeap, HashSet},
     hash::Hash,
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
 };
 use tokio::sync::{Notify, RwLock};
+use tokio_util::sync::CancellationToken;
 
 pub trait UniqueHeapItem<K>
 where
@@ -21,6 +26,8 @@ where
 {
     heap: BinaryHeap<T>,
     keys: HashSet<K>,
+    max_len: Option<usize>,
+    overflow: OverflowPolicy,
 }
 
 impl<T, K> InnerHeap<T, K>
@@ -32,6 +39,8 @@ where
         Self {
             heap: BinaryHeap::new(),
             keys: HashSet::new(),
+            max_len: None,
+            overflow: OverflowPolicy::Reject,
         }
     }
 
@@ -39,8 +48,75 @@ where
         Self {
             heap: BinaryHeap::with_capacity(capacity),
             keys: HashSet::new(),
+            max_len: None,
+            overflow: OverflowPolicy::Reject,
         }
     }
+
+    /// Removes the item with the lowest priority, scanning the whole heap and rebuilding it as
+    /// a max-heap gives no cheaper access to its minimum.
+    fn evict_lowest(&mut self) -> Option<T> {
+        let (index, _) = self.heap.iter().enumerate().min_by(|a, b| a.1.cmp(b.1))?;
+        let mut items = std::mem::take(&mut self.heap).into_vec();
+        let evicted = items.swap_remove(index);
+        self.keys.remove(&evicted.key());
+        self.heap = BinaryHeap::from(items);
+        Some(evicted)
+    }
+
+    /// Whether `keys` holds exactly the keys of the queued items, each item having its own key.
+    #[cfg(debug_assertions)]
+    fn is_consistent(&self) -> bool {
+        let heap_keys: HashSet<K> = self.heap.iter().map(UniqueHeapItem::key).collect();
+        heap_keys.len() == self.heap.len() && heap_keys == self.keys
+    }
+}
+
+/// What a bounded heap does with a push once it holds its maximum number of items.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Refuses the pushed item.
+    #[default]
+    Reject,
+    /// Evicts the item with the lowest priority if the pushed one has a higher priority, and
+    /// refuses the pushed item otherwise. Such a push costs O(n) rather than O(log n), the
+    /// minimum of the heap being searched for and the heap rebuilt without it.
+    EvictLowest,
+}
+
+/// Outcome of [`AsyncUniqueHeap::try_push`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PushResult {
+    Inserted,
+    /// The heap was full, so its lowest priority item was evicted to make room for this one,
+    /// see [`OverflowPolicy::EvictLowest`].
+    Replaced,
+    /// An item with the same key is already queued.
+    Duplicate,
+    /// The heap holds its maximum number of items, see [`AsyncUniqueHeap::with_max_size`].
+    Full,
+}
+
+/// Depth of the heap, readable without taking its lock.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HeapStats {
+    pub len: usize,
+    /// Largest `len` since the heap was created or [`AsyncUniqueHeap::reset_peak`] was called.
+    pub peak_len: usize,
+}
+
+#[derive(Debug, Default)]
+struct Depth {
+    len: AtomicUsize,
+    peak_len: AtomicUsize,
+}
+
+impl Depth {
+    /// Called with the write lock of the heap held, so that the updates are not reordered.
+    fn set(&self, len: usize) {
+        self.len.store(len, Ordering::Relaxed);
+        self.peak_len.fetch_max(len, Ordering::Relaxed);
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -51,6 +127,7 @@ where
 {
     inner: Arc<RwLock<InnerHeap<T, K>>>,
     notify: Arc<Notify>,
+    depth: Arc<Depth>,
 }
 
 impl<T, K> AsyncUniqueHeap<T, K>
@@ -62,6 +139,7 @@ where
         Self {
             inner: Arc::new(RwLock::new(InnerHeap::new())),
             notify: Arc::new(Notify::new()),
+            depth: Arc::default(),
         }
     }
 
@@ -69,19 +147,61 @@ where
         Self {
             inner: Arc::new(RwLock::new(InnerHeap::with_capacity(capacity))),
             notify: Arc::new(Notify::new()),
+            depth: Arc::default(),
         }
     }
 
+    /// Returns a heap holding at most `max_len` items, refusing the pushes beyond.
+    pub fn bounded(max_len: usize) -> Self {
+        Self::with_max_size(max_len, OverflowPolicy::Reject)
+    }
+
+    /// Returns a heap holding at most `max_len` items, handling the pushes beyond following
+    /// `overflow`.
+    pub fn with_max_size(max_len: usize, overflow: OverflowPolicy) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(InnerHeap {
+                max_len: Some(max_len),
+                overflow,
+                ..InnerHeap::new()
+            })),
+            notify: Arc::new(Notify::new()),
+            depth: Arc::default(),
+        }
+    }
+
+    /// Returns whether the item was queued, see [`AsyncUniqueHeap::try_push`] to know why it was
+    /// not.
     pub async fn push(&self, item: T) -> bool {
+        matches!(
+            self.try_push(item).await,
+            PushResult::Inserted | PushResult::Replaced
+        )
+    }
+
+    pub async fn try_push(&self, item: T) -> PushResult {
         let key = item.key();
         let mut inner = self.inner.write().await;
-        if inner.keys.insert(key) {
-            inner.heap.push(item);
-            self.notify.notify_one();
-            true
-        } else {
-            false
+        if inner.keys.contains(&key) {
+            return PushResult::Duplicate;
         }
+        let mut result = PushResult::Inserted;
+        if inner
+            .max_len
+            .is_some_and(|max_len| inner.heap.len() >= max_len)
+        {
+            let outranks_lowest = inner.heap.iter().min().is_some_and(|lowest| item > *lowest);
+            if inner.overflow == OverflowPolicy::Reject || !outranks_lowest {
+                return PushResult::Full;
+            }
+            inner.evict_lowest();
+            result = PushResult::Replaced;
+        }
+        inner.keys.insert(key);
+        inner.heap.push(item);
+        self.depth.set(inner.heap.len());
+        self.notify.notify_one();
+        result
     }
 
     pub async fn pop(&self) -> Option<T> {
@@ -89,12 +209,49 @@ where
         if let Some(item) = inner.heap.pop() {
             let key = item.key();
             inner.keys.remove(&key);
+            self.depth.set(inner.heap.len());
             Some(item)
         } else {
             None
         }
     }
 
+    /// Removes the queued item with the given key, if any.
+    pub async fn remove(&self, key: &K) -> Option<T> {
+        let mut inner = self.inner.write().await;
+        if !inner.keys.remove(key) {
+            return None;
+        }
+        let mut removed = None;
+        inner.heap.retain(|item| {
+            if removed.is_none() && item.key() == *key {
+                removed = Some(item.clone());
+                false
+            } else {
+                true
+            }
+        });
+        self.depth.set(inner.heap.len());
+        removed
+    }
+
+    /// Replaces the queued item with the same key as `item`, reordering the heap according to
+    /// the priority of `item`. Returns whether an item was replaced, `item` not being queued
+    /// otherwise.
+    pub async fn update(&self, item: T) -> bool {
+        let key = item.key();
+        let mut inner = self.inner.write().await;
+        if !inner.keys.contains(&key) {
+            return false;
+        }
+        let mut items = std::mem::take(&mut inner.heap).into_vec();
+        if let Some(stored) = items.iter_mut().find(|stored| stored.key() == key) {
+            *stored = item;
+        }
+        inner.heap = BinaryHeap::from(items);
+        true
+    }
+
     pub async fn pop_wait(&self) -> T {
         loop {
             if let Some(item) = self.pop().await {
@@ -104,6 +261,37 @@ where
         }
     }
 
+    /// Like [`AsyncUniqueHeap::pop_wait`], giving up once no item was pushed for `timeout`.
+    pub async fn pop_wait_timeout(&self, timeout: Duration) -> Option<T> {
+        tokio::time::timeout(timeout, self.pop_wait()).await.ok()
+    }
+
+    /// Like [`AsyncUniqueHeap::pop_wait`], giving up as soon as `token` is cancelled, e.g. to
+    /// stop a processing task on shutdown without waiting for the next item.
+    pub async fn pop_wait_cancellable(&self, token: &CancellationToken) -> Option<T> {
+        token.run_until_cancelled(self.pop_wait()).await
+    }
+
+    /// Returns a copy of the queued items in the order they would be popped, leaving the heap
+    /// untouched.
+    pub async fn snapshot_sorted(&self) -> Vec<T> {
+        let inner = self.inner.read().await;
+        let mut items = inner.heap.clone().into_sorted_vec();
+        items.reverse();
+        items
+    }
+
+    /// Empties the heap at once and returns its items in the order they would have been
+    /// popped, their keys becoming free to be pushed again.
+    pub async fn drain(&self) -> Vec<T> {
+        let mut inner = self.inner.write().await;
+        let mut items = std::mem::take(&mut inner.heap).into_sorted_vec();
+        inner.keys.clear();
+        self.depth.set(0);
+        items.reverse();
+        items
+    }
+
     pub async fn peek(&self) -> Option<T> {
         let inner = self.inner.read().await;
         inner.heap.peek().cloned()
@@ -118,6 +306,39 @@ where
         let inner = self.inner.read().await;
         inner.heap.is_empty()
     }
+
+    /// Current and peak depth of the heap, e.g. for metrics, without waiting for its lock.
+    pub fn stats(&self) -> HeapStats {
+        HeapStats {
+            len: self.depth.len.load(Ordering::Relaxed),
+            peak_len: self.depth.peak_len.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Checks that the set of keys used to refuse duplicates matches the queued items, which
+    /// deduplication relies on.
+    #[cfg(debug_assertions)]
+    pub async fn audit(&self) -> bool {
+        self.inner.read().await.is_consistent()
+    }
+
+    /// Rebuilds the set of keys from the queued items, dropping the items whose key is already
+    /// queued so that the heap holds unique keys again.
+    pub async fn rebuild_keys(&self) {
+        let mut inner = self.inner.write().await;
+        let mut keys = HashSet::with_capacity(inner.heap.len());
+        inner.heap.retain(|item| keys.insert(item.key()));
+        inner.keys = keys;
+        self.depth.set(inner.heap.len());
+    }
+
+    /// Starts tracking the peak depth again from the current depth.
+    pub async fn reset_peak(&self) {
+        let inner = self.inner.write().await;
+        self.depth
+            .peak_len
+            .store(inner.heap.len(), Ordering::Relaxed);
+    }
 }
 
 impl<T, K> Default for AsyncUniqueHeap<T, K>
@@ -188,6 +409,272 @@ mod tests {
         assert_eq!(heap.len().await, 0);
     }
 
+    #[tokio::test]
+    async fn test_stats_track_the_peak_depth() {
+        let heap = AsyncUniqueHeap::new();
+        assert_eq!(heap.stats(), HeapStats::default());
+
+        for id in 0..3 {
+            heap.push(NumericItem::new(id, id)).await;
+        }
+        // A duplicate is not queued, so it does not change the depth.
+        heap.push(NumericItem::new(0, 0)).await;
+        assert_eq!(
+            heap.stats(),
+            HeapStats {
+                len: 3,
+                peak_len: 3
+            }
+        );
+
+        heap.pop().await;
+        heap.pop().await;
+        assert_eq!(
+            heap.stats(),
+            HeapStats {
+                len: 1,
+                peak_len: 3
+            }
+        );
+
+        heap.push(NumericItem::new(5, 5)).await;
+        assert_eq!(
+            heap.stats(),
+            HeapStats {
+                len: 2,
+                peak_len: 3
+            }
+        );
+
+        heap.reset_peak().await;
+        assert_eq!(
+            heap.stats(),
+            HeapStats {
+                len: 2,
+                peak_len: 2
+            }
+        );
+        heap.pop().await;
+        heap.pop().await;
+        heap.pop().await;
+        assert_eq!(
+            heap.stats(),
+            HeapStats {
+                len: 0,
+                peak_len: 2
+            }
+        );
+
+        // Clones share the same heap, and so the same stats.
+        let clone = heap.clone();
+        clone.push(NumericItem::new(1, 1)).await;
+        assert_eq!(heap.stats().len, 1);
+    }
+
+    #[tokio::test]
+    async fn test_try_push_on_a_bounded_heap() {
+        let heap = AsyncUniqueHeap::bounded(2);
+
+        assert_eq!(
+            heap.try_push(NumericItem::new(1, 1)).await,
+            PushResult::Inserted
+        );
+        assert_eq!(
+            heap.try_push(NumericItem::new(1, 1)).await,
+            PushResult::Duplicate
+        );
+        assert_eq!(
+            heap.try_push(NumericItem::new(2, 2)).await,
+            PushResult::Inserted
+        );
+        assert_eq!(
+            heap.try_push(NumericItem::new(3, 3)).await,
+            PushResult::Full
+        );
+        // A duplicate is reported as such even when the heap is full.
+        assert_eq!(
+            heap.try_push(NumericItem::new(2, 2)).await,
+            PushResult::Duplicate
+        );
+        assert!(!heap.push(NumericItem::new(3, 3)).await);
+        assert_eq!(heap.len().await, 2);
+
+        // Popping frees room for another item.
+        heap.pop().await;
+        assert_eq!(
+            heap.try_push(NumericItem::new(3, 3)).await,
+            PushResult::Inserted
+        );
+        assert!(heap.peek().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_eviction_of_the_lowest_priority_item() {
+        let heap = AsyncUniqueHeap::with_max_size(3, OverflowPolicy::EvictLowest);
+        for id in [5, 1, 3] {
+            assert_eq!(
+                heap.try_push(NumericItem::new(id, id)).await,
+                PushResult::Inserted
+            );
+        }
+
+        // A higher priority item replaces the lowest one, whose key is freed.
+        assert_eq!(
+            heap.try_push(NumericItem::new(4, 4)).await,
+            PushResult::Replaced
+        );
+        assert_eq!(heap.len().await, 3);
+        // An item of lower priority than all the queued ones is refused.
+        assert_eq!(
+            heap.try_push(NumericItem::new(2, 2)).await,
+            PushResult::Full
+        );
+        assert!(heap.push(NumericItem::new(6, 6)).await);
+        assert_eq!(
+            heap.try_push(NumericItem::new(6, 6)).await,
+            PushResult::Duplicate
+        );
+
+        let mut priorities = Vec::new();
+        while let Some(item) = heap.pop().await {
+            priorities.push(item.priority);
+        }
+        assert_eq!(priorities, vec![6, 5, 4]);
+        // The evicted key can be queued again.
+        assert!(heap.push(NumericItem::new(1, 1)).await);
+    }
+
+    #[tokio::test]
+    async fn test_unbounded_heap_is_never_full() {
+        let heap = AsyncUniqueHeap::new();
+        for id in 0..1_000 {
+            assert_eq!(
+                heap.try_push(NumericItem::new(id, id)).await,
+                PushResult::Inserted
+            );
+        }
+        assert_eq!(heap.len().await, 1_000);
+    }
+
+    #[tokio::test]
+    async fn test_remove() {
+        let heap = AsyncUniqueHeap::new();
+        heap.push(TestItem::new(10, "key1")).await;
+        heap.push(TestItem::new(20, "key2")).await;
+
+        assert_eq!(
+            heap.remove(&"key1".to_string()).await,
+            Some(TestItem::new(10, "key1"))
+        );
+        assert_eq!(heap.remove(&"key1".to_string()).await, None);
+        assert_eq!(heap.len().await, 1);
+        assert_eq!(heap.stats().len, 1);
+
+        // The key of a removed item can be queued again.
+        assert!(heap.push(TestItem::new(30, "key1")).await);
+        assert_eq!(heap.pop().await.map(|item| item.priority), Some(30));
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_sorted_leaves_the_heap_untouched() {
+        let heap = AsyncUniqueHeap::new();
+        for priority in [20, 40, 10, 30] {
+            heap.push(NumericItem::new(priority, priority)).await;
+        }
+
+        let priorities = |items: Vec<NumericItem>| -> Vec<i32> {
+            items.into_iter().map(|item| item.priority).collect()
+        };
+        assert_eq!(
+            priorities(heap.snapshot_sorted().await),
+            vec![40, 30, 20, 10]
+        );
+        assert_eq!(heap.len().await, 4);
+        assert!(!heap.push(NumericItem::new(50, 40)).await);
+        assert_eq!(heap.pop().await.map(|item| item.priority), Some(40));
+    }
+
+    #[tokio::test]
+    async fn test_drain_frees_the_keys() {
+        let heap = AsyncUniqueHeap::new();
+        for priority in [20, 40, 10] {
+            heap.push(NumericItem::new(priority, priority)).await;
+        }
+
+        let drained: Vec<_> = heap.drain().await.into_iter().map(|item| item.id).collect();
+        assert_eq!(drained, vec![40, 20, 10]);
+        assert!(heap.is_empty().await);
+        assert_eq!(heap.stats().len, 0);
+        assert!(heap.drain().await.is_empty());
+
+        for id in drained {
+            assert!(heap.push(NumericItem::new(0, id)).await);
+        }
+        assert_eq!(heap.len().await, 3);
+    }
+
+    #[tokio::test]
+    async fn test_update_reorders_the_heap() {
+        let heap = AsyncUniqueHeap::new();
+        heap.push(NumericItem::new(10, 1)).await;
+        heap.push(NumericItem::new(20, 2)).await;
+        heap.push(NumericItem::new(30, 3)).await;
+
+        assert!(heap.update(NumericItem::new(40, 1)).await);
+        assert_eq!(heap.peek().await, Some(NumericItem::new(40, 1)));
+        assert!(heap.update(NumericItem::new(5, 3)).await);
+        // An item whose key is not queued is not inserted.
+        assert!(!heap.update(NumericItem::new(50, 4)).await);
+        assert_eq!(heap.len().await, 3);
+
+        let mut order = Vec::new();
+        while let Some(item) = heap.pop().await {
+            order.push((item.priority, item.id));
+        }
+        assert_eq!(order, vec![(40, 1), (20, 2), (5, 3)]);
+    }
+
+    #[cfg(debug_assertions)]
+    #[tokio::test]
+    async fn test_keys_stay_consistent_with_the_heap() {
+        let heap = AsyncUniqueHeap::bounded(4);
+        assert!(heap.audit().await);
+
+        for id in 0..6 {
+            heap.try_push(NumericItem::new(id, id)).await;
+        }
+        heap.push(NumericItem::new(7, 1)).await;
+        assert!(heap.audit().await);
+        heap.pop().await;
+        heap.remove(&0).await;
+        heap.remove(&42).await;
+        assert!(heap.audit().await);
+        heap.push(NumericItem::new(0, 0)).await;
+        heap.pop().await;
+        assert!(heap.audit().await);
+    }
+
+    #[cfg(debug_assertions)]
+    #[tokio::test]
+    async fn test_rebuild_keys_repairs_drifted_keys() {
+        let heap = AsyncUniqueHeap::new();
+        heap.push(NumericItem::new(1, 1)).await;
+        heap.push(NumericItem::new(2, 2)).await;
+        {
+            let mut inner = heap.inner.write().await;
+            inner.keys.remove(&1);
+            inner.keys.insert(3);
+            inner.heap.push(NumericItem::new(5, 2));
+        }
+        assert!(!heap.audit().await);
+
+        heap.rebuild_keys().await;
+        assert!(heap.audit().await);
+        assert_eq!(heap.len().await, 2);
+        assert!(!heap.push(NumericItem::new(1, 1)).await);
+        assert!(heap.push(NumericItem::new(3, 3)).await);
+    }
+
     #[tokio::test]
     async fn test_with_capacity() {
         let heap: AsyncUniqueHeap<TestItem, String> = AsyncUniqueHeap::with_capacity(10);
@@ -355,6 +842,47 @@ mod tests {
         push_task.await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_pop_wait_timeout() {
+        let heap = Arc::new(AsyncUniqueHeap::new());
+        assert_eq!(heap.pop_wait_timeout(Duration::from_millis(20)).await, None);
+
+        let heap_clone = heap.clone();
+        let push_task = tokio::spawn(async move {
+            sleep(Duration::from_millis(20)).await;
+            heap_clone.push(TestItem::new(42, "delayed_key")).await;
+        });
+        let result = heap.pop_wait_timeout(Duration::from_secs(5)).await;
+        assert_eq!(result.map(|item| item.priority), Some(42));
+        push_task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pop_wait_cancellable() {
+        let heap = Arc::new(AsyncUniqueHeap::new());
+        let token = CancellationToken::new();
+
+        let heap_clone = heap.clone();
+        let push_task = tokio::spawn(async move {
+            sleep(Duration::from_millis(20)).await;
+            heap_clone.push(TestItem::new(42, "delayed_key")).await;
+        });
+        let result = heap.pop_wait_cancellable(&token).await;
+        assert_eq!(result.map(|item| item.priority), Some(42));
+        push_task.await.unwrap();
+
+        let cancel_token = token.clone();
+        let cancel_task = tokio::spawn(async move {
+            sleep(Duration::from_millis(20)).await;
+            cancel_token.cancel();
+        });
+        assert_eq!(heap.pop_wait_cancellable(&token).await, None);
+        cancel_task.await.unwrap();
+        // The heap stays usable once the wait was given up.
+        heap.push(TestItem::new(1, "key")).await;
+        assert_eq!(heap.len().await, 1);
+    }
+
     #[tokio::test]
     async fn test_complex_scenario() {
         let heap = AsyncUniqueHeap::new();